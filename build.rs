@@ -23,8 +23,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create src/generated directory if it doesn't exist
     std::fs::create_dir_all("src/generated")?;
 
+    // Server stubs are always generated but only compiled with the `mock-server`
+    // feature, so the generated file is the same for every feature set
     tonic_prost_build::configure()
-        .build_server(false)
+        .build_server(true)
+        .server_mod_attribute(".", r#"#[cfg(feature = "mock-server")]"#)
         .build_client(true)
         .out_dir("src/generated")
         .compile_protos(
@@ -47,7 +47,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect()
         .await?;
 
-    let mut client = GrokClient::with_channel(channel, api_key.clone());
+    let client = GrokClient::with_channel(channel, api_key.clone());
 
     let request = ChatRequest::new()
         .user_message("Say hello! Keep it brief.")
@@ -94,7 +94,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .connect()
         .await?;
 
-    let mut client = GrokClient::with_channel(channel, api_key);
+    let client = GrokClient::with_channel(channel, api_key);
 
     let request = ChatRequest::new()
         .user_message("Hello with custom CA!")
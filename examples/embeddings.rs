@@ -3,7 +3,7 @@ use xai_grpc_client::{EmbedRequest, GrokClient};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client from XAI_API_KEY environment variable
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     println!("=== Text Embeddings Example ===\n");
 
@@ -3,7 +3,7 @@ use xai_grpc_client::{ChatRequest, GrokClient};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     // Example with a publicly accessible image URL
     let image_url = "https://upload.wikimedia.org/wikipedia/commons/thumb/d/d5/Rust_programming_language_black_logo.svg/1200px-Rust_programming_language_black_logo.svg.png";
@@ -3,7 +3,7 @@ use xai_grpc_client::{ChatRequest, GrokClient};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client from GROK_API_KEY environment variable
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     // Create a simple chat request
     let request = ChatRequest::new()
@@ -17,7 +17,7 @@ use xai_grpc_client::{GrokClient, TokenizeRequest};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client from XAI_API_KEY environment variable
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     // Example 1: Basic tokenization
     println!("=== Example 1: Basic Tokenization ===\n");
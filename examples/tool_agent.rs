@@ -0,0 +1,74 @@
+use serde_json::{json, Value};
+use xai_grpc_client::{ChatRequest, FunctionTool, GrokClient, Message, ToolRegistry};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize client
+    let client = GrokClient::from_env().await?;
+
+    // Register the tools the model may call, each with its Rust handler.
+    // Handlers receive the arguments as JSON and return a JSON result.
+    let registry = ToolRegistry::new()
+        .register(
+            FunctionTool::new("get_weather", "Get the current weather in a location")
+                .with_parameters(json!({
+                    "type": "object",
+                    "properties": {
+                        "location": { "type": "string", "description": "City name" }
+                    },
+                    "required": ["location"]
+                })),
+            |args: Value| async move {
+                // In production, call a real weather API here
+                let location = args["location"].as_str().ok_or("location is required")?;
+                Ok::<_, &str>(json!({
+                    "location": location,
+                    "temperature": 22,
+                    "condition": "Partly cloudy"
+                }))
+            },
+        )
+        .register(
+            FunctionTool::new("get_time", "Get the current UTC time"),
+            |_| async { Ok::<_, String>(json!("2025-01-01T12:00:00Z")) },
+        )
+        .with_max_iterations(5);
+
+    let request = ChatRequest::new()
+        .user_message("What's the weather in Tokyo and London, and what time is it?")
+        .with_model("grok-2-1212")
+        .with_parallel_tool_calls(true);
+
+    // Calls the model, runs the requested tools and feeds the results back
+    // until the model produces its final answer
+    let run = client.run_with_tools(request, &registry).await?;
+
+    println!("=== Transcript ===\n");
+    for message in &run.messages {
+        match message {
+            Message::User(_) => println!("[user]"),
+            Message::Assistant(content) => println!("[assistant] {content}"),
+            Message::AssistantReply {
+                content,
+                tool_calls,
+                ..
+            } => {
+                println!("[assistant] {content}");
+                for call in tool_calls {
+                    println!("  -> {}({})", call.function.name, call.function.arguments);
+                }
+            }
+            Message::Tool { content, .. } => println!("[tool] {content}"),
+            Message::System(content) => println!("[system] {content}"),
+        }
+    }
+
+    println!("\n=== Final answer ===\n");
+    println!("{}", run.response.content);
+    println!(
+        "\n{} requests, {} tokens total",
+        run.iterations, run.usage.total_tokens
+    );
+
+    Ok(())
+}
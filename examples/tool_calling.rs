@@ -4,7 +4,7 @@ use xai_grpc_client::{ChatRequest, FunctionTool, GrokClient, Tool, ToolChoice};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     // Define a function tool
     let get_weather = FunctionTool::new("get_weather", "Get the current weather in a location")
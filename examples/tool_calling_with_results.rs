@@ -4,7 +4,7 @@ use xai_grpc_client::{ChatRequest, FunctionTool, GrokClient, Tool, ToolChoice};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize client
-    let mut client = GrokClient::from_env().await?;
+    let client = GrokClient::from_env().await?;
 
     // Define a weather tool
     let get_weather = FunctionTool::new("get_weather", "Get the current weather in a location")
@@ -62,13 +62,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Step 4: Send tool result back to model
     println!("\n=== Step 3: Send tool result back ===\n");
 
-    // IMPORTANT: Tool results must be provided in the same order as tool calls were received.
-    // xAI's gRPC API matches results to calls by message order, not by ID.
+    // IMPORTANT: Tool results should be provided in the same order as tool calls were received.
+    // The call ID is sent along, but xAI's gRPC API falls back to matching by message order.
     // NOTE: This example handles a single tool call scenario. For multiple tool calls,
     // iterate through all calls and provide results in the same order.
+    // (See the `tool_agent` example for an automated loop using `ToolRegistry`.)
     let follow_up_request = ChatRequest::new()
         .user_message("What's the weather in Tokyo?")
-        .assistant_message(&response.content) // Include the assistant's tool call message
+        .assistant_response(&response) // Include the assistant's message with its tool calls
         // Safe: we verified tool_calls is non-empty at line 38
         .tool_result(&response.tool_calls[0].id, tool_result.to_string())
         .with_model("grok-2-1212");
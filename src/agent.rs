@@ -0,0 +1,469 @@
+//! Automatic tool execution.
+//!
+//! A [`ToolRegistry`] maps [`FunctionTool`] definitions to async Rust handlers.
+//! [`GrokClient::run_with_tools`] then drives the conversation: it sends the
+//! request, executes every client-side [`ToolCall`] the model makes, appends the
+//! results and re-submits until the model stops asking for tools.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{ChatRequest, FunctionTool, GrokClient, ToolRegistry};
+//! use serde_json::{json, Value};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//!
+//! let registry = ToolRegistry::new().register(
+//!     FunctionTool::new("get_weather", "Get the current weather in a location")
+//!         .with_parameters(json!({
+//!             "type": "object",
+//!             "properties": { "location": { "type": "string" } },
+//!             "required": ["location"]
+//!         })),
+//!     |args: Value| async move {
+//!         let location = args["location"].as_str().unwrap_or("unknown");
+//!         Ok::<_, String>(json!({ "location": location, "temperature": 22 }))
+//!     },
+//! );
+//!
+//! let request = ChatRequest::new().user_message("What's the weather in Tokyo?");
+//! let run = client.run_with_tools(request, &registry).await?;
+//!
+//! println!("{}", run.response.content);
+//! println!("Used {} tokens over {} rounds", run.usage.total_tokens, run.iterations);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::GrokClient,
+    error::Result,
+    request::{ChatRequest, Message},
+    response::{ChatResponse, TokenUsage},
+    tools::{FunctionTool, Tool, ToolCall, ToolCallKind},
+};
+use serde_json::Value;
+use std::{collections::HashMap, fmt, future::Future, pin::Pin, sync::Arc};
+
+/// Default maximum number of model round-trips for [`GrokClient::run_with_tools`].
+pub const DEFAULT_MAX_ITERATIONS: u32 = 10;
+
+/// Boxed future returned by a registered tool handler.
+type HandlerFuture = Pin<Box<dyn Future<Output = std::result::Result<Value, String>> + Send>>;
+
+/// Type-erased tool handler.
+type Handler = Arc<dyn Fn(Value) -> HandlerFuture + Send + Sync>;
+
+/// Registry of client-side tools and the Rust handlers that execute them.
+///
+/// Each handler receives the call arguments parsed as JSON and returns either a
+/// JSON result or an error. Both are sent back to the model as the tool result:
+/// string values are sent as-is, other values are serialized to JSON, and errors
+/// are wrapped as `{"error": "..."}` so the model can recover from them.
+///
+/// The registry is cheap to clone and can be shared across tasks.
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: Vec<FunctionTool>,
+    handlers: HashMap<String, Handler>,
+    max_iterations: u32,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self {
+            tools: Vec::new(),
+            handlers: HashMap::new(),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.names().collect::<Vec<_>>())
+            .field("max_iterations", &self.max_iterations)
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a function tool together with the handler that executes it.
+    ///
+    /// Registering a tool with the same name again replaces the previous one.
+    pub fn register<F, Fut, E>(mut self, tool: FunctionTool, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Value, E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        let handler: Handler = Arc::new(move |args| {
+            let future = handler(args);
+            Box::pin(async move { future.await.map_err(|e| e.to_string()) })
+        });
+
+        self.tools.retain(|existing| existing.name != tool.name);
+        self.handlers.insert(tool.name.clone(), handler);
+        self.tools.push(tool);
+        self
+    }
+
+    /// Set the maximum number of model round-trips (default: 10).
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Maximum number of model round-trips.
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    /// Names of the registered tools, in registration order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tools.iter().map(|tool| tool.name.as_str())
+    }
+
+    /// Returns true if a handler is registered for `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Tool definitions to send to the model.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tools.iter().cloned().map(Tool::Function).collect()
+    }
+
+    /// Execute a single tool call and return the content to send back to the model.
+    ///
+    /// Unknown tools, malformed arguments and handler errors never fail; they are
+    /// reported to the model as `{"error": "..."}` instead.
+    pub async fn execute(&self, call: &ToolCall) -> String {
+        match self.start(call) {
+            Ok(future) => render(future.await),
+            Err(error) => render(Err(error)),
+        }
+    }
+
+    /// Look up the handler for `call` and start it.
+    fn start(&self, call: &ToolCall) -> std::result::Result<HandlerFuture, String> {
+        let handler = self
+            .handlers
+            .get(&call.function.name)
+            .ok_or_else(|| format!("Unknown tool: {}", call.function.name))?;
+
+        // Models send "" for functions without parameters
+        let args = if call.function.arguments.trim().is_empty() {
+            Value::Object(Default::default())
+        } else {
+            call.function
+                .arguments_json()
+                .map_err(|e| format!("Invalid arguments for {}: {e}", call.function.name))?
+        };
+
+        Ok(handler(args))
+    }
+
+    /// Execute `calls` concurrently, returning results in the same order.
+    async fn execute_parallel(&self, calls: &[&ToolCall]) -> Vec<String> {
+        let tasks: Vec<_> = calls
+            .iter()
+            .map(|call| self.start(call).map(tokio::spawn))
+            .collect();
+
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let result = match task {
+                Ok(handle) => match handle.await {
+                    Ok(result) => result,
+                    Err(e) => Err(format!("Tool handler failed: {e}")),
+                },
+                Err(error) => Err(error),
+            };
+            results.push(render(result));
+        }
+        results
+    }
+}
+
+/// Turn a handler outcome into tool result content.
+fn render(result: std::result::Result<Value, String>) -> String {
+    match result {
+        Ok(Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(error) => serde_json::json!({ "error": error }).to_string(),
+    }
+}
+
+/// Outcome of [`GrokClient::run_with_tools`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToolRun {
+    /// The full conversation: the original messages followed by every assistant
+    /// reply (as [`Message::AssistantReply`], including its tool calls) and tool
+    /// result, ending with the final assistant reply.
+    pub messages: Vec<Message>,
+    /// The last response returned by the model.
+    ///
+    /// If `iterations` reached the registry's limit, its `finish_reason` may
+    /// still be [`FinishReason::ToolCalls`](crate::FinishReason::ToolCalls).
+    pub response: ChatResponse,
+    /// Token usage summed over every request made.
+    pub usage: TokenUsage,
+    /// Number of requests sent to the model.
+    pub iterations: u32,
+}
+
+impl GrokClient {
+    /// Run a chat request, automatically executing tool calls with `registry`.
+    ///
+    /// The registry's tools are added to the request (tools already present with
+    /// the same name are kept as-is). Whenever the model answers with client-side
+    /// tool calls, they are executed and their results appended in call order,
+    /// then the conversation is re-submitted. The loop ends when the model stops
+    /// requesting tools (normally with [`FinishReason::Stop`](crate::FinishReason::Stop))
+    /// or after [`ToolRegistry::max_iterations`] requests.
+    ///
+    /// Tool calls from the same response run concurrently when the request has
+    /// `parallel_tool_calls` enabled, and one after the other otherwise.
+    /// Server-side tools (web search, code execution, ...) are executed by xAI
+    /// and are not dispatched to the registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from [`complete_chat`](Self::complete_chat).
+    /// Tool failures are reported to the model rather than returned.
+    pub async fn run_with_tools(
+        &self,
+        request: ChatRequest,
+        registry: &ToolRegistry,
+    ) -> Result<ToolRun> {
+        let mut request = request;
+        for tool in registry.tools() {
+            let Tool::Function(function) = &tool else {
+                continue;
+            };
+            let already_present = request.tools().is_some_and(|tools| {
+                tools
+                    .iter()
+                    .any(|t| matches!(t, Tool::Function(f) if f.name == function.name))
+            });
+            if !already_present {
+                request = request.add_tool(tool);
+            }
+        }
+
+        let parallel = request.parallel_tool_calls().unwrap_or(false);
+        let mut usage = TokenUsage::default();
+        let mut iterations = 0;
+
+        loop {
+            let response = self.complete_chat(request.clone()).await?;
+            iterations += 1;
+            usage += &response.usage;
+            request = request.assistant_response(&response);
+
+            let calls: Vec<&ToolCall> = response
+                .tool_calls
+                .iter()
+                .filter(|call| call.call_type == ToolCallKind::ClientSideTool)
+                .collect();
+
+            if calls.is_empty() || iterations >= registry.max_iterations {
+                return Ok(ToolRun {
+                    messages: request.messages().to_vec(),
+                    response,
+                    usage,
+                    iterations,
+                });
+            }
+
+            // Results must follow the order of the calls: xAI pairs them by position
+            let results = if parallel {
+                registry.execute_parallel(&calls).await
+            } else {
+                let mut results = Vec::with_capacity(calls.len());
+                for call in &calls {
+                    results.push(registry.execute(call).await);
+                }
+                results
+            };
+
+            for (call, result) in calls.iter().zip(results) {
+                request = request.tool_result(&call.id, result);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{FunctionCall, ToolCallStatusKind};
+    use serde_json::json;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: format!("call_{name}"),
+            call_type: ToolCallKind::ClientSideTool,
+            status: ToolCallStatusKind::Completed,
+            error_message: None,
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    fn registry() -> ToolRegistry {
+        ToolRegistry::new()
+            .register(
+                FunctionTool::new("add", "Add two numbers"),
+                |args| async move {
+                    let a = args["a"].as_i64().ok_or("missing a")?;
+                    let b = args["b"].as_i64().ok_or("missing b")?;
+                    Ok::<_, &str>(json!(a + b))
+                },
+            )
+            .register(FunctionTool::new("greet", "Say hello"), |_| async {
+                Ok::<_, String>(json!("hello"))
+            })
+    }
+
+    #[test]
+    fn test_register_and_lookup() {
+        let registry = registry();
+
+        assert!(registry.contains("add"));
+        assert!(!registry.contains("sub"));
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["add", "greet"]);
+        assert_eq!(registry.tools().len(), 2);
+        assert_eq!(registry.max_iterations(), DEFAULT_MAX_ITERATIONS);
+    }
+
+    #[test]
+    fn test_register_replaces_existing_tool() {
+        let registry = registry()
+            .register(FunctionTool::new("add", "Add numbers, again"), |_| async {
+                Ok::<_, String>(json!(0))
+            });
+
+        assert_eq!(registry.tools().len(), 2);
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["greet", "add"]);
+    }
+
+    #[test]
+    fn test_max_iterations_at_least_one() {
+        assert_eq!(
+            ToolRegistry::new().with_max_iterations(0).max_iterations(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_success() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.execute(&call("add", r#"{"a": 2, "b": 3}"#)).await,
+            "5"
+        );
+        // String results are sent verbatim, empty arguments are treated as {}
+        assert_eq!(registry.execute(&call("greet", "")).await, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_execute_errors_are_reported_to_model() {
+        let registry = registry();
+
+        let result = registry.execute(&call("add", r#"{"a": 2}"#)).await;
+        assert_eq!(result, r#"{"error":"missing b"}"#);
+
+        let result = registry.execute(&call("unknown", "{}")).await;
+        assert!(result.contains("Unknown tool: unknown"));
+
+        let result = registry.execute(&call("add", "not json")).await;
+        assert!(result.contains("Invalid arguments for add"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_parallel_preserves_order() {
+        let registry = ToolRegistry::new().register(
+            FunctionTool::new("sleep", "Sleep then echo"),
+            |args| async move {
+                let ms = args["ms"].as_u64().unwrap_or(0);
+                tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+                Ok::<_, String>(json!(ms))
+            },
+        );
+
+        let slow = call("sleep", r#"{"ms": 30}"#);
+        let fast = call("sleep", r#"{"ms": 1}"#);
+        let missing = call("missing", "{}");
+
+        let results = registry.execute_parallel(&[&slow, &fast, &missing]).await;
+
+        assert_eq!(results[0], "30");
+        assert_eq!(results[1], "1");
+        assert!(results[2].contains("Unknown tool"));
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_run_with_tools_loops_until_stop() {
+        use crate::{mock::MockGrokServer, response::FinishReason};
+
+        let response = |content: &str, finish_reason, tool_calls| ChatResponse {
+            finish_reason,
+            model: "grok-2-1212".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+                ..Default::default()
+            },
+            tool_calls,
+            ..ChatResponse::test_reply("req", content)
+        };
+
+        let server = MockGrokServer::new();
+        server
+            .push_chat_response(response(
+                "",
+                FinishReason::ToolCalls,
+                vec![call("add", r#"{"a": 1, "b": 2}"#)],
+            ))
+            .push_chat_response(response("1 + 2 = 3", FinishReason::Stop, vec![]));
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let run = client
+            .run_with_tools(ChatRequest::new().user_message("1 + 2?"), &registry())
+            .await
+            .unwrap();
+
+        assert_eq!(run.iterations, 2);
+        assert_eq!(run.usage.total_tokens, 30);
+        assert_eq!(run.response.content, "1 + 2 = 3");
+        // user, assistant (tool call), tool result, final assistant
+        assert_eq!(run.messages.len(), 4);
+        assert!(matches!(
+            &run.messages[1],
+            Message::AssistantReply { tool_calls, .. } if tool_calls.len() == 1
+        ));
+        assert!(matches!(
+            &run.messages[2],
+            Message::Tool { tool_call_id, content } if tool_call_id == "call_add" && content == "3"
+        ));
+    }
+}
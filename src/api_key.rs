@@ -9,7 +9,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!     let api_key_info = client.get_api_key_info().await?;
 //!
 //!     println!("API Key: {}", api_key_info.redacted_api_key);
@@ -33,7 +33,7 @@ use crate::proto;
 /// # use xai_grpc_client::GrokClient;
 /// # #[tokio::main]
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// # let mut client = GrokClient::from_env().await?;
+/// # let client = GrokClient::from_env().await?;
 /// let info = client.get_api_key_info().await?;
 ///
 /// // Check if key is active
@@ -50,6 +50,7 @@ use crate::proto;
 /// # }
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ApiKeyInfo {
     /// Redacted API key for display purposes.
     ///
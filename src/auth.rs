@@ -1,20 +1,26 @@
-use secrecy::{ExposeSecret, SecretString};
+use crate::credentials::CredentialProvider;
+use secrecy::ExposeSecret;
+use std::sync::Arc;
 use tonic::{Request, Status};
 
 #[derive(Clone)]
 pub struct AuthInterceptor {
-    api_key: SecretString,
+    credentials: Arc<dyn CredentialProvider>,
 }
 
 impl AuthInterceptor {
-    pub fn new(api_key: SecretString) -> Self {
-        Self { api_key }
+    pub fn new(credentials: Arc<dyn CredentialProvider>) -> Self {
+        Self { credentials }
     }
 }
 
 impl tonic::service::Interceptor for AuthInterceptor {
     fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
-        let token = format!("Bearer {}", self.api_key.expose_secret());
+        let api_key = self
+            .credentials
+            .api_key()
+            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+        let token = format!("Bearer {}", api_key.expose_secret());
         let metadata_value = token
             .parse()
             .map_err(|e| Status::internal(format!("Invalid auth token: {e}")))?;
@@ -23,6 +29,32 @@ impl tonic::service::Interceptor for AuthInterceptor {
             .metadata_mut()
             .insert("authorization", metadata_value);
 
+        #[cfg(feature = "otel")]
+        inject_trace_context(request.metadata_mut());
+
         Ok(request)
     }
 }
+
+/// Add the current OpenTelemetry context (`traceparent`, `tracestate`, ...)
+/// to the request metadata, using the global text map propagator.
+#[cfg(feature = "otel")]
+fn inject_trace_context(metadata: &mut tonic::metadata::MetadataMap) {
+    struct MetadataInjector<'a>(&'a mut tonic::metadata::MetadataMap);
+
+    impl opentelemetry::propagation::Injector for MetadataInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            let key = tonic::metadata::MetadataKey::from_bytes(key.as_bytes());
+            if let (Ok(key), Ok(value)) = (key, value.parse()) {
+                self.0.insert(key, value);
+            }
+        }
+    }
+
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(
+            &opentelemetry::Context::current(),
+            &mut MetadataInjector(metadata),
+        );
+    });
+}
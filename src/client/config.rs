@@ -1,5 +1,12 @@
+use super::{
+    middleware::{GrpcService, MiddlewareStack},
+    operations::PendingDeferred,
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+};
 use crate::{
     auth::AuthInterceptor,
+    credentials::{CredentialProvider, StaticKey},
     error::{GrokError, Result},
     proto::auth_client::AuthClient,
     proto::chat_client::ChatClient,
@@ -9,16 +16,28 @@ use crate::{
     proto::models_client::ModelsClient,
     proto::sample_client::SampleClient,
     proto::tokenize_client::TokenizeClient,
+    usage::UsageTracker,
 };
 use secrecy::{ExposeSecret, SecretString};
-use std::time::Duration;
-use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tonic::{
+    service::interceptor::InterceptedService,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+};
 use url::Url;
 
+/// The service every API client sends through: the middleware stack with the
+/// API key attached.
+pub(super) type AuthenticatedService = InterceptedService<GrpcService, AuthInterceptor>;
+
 /// Configuration for the Grok API client.
 ///
 /// This struct contains all the settings needed to connect to the xAI Grok API,
-/// including the endpoint URL, API key, default model, and timeout settings.
+/// including the endpoint URL, API key, default model, timeout and retry settings.
 ///
 /// # TLS and Endpoint Requirements
 ///
@@ -42,6 +61,7 @@ use url::Url;
 ///     api_key: SecretString::from("your-api-key".to_string()),
 ///     default_model: "grok-2-1212".to_string(),
 ///     timeout: Duration::from_secs(120),
+///     ..Default::default()
 /// };
 /// ```
 #[derive(Clone)]
@@ -53,13 +73,47 @@ pub struct GrokConfig {
     pub endpoint: String,
 
     /// API key for authentication (stored securely using SecretString).
+    ///
+    /// Ignored when [`credentials`](Self::credentials) is set.
     pub api_key: SecretString,
 
+    /// Provider asked for the API key on every request (default: none).
+    ///
+    /// Set it to rotate keys without rebuilding the client; see
+    /// [`credentials`](crate::credentials) for the built-in providers.
+    pub credentials: Option<Arc<dyn CredentialProvider>>,
+
     /// Default model to use for requests (default: "grok-code-fast-1").
     pub default_model: String,
 
     /// Request timeout duration (default: 60 seconds).
+    ///
+    /// Applies to each attempt individually when retries are enabled.
     pub timeout: Duration,
+
+    /// Retry policy applied to every RPC (default: 3 attempts with exponential backoff).
+    ///
+    /// Use [`RetryPolicy::disabled()`] to make exactly one attempt per call.
+    pub retry: RetryPolicy,
+
+    /// Record prompt and completion text in tracing spans (default: false).
+    ///
+    /// Only has an effect with the `tracing` feature. Leave it off unless your
+    /// traces are allowed to contain user data.
+    pub trace_content: bool,
+
+    /// Tower layers applied to every gRPC call (default: none).
+    pub middleware: MiddlewareStack,
+
+    /// Client-side limit on requests and tokens per minute (default: none).
+    ///
+    /// Shared by every clone of the client; see [`RateLimiter`].
+    pub rate_limiter: Option<RateLimiter>,
+
+    /// Records the token usage and cost of chat completions (default: none).
+    ///
+    /// Keep a clone of the tracker to read its reports; see [`UsageTracker`].
+    pub usage_tracker: Option<UsageTracker>,
 }
 
 impl Default for GrokConfig {
@@ -67,8 +121,14 @@ impl Default for GrokConfig {
         Self {
             endpoint: "https://api.x.ai".to_string(),
             api_key: SecretString::from(String::new()),
+            credentials: None,
             default_model: "grok-code-fast-1".to_string(),
             timeout: Duration::from_secs(60),
+            retry: RetryPolicy::default(),
+            trace_content: false,
+            middleware: MiddlewareStack::default(),
+            rate_limiter: None,
+            usage_tracker: None,
         }
     }
 }
@@ -78,6 +138,13 @@ impl Default for GrokConfig {
 /// `GrokClient` provides methods for chat completions, streaming responses,
 /// deferred completions, and managing stored completions.
 ///
+/// # Concurrency
+///
+/// All operations take `&self`, and the client is cheap to clone: every clone
+/// shares the same underlying HTTP/2 connection. A single client can be shared
+/// across many tasks (e.g. behind an `Arc`, or by cloning it into each task)
+/// without any external locking.
+///
 /// # Examples
 ///
 /// ## Creating a client from environment
@@ -87,7 +154,7 @@ impl Default for GrokConfig {
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// // Reads XAI_API_KEY from environment
-/// let mut client = GrokClient::from_env().await?;
+/// let client = GrokClient::from_env().await?;
 /// # Ok(())
 /// # }
 /// ```
@@ -105,30 +172,53 @@ impl Default for GrokConfig {
 ///     api_key: SecretString::from("your-api-key".to_string()),
 ///     default_model: "grok-2-1212".to_string(),
 ///     timeout: Duration::from_secs(120),
+///     ..Default::default()
 /// };
 ///
-/// let mut client = GrokClient::new(config).await?;
+/// let client = GrokClient::new(config).await?;
 /// # Ok(())
 /// # }
 /// ```
+///
+/// ## Running requests concurrently
+///
+/// ```no_run
+/// use xai_grpc_client::{ChatRequest, GrokClient};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = GrokClient::from_env().await?;
+///
+/// let handles: Vec<_> = ["Rust", "Go", "Zig"]
+///     .into_iter()
+///     .map(|lang| {
+///         let client = client.clone();
+///         tokio::spawn(async move {
+///             let request = ChatRequest::new().user_message(format!("Describe {lang} in one line"));
+///             client.complete_chat(request).await
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     println!("{}", handle.await??.content);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
 pub struct GrokClient {
-    pub(super) inner:
-        ChatClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) models_client:
-        ModelsClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) embedder_client:
-        EmbedderClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) tokenize_client:
-        TokenizeClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) auth_client:
-        AuthClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) sample_client:
-        SampleClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) image_client:
-        ImageClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
-    pub(super) documents_client:
-        DocumentsClient<tonic::service::interceptor::InterceptedService<Channel, AuthInterceptor>>,
+    pub(super) inner: ChatClient<AuthenticatedService>,
+    pub(super) models_client: ModelsClient<AuthenticatedService>,
+    pub(super) embedder_client: EmbedderClient<AuthenticatedService>,
+    pub(super) tokenize_client: TokenizeClient<AuthenticatedService>,
+    pub(super) auth_client: AuthClient<AuthenticatedService>,
+    pub(super) sample_client: SampleClient<AuthenticatedService>,
+    pub(super) image_client: ImageClient<AuthenticatedService>,
+    pub(super) documents_client: DocumentsClient<AuthenticatedService>,
     pub(super) config: GrokConfig,
+    /// Deferred completions started by this client (or a clone) and not yet
+    /// done, by request id.
+    pub(super) deferred: Arc<Mutex<HashMap<String, PendingDeferred>>>,
 }
 
 impl GrokClient {
@@ -200,34 +290,59 @@ impl GrokClient {
     /// # }
     /// ```
     pub fn with_channel(channel: Channel, api_key: SecretString) -> Self {
-        let interceptor = AuthInterceptor::new(api_key.clone());
-
-        let inner = ChatClient::with_interceptor(channel.clone(), interceptor.clone());
-        let models_client = ModelsClient::with_interceptor(channel.clone(), interceptor.clone());
-        let embedder_client =
-            EmbedderClient::with_interceptor(channel.clone(), interceptor.clone());
-        let tokenize_client =
-            TokenizeClient::with_interceptor(channel.clone(), interceptor.clone());
-        let auth_client = AuthClient::with_interceptor(channel.clone(), interceptor.clone());
-        let sample_client = SampleClient::with_interceptor(channel.clone(), interceptor.clone());
-        let image_client = ImageClient::with_interceptor(channel.clone(), interceptor.clone());
-        let documents_client = DocumentsClient::with_interceptor(channel, interceptor);
-
-        Self {
-            inner,
-            models_client,
-            embedder_client,
-            tokenize_client,
-            auth_client,
-            sample_client,
-            image_client,
-            documents_client,
-            config: GrokConfig {
-                endpoint: "https://api.x.ai".to_string(),
+        Self::with_channel_and_config(
+            channel,
+            GrokConfig {
                 api_key,
-                default_model: "grok-code-fast-1".to_string(),
-                timeout: Duration::from_secs(60),
+                ..Default::default()
             },
+        )
+    }
+
+    /// Creates a client with a custom configured channel and the rest of
+    /// `config` (model, timeout, retries, middleware, ...).
+    ///
+    /// The `endpoint` of `config` is ignored: requests go through `channel`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xai_grpc_client::{GrokClient, GrokConfig, RetryPolicy};
+    /// use tonic::transport::Channel;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let channel = Channel::from_static("http://localhost:50051").connect().await?;
+    ///
+    /// let client = GrokClient::with_channel_and_config(
+    ///     channel,
+    ///     GrokConfig {
+    ///         api_key: "xai-your-key".to_string().into(),
+    ///         retry: RetryPolicy::disabled(),
+    ///         ..Default::default()
+    ///     },
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_channel_and_config(channel: Channel, config: GrokConfig) -> Self {
+        let credentials = config
+            .credentials
+            .clone()
+            .unwrap_or_else(|| Arc::new(StaticKey::new(config.api_key.clone())));
+        let interceptor = AuthInterceptor::new(credentials);
+        let service = InterceptedService::new(config.middleware.apply(channel), interceptor);
+
+        Self {
+            inner: ChatClient::new(service.clone()),
+            models_client: ModelsClient::new(service.clone()),
+            embedder_client: EmbedderClient::new(service.clone()),
+            tokenize_client: TokenizeClient::new(service.clone()),
+            auth_client: AuthClient::new(service.clone()),
+            sample_client: SampleClient::new(service.clone()),
+            image_client: ImageClient::new(service.clone()),
+            documents_client: DocumentsClient::new(service),
+            config,
+            deferred: Arc::default(),
         }
     }
 
@@ -254,7 +369,7 @@ impl GrokClient {
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let api_key = SecretString::from("xai-your-key".to_string());
-    /// let mut client = GrokClient::connect(api_key).await?;
+    /// let client = GrokClient::connect(api_key).await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -289,7 +404,7 @@ impl GrokClient {
     /// use xai_grpc_client::GrokClient;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut client = GrokClient::from_env().await?;
+    /// let client = GrokClient::from_env().await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -321,7 +436,7 @@ impl GrokClient {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - API key is empty
+    /// - API key is empty and no credential provider is set
     /// - Endpoint URL is invalid
     /// - Connection to the API fails
     /// - TLS configuration fails
@@ -339,24 +454,22 @@ impl GrokClient {
     ///     api_key: SecretString::from("your-api-key".to_string()),
     ///     default_model: "grok-2-1212".to_string(),
     ///     timeout: Duration::from_secs(120),
+    ///     ..Default::default()
     /// };
     ///
-    /// let mut client = GrokClient::new(config).await?;
+    /// let client = GrokClient::new(config).await?;
     /// # Ok(())
     /// # }
     /// ```
     pub async fn new(config: GrokConfig) -> Result<Self> {
-        if config.api_key.expose_secret().is_empty() {
+        if config.credentials.is_none() && config.api_key.expose_secret().is_empty() {
             return Err(GrokError::Config("API key is empty".to_string()));
         }
 
         // Build channel from config
         let channel = Self::build_channel_from_config(&config).await?;
 
-        // Reuse with_channel logic
-        let mut client = Self::with_channel(channel, config.api_key.clone());
-        client.config = config; // Update config with provided values
-        Ok(client)
+        Ok(Self::with_channel_and_config(channel, config))
     }
 
     /// Helper method to build a channel from GrokConfig.
@@ -419,13 +532,13 @@ impl GrokClient {
     /// use xai_grpc_client::GrokClient;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut client = GrokClient::from_env().await?;
+    /// let client = GrokClient::from_env().await?;
     /// let response = client.test_connection().await?;
     /// println!("Connection test: {}", response);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn test_connection(&mut self) -> Result<String> {
+    pub async fn test_connection(&self) -> Result<String> {
         use crate::proto::{content, Content, GetCompletionsRequest, Message, MessageRole};
 
         let request = GetCompletionsRequest {
@@ -444,11 +557,10 @@ impl GrokClient {
         };
 
         // Apply timeout to test connection
-        let response = super::operations::with_timeout(
-            self.config.timeout,
-            self.inner.get_completion(request),
-        )
-        .await?;
+        let mut client = self.inner.clone();
+        let response =
+            super::operations::with_timeout(self.config.timeout, client.get_completion(request))
+                .await?;
         let completion = response.into_inner();
 
         // Extract text from first output
@@ -463,3 +575,39 @@ impl GrokClient {
         ))
     }
 }
+
+#[cfg(test)]
+impl GrokClient {
+    /// Client whose channel never connects; enough when nothing is fetched.
+    /// Must be created inside a tokio runtime.
+    pub(crate) fn test_client() -> Self {
+        let channel = Channel::from_static("http://127.0.0.1:1").connect_lazy();
+        Self::with_channel(channel, SecretString::from("test-key".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_clone_send_sync<T: Clone + Send + Sync + 'static>() {}
+
+    #[test]
+    fn test_client_is_clone_send_sync() {
+        assert_clone_send_sync::<GrokClient>();
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = GrokConfig::default();
+        assert_eq!(config.endpoint, "https://api.x.ai");
+        assert_eq!(config.default_model, "grok-code-fast-1");
+        assert_eq!(config.timeout, Duration::from_secs(60));
+        assert_eq!(config.retry.max_attempts, 3);
+        assert!(!config.trace_content);
+        assert!(config.middleware.is_empty());
+        assert!(config.credentials.is_none());
+        assert!(config.rate_limiter.is_none());
+        assert!(config.usage_tracker.is_none());
+    }
+}
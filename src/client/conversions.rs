@@ -1,14 +1,23 @@
 use super::config::GrokClient;
 use crate::{
+    embedding::{CompactVector, VectorFormat},
     error::{GrokError, Result},
     proto::{self, GetCompletionsRequest},
     request::{
-        ChatRequest, ContentPart, ImageDetail, Message, MessageContent, ReasoningEffort, SearchMode,
+        ChatRequest, ContentPart, ImageDetail, Message, MessageContent, ReasoningEffort,
+        SearchConfig, SearchMode, SearchSource,
+    },
+    response::{
+        ChatChunk, ChatResponse, Choice, ChoiceDelta, FinishReason, LogProb, LogProbs, TokenUsage,
+        TopLogProb,
     },
-    response::{ChatChunk, ChatResponse, FinishReason, LogProb, LogProbs, TokenUsage, TopLogProb},
     tools::ToolCall,
 };
-use base64::Engine;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
 
 impl GrokClient {
     /// Convert ChatRequest to protobuf GetCompletionsRequest
@@ -30,6 +39,7 @@ impl GrokClient {
             top_p: request.top_p(),
             stop: request.stop_sequences().to_vec(),
             seed: request.seed(),
+            n: request.n().map(|n| n as i32),
             ..Default::default()
         };
 
@@ -54,17 +64,7 @@ impl GrokClient {
 
         // Add search config if specified
         if let Some(search) = request.search_config() {
-            let search_mode = match search.mode {
-                SearchMode::Off => 0,  // proto::SearchMode::Off
-                SearchMode::On => 1,   // proto::SearchMode::On
-                SearchMode::Auto => 2, // proto::SearchMode::Auto
-            };
-
-            proto_req.search_parameters = Some(proto::SearchParameters {
-                mode: search_mode,
-                max_search_results: search.max_results.map(|v| v as i32),
-                ..Default::default()
-            });
+            proto_req.search_parameters = Some(Self::search_config_to_proto(search));
         }
 
         // Add response format if specified
@@ -129,46 +129,101 @@ impl GrokClient {
         Ok(proto_req)
     }
 
+    fn search_config_to_proto(search: &SearchConfig) -> proto::SearchParameters {
+        let mode = match search.mode {
+            SearchMode::Off => proto::SearchMode::OffSearchMode,
+            SearchMode::On => proto::SearchMode::OnSearchMode,
+            SearchMode::Auto => proto::SearchMode::AutoSearchMode,
+        };
+
+        let sources = search
+            .sources
+            .iter()
+            .map(|source| {
+                let source = match source {
+                    SearchSource::Web(web) => proto::source::Source::Web(proto::WebSource {
+                        allowed_websites: web.allowed_websites.clone(),
+                        excluded_websites: web.excluded_websites.clone(),
+                        country: web.country.clone(),
+                        safe_search: web.safe_search,
+                    }),
+                    SearchSource::News(news) => proto::source::Source::News(proto::NewsSource {
+                        excluded_websites: news.excluded_websites.clone(),
+                        country: news.country.clone(),
+                        safe_search: news.safe_search,
+                    }),
+                    SearchSource::X(x) => proto::source::Source::X(proto::XSource {
+                        included_x_handles: x.included_x_handles.clone(),
+                        excluded_x_handles: x.excluded_x_handles.clone(),
+                        post_favorite_count: x.post_favorite_count,
+                        post_view_count: x.post_view_count,
+                    }),
+                    SearchSource::Rss(rss) => proto::source::Source::Rss(proto::RssSource {
+                        links: rss.links.clone(),
+                    }),
+                };
+                proto::Source {
+                    source: Some(source),
+                }
+            })
+            .collect();
+
+        proto::SearchParameters {
+            mode: mode as i32,
+            sources,
+            from_date: search.from_date,
+            to_date: search.to_date,
+            return_citations: search.return_citations,
+            max_search_results: search.max_results.map(|v| v as i32),
+        }
+    }
+
     fn message_to_proto(&self, message: &Message) -> proto::Message {
-        let (role, content_vec, name) = match message {
-            Message::System(text) => (
-                proto::MessageRole::RoleSystem,
-                vec![proto::Content {
-                    content: Some(proto::content::Content::Text(text.clone())),
-                }],
-                String::new(),
-            ),
-            Message::User(content) => {
-                let parts = self.message_content_to_proto(content);
-                (proto::MessageRole::RoleUser, parts, String::new())
-            }
-            Message::Assistant(text) => (
-                proto::MessageRole::RoleAssistant,
-                vec![proto::Content {
-                    content: Some(proto::content::Content::Text(text.clone())),
-                }],
-                String::new(),
-            ),
-            // Tool result message - matches xAI Python SDK behavior
-            // The tool_call_id is accepted for API compatibility (e.g., with OpenAI)
-            // but not used in the gRPC protobuf as xAI's API matches results by message order
+        match message {
+            Message::System(text) => proto::Message {
+                role: proto::MessageRole::RoleSystem as i32,
+                content: vec![Self::text_content(text)],
+                ..Default::default()
+            },
+            Message::User(content) => proto::Message {
+                role: proto::MessageRole::RoleUser as i32,
+                content: self.message_content_to_proto(content),
+                ..Default::default()
+            },
+            Message::Assistant(text) => proto::Message {
+                role: proto::MessageRole::RoleAssistant as i32,
+                content: vec![Self::text_content(text)],
+                ..Default::default()
+            },
+            Message::AssistantReply {
+                content,
+                reasoning_content,
+                tool_calls,
+            } => proto::Message {
+                role: proto::MessageRole::RoleAssistant as i32,
+                content: vec![Self::text_content(content)],
+                reasoning_content: reasoning_content.clone(),
+                tool_calls: tool_calls.iter().map(ToolCall::to_proto).collect(),
+                ..Default::default()
+            },
+            // Tool result message - matches xAI Python SDK behavior.
+            // The name field is left empty; results are paired with calls by
+            // tool_call_id when set, and by message order otherwise.
             Message::Tool {
-                tool_call_id: _,
+                tool_call_id,
                 content,
-            } => (
-                proto::MessageRole::RoleTool,
-                vec![proto::Content {
-                    content: Some(proto::content::Content::Text(content.clone())),
-                }],
-                String::new(), // Don't set name field, following Python SDK behavior
-            ),
-        };
+            } => proto::Message {
+                role: proto::MessageRole::RoleTool as i32,
+                content: vec![Self::text_content(content)],
+                tool_call_id: (!tool_call_id.is_empty()).then(|| tool_call_id.clone()),
+                ..Default::default()
+            },
+        }
+    }
 
-        proto::Message {
-            role: role as i32,
-            content: content_vec,
-            name,
-            ..Default::default()
+    fn text_content(text: &str) -> proto::Content {
+        proto::Content {
+            content: Some(proto::content::Content::Text(text.to_string())),
         }
     }
 
@@ -214,64 +269,21 @@ impl GrokClient {
         &self,
         proto: proto::GetChatCompletionResponse,
     ) -> Result<ChatResponse> {
-        let output = proto
-            .outputs
-            .first()
-            .ok_or_else(|| GrokError::InvalidRequest("Response has no outputs".to_string()))?;
-
-        let message = output
-            .message
-            .as_ref()
-            .ok_or_else(|| GrokError::InvalidRequest("Output has no message".to_string()))?;
-
-        let content = message.content.clone();
+        let mut outputs = proto.outputs;
+        outputs.sort_by_key(|output| output.index);
 
-        // Extract reasoning content if present (convert empty string to None)
-        let reasoning_content = if message.reasoning_content.is_empty() {
-            None
-        } else {
-            Some(message.reasoning_content.clone())
-        };
-
-        // Extract tool calls from message
-        let tool_calls: Vec<ToolCall> = message
-            .tool_calls
-            .iter()
-            .filter_map(|tc| ToolCall::from_proto(tc.clone()))
-            .collect();
+        let choices = outputs
+            .into_iter()
+            .map(Self::proto_output_to_choice)
+            .collect::<Result<Vec<_>>>()?;
 
-        let finish_reason = Self::parse_finish_reason_static(output.finish_reason);
+        // The top-level fields mirror the first choice
+        let first = choices
+            .first()
+            .cloned()
+            .ok_or_else(|| GrokError::InvalidRequest("Response has no outputs".to_string()))?;
 
-        let usage = proto
-            .usage
-            .map(|u| TokenUsage {
-                prompt_tokens: u.prompt_tokens as u32,
-                completion_tokens: u.completion_tokens as u32,
-                total_tokens: u.total_tokens as u32,
-            })
-            .unwrap_or_default();
-
-        // Parse logprobs if present (from output, not message)
-        let logprobs = output.logprobs.as_ref().map(|lp| LogProbs {
-            content: lp
-                .content
-                .iter()
-                .map(|log_prob| LogProb {
-                    token: log_prob.token.clone(),
-                    logprob: log_prob.logprob,
-                    bytes: log_prob.bytes.clone(),
-                    top_logprobs: log_prob
-                        .top_logprobs
-                        .iter()
-                        .map(|top| TopLogProb {
-                            token: top.token.clone(),
-                            logprob: top.logprob,
-                            bytes: top.bytes.clone(),
-                        })
-                        .collect(),
-                })
-                .collect(),
-        });
+        let usage = proto.usage.map(TokenUsage::from).unwrap_or_default();
 
         // Parse timestamp if present
         let created = proto.created.map(|ts| ts.seconds);
@@ -285,99 +297,135 @@ impl GrokClient {
 
         Ok(ChatResponse {
             request_id: proto.id,
-            content,
-            finish_reason,
+            content: first.content,
+            finish_reason: first.finish_reason,
             model: proto.model,
             usage,
             citations: proto.citations,
-            tool_calls,
-            reasoning_content,
-            logprobs,
+            tool_calls: first.tool_calls,
+            reasoning_content: first.reasoning_content,
+            logprobs: first.logprobs,
             created,
             system_fingerprint,
+            choices,
+        })
+    }
+
+    fn proto_output_to_choice(output: proto::CompletionOutput) -> Result<Choice> {
+        let message = output
+            .message
+            .ok_or_else(|| GrokError::InvalidRequest("Output has no message".to_string()))?;
+
+        // Extract reasoning content if present (convert empty string to None)
+        let reasoning_content = if message.reasoning_content.is_empty() {
+            None
+        } else {
+            Some(message.reasoning_content)
+        };
+
+        // Extract tool calls from message
+        let tool_calls = message
+            .tool_calls
+            .into_iter()
+            .filter_map(ToolCall::from_proto)
+            .collect();
+
+        Ok(Choice {
+            index: output.index as u32,
+            content: message.content,
+            finish_reason: Self::parse_finish_reason_static(output.finish_reason),
+            tool_calls,
+            reasoning_content,
+            // Logprobs live on the output, not the message
+            logprobs: output.logprobs.as_ref().map(Self::proto_to_logprobs),
         })
     }
 
     pub(super) fn proto_chunk_to_chunk(chunk: proto::GetChatCompletionChunk) -> Result<ChatChunk> {
-        let output = chunk.outputs.first();
-
-        // Extract delta text from chunk
-        let delta = output
-            .and_then(|output| output.delta.as_ref().map(|d| d.content.clone()))
-            .unwrap_or_default();
-
-        // Extract reasoning delta if present
-        let reasoning_delta = output
-            .and_then(|output| output.delta.as_ref())
-            .map(|delta| delta.reasoning_content.clone())
-            .filter(|s| !s.is_empty()); // Filter out empty strings
-
-        // Extract tool calls from delta
-        let tool_calls = output
-            .and_then(|output| output.delta.as_ref())
-            .map(|delta| {
-                delta
-                    .tool_calls
-                    .iter()
-                    .filter_map(|tc| ToolCall::from_proto(tc.clone()))
-                    .collect()
-            })
-            .unwrap_or_default();
+        let mut outputs = chunk.outputs;
+        outputs.sort_by_key(|output| output.index);
 
-        // Check finish_reason - only set if it's not REASON_INVALID (0)
-        let finish_reason = output
-            .map(|output| output.finish_reason)
-            .filter(|&reason| reason != 0) // Filter out REASON_INVALID
-            .map(Self::parse_finish_reason_static);
+        let choices: Vec<ChoiceDelta> = outputs
+            .into_iter()
+            .map(Self::proto_output_chunk_to_delta)
+            .collect();
+
+        // The top-level fields mirror the first delta in the chunk
+        let first = choices.first().cloned().unwrap_or_default();
 
         // Usage is in the chunk itself, not cumulative in streaming
-        let cumulative_usage = chunk
-            .usage
-            .map(|u| TokenUsage {
-                prompt_tokens: u.prompt_tokens as u32,
-                completion_tokens: u.completion_tokens as u32,
-                total_tokens: u.total_tokens as u32,
-            })
-            .unwrap_or_default();
-
-        // Extract logprobs if present
-        let logprobs = output.and_then(|output| {
-            output.logprobs.as_ref().map(|lp| LogProbs {
-                content: lp
-                    .content
-                    .iter()
-                    .map(|log_prob| LogProb {
-                        token: log_prob.token.clone(),
-                        logprob: log_prob.logprob,
-                        bytes: log_prob.bytes.clone(),
-                        top_logprobs: log_prob
-                            .top_logprobs
-                            .iter()
-                            .map(|top| TopLogProb {
-                                token: top.token.clone(),
-                                logprob: top.logprob,
-                                bytes: top.bytes.clone(),
-                            })
-                            .collect(),
-                    })
-                    .collect(),
-            })
-        });
+        let cumulative_usage = chunk.usage.map(TokenUsage::from).unwrap_or_default();
 
         // Extract citations (typically only in the last chunk)
         let citations = chunk.citations;
 
+        let system_fingerprint = if chunk.system_fingerprint.is_empty() {
+            None
+        } else {
+            Some(chunk.system_fingerprint)
+        };
+
         Ok(ChatChunk {
-            delta,
-            finish_reason,
+            request_id: chunk.id,
+            model: chunk.model,
+            created: chunk.created.map(|ts| ts.seconds),
+            system_fingerprint,
+            index: first.index,
+            delta: first.delta,
+            finish_reason: first.finish_reason,
             cumulative_usage,
-            reasoning_delta,
-            tool_calls,
-            logprobs,
+            reasoning_delta: first.reasoning_delta,
+            tool_calls: first.tool_calls,
+            logprobs: first.logprobs,
             citations,
+            choices,
         })
     }
 
+    fn proto_output_chunk_to_delta(output: proto::CompletionOutputChunk) -> ChoiceDelta {
+        let delta = output.delta.unwrap_or_default();
+
+        ChoiceDelta {
+            index: output.index as u32,
+            delta: delta.content,
+            // Check finish_reason - only set if it's not REASON_INVALID (0)
+            finish_reason: (output.finish_reason != 0)
+                .then(|| Self::parse_finish_reason_static(output.finish_reason)),
+            // Filter out empty reasoning deltas
+            reasoning_delta: (!delta.reasoning_content.is_empty())
+                .then_some(delta.reasoning_content),
+            tool_calls: delta
+                .tool_calls
+                .into_iter()
+                .filter_map(ToolCall::from_proto)
+                .collect(),
+            logprobs: output.logprobs.as_ref().map(Self::proto_to_logprobs),
+        }
+    }
+
+    fn proto_to_logprobs(logprobs: &proto::LogProbs) -> LogProbs {
+        LogProbs {
+            content: logprobs
+                .content
+                .iter()
+                .map(|log_prob| LogProb {
+                    token: log_prob.token.clone(),
+                    logprob: log_prob.logprob,
+                    bytes: log_prob.bytes.clone(),
+                    top_logprobs: log_prob
+                        .top_logprobs
+                        .iter()
+                        .map(|top| TopLogProb {
+                            token: top.token.clone(),
+                            logprob: top.logprob,
+                            bytes: top.bytes.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
     pub(super) fn parse_finish_reason_static(reason: i32) -> FinishReason {
         // Map proto FinishReason enum to our FinishReason
         match reason {
@@ -429,9 +477,11 @@ impl GrokClient {
         }
     }
 
-    /// Convert protobuf EmbedResponse to EmbedResponse
+    /// Convert protobuf EmbedResponse to EmbedResponse, keeping the vectors
+    /// in `vector_format` if set.
     pub(super) fn proto_to_embed_response(
         response: proto::EmbedResponse,
+        vector_format: Option<VectorFormat>,
     ) -> Result<crate::embedding::EmbedResponse> {
         let embeddings = response
             .embeddings
@@ -443,10 +493,25 @@ impl GrokClient {
                         GrokError::InvalidRequest("missing embedding vector".into())
                     })?;
 
-                let vector = if !fv.float_array.is_empty() {
-                    fv.float_array
+                let (vector, compact) = if !fv.float_array.is_empty() {
+                    match vector_format {
+                        None => (fv.float_array, None),
+                        Some(format) => (
+                            Vec::new(),
+                            Some(CompactVector::from_f32(&fv.float_array, format)),
+                        ),
+                    }
                 } else if !fv.base64_array.is_empty() {
-                    Self::decode_base64_embedding(&fv.base64_array)?
+                    let raw = Self::decode_base64_embedding(&fv.base64_array)?;
+                    match vector_format {
+                        None => (raw.to_f32(), None),
+                        // Keep the decoded bytes as they are
+                        Some(VectorFormat::F32) => (Vec::new(), Some(raw)),
+                        Some(format) => (
+                            Vec::new(),
+                            Some(CompactVector::from_f32(&raw.to_f32(), format)),
+                        ),
+                    }
                 } else {
                     return Err(GrokError::InvalidRequest(
                         "embedding had neither float nor base64 array".into(),
@@ -456,6 +521,7 @@ impl GrokClient {
                 Ok(crate::embedding::Embedding {
                     index: emb.index as usize,
                     vector,
+                    compact,
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -469,28 +535,344 @@ impl GrokClient {
         })
     }
 
-    fn decode_base64_embedding(base64_str: &str) -> Result<Vec<f32>> {
-        let decoded = base64::engine::general_purpose::STANDARD
-            .decode(base64_str)
+    /// Decode a base64 embedding: little-endian `f32` values, with or without
+    /// padding.
+    fn decode_base64_embedding(base64_str: &str) -> Result<CompactVector> {
+        let decoded = BASE64_EMBEDDING
+            .decode(base64_str.trim())
             .map_err(|e| GrokError::InvalidRequest(format!("invalid base64 embedding: {e}")))?;
 
-        if decoded.len() % 4 != 0 {
-            return Err(GrokError::InvalidRequest(
-                "embedding byte length not divisible by 4".into(),
-            ));
+        CompactVector::from_bytes(VectorFormat::F32, 1.0, decoded)
+    }
+}
+
+/// Standard base64 alphabet, accepting encodings with or without padding.
+const BASE64_EMBEDDING: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{FunctionCall, ToolCallKind, ToolCallStatusKind};
+
+    fn tool_call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: ToolCallKind::ClientSideTool,
+            status: ToolCallStatusKind::Completed,
+            error_message: None,
+            function: FunctionCall {
+                name: "get_weather".to_string(),
+                arguments: r#"{"location": "Tokyo"}"#.to_string(),
+            },
         }
+    }
 
-        // Convert bytes to f32 array (little-endian)
-        let floats: Vec<f32> = decoded
-            .chunks_exact(4)
-            .map(|chunk| {
-                let bytes: [u8; 4] = chunk
-                    .try_into()
-                    .expect("chunk size already validated as divisible by 4");
-                f32::from_le_bytes(bytes)
-            })
+    #[tokio::test]
+    async fn test_search_config_to_proto() {
+        use crate::request::{NewsSource, WebSource, XSource};
+
+        let client = GrokClient::test_client();
+        let request = ChatRequest::new().user_message("News?").with_search(
+            SearchConfig::new()
+                .with_mode(SearchMode::On)
+                .with_source(NewsSource::new().with_country("FR"))
+                .with_source(
+                    WebSource::new()
+                        .with_allowed_websites(vec!["x.ai".to_string()])
+                        .with_safe_search(false),
+                )
+                .with_source(XSource::new().with_excluded_handles(vec!["spam".to_string()]))
+                .with_date_range(
+                    Some(prost_types::Timestamp {
+                        seconds: 1_700_000_000,
+                        nanos: 0,
+                    }),
+                    None,
+                )
+                .with_max_results(3)
+                .with_return_citations(false),
+        );
+
+        let params = client
+            .to_proto_request(&request)
+            .unwrap()
+            .search_parameters
+            .unwrap();
+
+        assert_eq!(params.mode, proto::SearchMode::OnSearchMode as i32);
+        assert_eq!(params.max_search_results, Some(3));
+        assert_eq!(params.from_date.map(|t| t.seconds), Some(1_700_000_000));
+        assert!(params.to_date.is_none());
+        assert!(!params.return_citations);
+        assert_eq!(params.sources.len(), 3);
+
+        match &params.sources[0].source {
+            Some(proto::source::Source::News(news)) => {
+                assert_eq!(news.country.as_deref(), Some("FR"));
+                assert!(news.safe_search);
+            }
+            other => panic!("Expected news source, got {other:?}"),
+        }
+        match &params.sources[1].source {
+            Some(proto::source::Source::Web(web)) => {
+                assert_eq!(web.allowed_websites, vec!["x.ai".to_string()]);
+                assert!(!web.safe_search);
+            }
+            other => panic!("Expected web source, got {other:?}"),
+        }
+        match &params.sources[2].source {
+            Some(proto::source::Source::X(x)) => {
+                assert_eq!(x.excluded_x_handles, vec!["spam".to_string()]);
+            }
+            other => panic!("Expected X source, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assistant_reply_to_proto() {
+        let client = GrokClient::test_client();
+        let message = Message::AssistantReply {
+            content: "Checking the weather".to_string(),
+            reasoning_content: Some("Need the weather tool".to_string()),
+            tool_calls: vec![tool_call("call_1"), tool_call("call_2")],
+        };
+
+        let proto = client.message_to_proto(&message);
+
+        assert_eq!(proto.role, proto::MessageRole::RoleAssistant as i32);
+        assert_eq!(
+            proto.content,
+            vec![GrokClient::text_content("Checking the weather")]
+        );
+        assert_eq!(
+            proto.reasoning_content.as_deref(),
+            Some("Need the weather tool")
+        );
+        assert_eq!(proto.tool_calls.len(), 2);
+        assert_eq!(proto.tool_calls[1].id, "call_2");
+
+        // Tool calls survive the round trip back into the public type
+        let parsed = ToolCall::from_proto(proto.tool_calls[0].clone()).unwrap();
+        assert_eq!(parsed.function.name, "get_weather");
+        assert_eq!(parsed.function.arguments, r#"{"location": "Tokyo"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_plain_assistant_has_no_tool_calls() {
+        let client = GrokClient::test_client();
+        let proto = client.message_to_proto(&Message::Assistant("Hi".to_string()));
+
+        assert_eq!(proto.role, proto::MessageRole::RoleAssistant as i32);
+        assert!(proto.tool_calls.is_empty());
+        assert!(proto.reasoning_content.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_keeps_tool_call_id() {
+        let client = GrokClient::test_client();
+
+        let proto = client.message_to_proto(&Message::Tool {
+            tool_call_id: "call_1".to_string(),
+            content: "22C".to_string(),
+        });
+        assert_eq!(proto.role, proto::MessageRole::RoleTool as i32);
+        assert_eq!(proto.tool_call_id.as_deref(), Some("call_1"));
+
+        let proto = client.message_to_proto(&Message::Tool {
+            tool_call_id: String::new(),
+            content: "22C".to_string(),
+        });
+        assert!(proto.tool_call_id.is_none());
+    }
+
+    fn output(index: i32, content: &str) -> proto::CompletionOutput {
+        proto::CompletionOutput {
+            finish_reason: proto::FinishReason::ReasonStop as i32,
+            index,
+            message: Some(proto::CompletionMessage {
+                content: content.to_string(),
+                role: proto::MessageRole::RoleAssistant as i32,
+                ..Default::default()
+            }),
+            logprobs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_n_forwarded_to_proto() {
+        let client = GrokClient::test_client();
+
+        let proto_req = client
+            .to_proto_request(&ChatRequest::new().user_message("Hi").with_n(3))
+            .unwrap();
+        assert_eq!(proto_req.n, Some(3));
+
+        let proto_req = client
+            .to_proto_request(&ChatRequest::new().user_message("Hi"))
+            .unwrap();
+        assert!(proto_req.n.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proto_to_response_keeps_all_choices() {
+        let client = GrokClient::test_client();
+        let proto_response = proto::GetChatCompletionResponse {
+            id: "req_1".to_string(),
+            outputs: vec![output(1, "Second"), output(0, "First"), output(2, "Third")],
+            ..Default::default()
+        };
+
+        let response = client.proto_to_response(proto_response).unwrap();
+
+        assert_eq!(response.content, "First");
+        assert_eq!(response.choices.len(), 3);
+        let contents: Vec<_> = response
+            .choices
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["First", "Second", "Third"]);
+        assert_eq!(response.choices[2].index, 2);
+        assert!(matches!(
+            response.choices[1].finish_reason,
+            FinishReason::Stop
+        ));
+    }
+
+    #[test]
+    fn test_proto_chunk_carries_output_index() {
+        let delta = |index: i32, content: &str| proto::CompletionOutputChunk {
+            delta: Some(proto::Delta {
+                content: content.to_string(),
+                ..Default::default()
+            }),
+            index,
+            ..Default::default()
+        };
+        let chunk = proto::GetChatCompletionChunk {
+            outputs: vec![delta(2, "c"), delta(1, "b")],
+            ..Default::default()
+        };
+
+        let chunk = GrokClient::proto_chunk_to_chunk(chunk).unwrap();
+
+        assert_eq!(chunk.index, 1);
+        assert_eq!(chunk.delta, "b");
+        assert_eq!(chunk.choices.len(), 2);
+        assert_eq!(chunk.choices[1].index, 2);
+        assert_eq!(chunk.choices[1].delta, "c");
+        assert!(chunk.choices[1].finish_reason.is_none());
+    }
+
+    fn detailed_usage() -> proto::SamplingUsage {
+        proto::SamplingUsage {
+            prompt_tokens: 120,
+            completion_tokens: 30,
+            total_tokens: 350,
+            cached_prompt_text_tokens: 100,
+            prompt_image_tokens: 15,
+            reasoning_tokens: 200,
+            num_sources_used: 4,
+            ..Default::default()
+        }
+    }
+
+    fn assert_detailed_usage(usage: &TokenUsage) {
+        assert_eq!(usage.prompt_tokens, 120);
+        assert_eq!(usage.completion_tokens, 30);
+        assert_eq!(usage.total_tokens, 350);
+        assert_eq!(usage.cached_prompt_tokens, 100);
+        assert_eq!(usage.prompt_image_tokens, 15);
+        assert_eq!(usage.reasoning_tokens, 200);
+        assert_eq!(usage.num_sources_used, 4);
+    }
+
+    #[tokio::test]
+    async fn test_proto_to_response_keeps_usage_details() {
+        let client = GrokClient::test_client();
+        let proto_response = proto::GetChatCompletionResponse {
+            outputs: vec![output(0, "Hi")],
+            usage: Some(detailed_usage()),
+            ..Default::default()
+        };
+
+        let response = client.proto_to_response(proto_response).unwrap();
+        assert_detailed_usage(&response.usage);
+    }
+
+    #[test]
+    fn test_proto_chunk_keeps_usage_details() {
+        let chunk = proto::GetChatCompletionChunk {
+            usage: Some(detailed_usage()),
+            ..Default::default()
+        };
+
+        let chunk = GrokClient::proto_chunk_to_chunk(chunk).unwrap();
+        assert_detailed_usage(&chunk.cumulative_usage);
+    }
+
+    fn embed_response(vector: proto::FeatureVector) -> proto::EmbedResponse {
+        proto::EmbedResponse {
+            id: "emb-1".to_string(),
+            embeddings: vec![proto::Embedding {
+                index: 0,
+                embeddings: vec![vector],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_base64_embedding_with_or_without_padding() {
+        let bytes: Vec<u8> = [1.5f32, -2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
             .collect();
+        let padded = base64::engine::general_purpose::STANDARD.encode(&bytes[..4]);
+        assert!(padded.ends_with('='));
+
+        let decoded = GrokClient::decode_base64_embedding(&padded).unwrap();
+        assert_eq!(decoded.to_f32(), [1.5]);
+        let decoded = GrokClient::decode_base64_embedding(padded.trim_end_matches('=')).unwrap();
+        assert_eq!(decoded.to_f32(), [1.5]);
+
+        let both = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        let decoded = GrokClient::decode_base64_embedding(&both).unwrap();
+        assert_eq!(decoded.to_f32(), [1.5, -2.0]);
+
+        let odd = base64::engine::general_purpose::STANDARD.encode([0u8; 6]);
+        assert!(GrokClient::decode_base64_embedding(&odd).is_err());
+        assert!(GrokClient::decode_base64_embedding("not base64!").is_err());
+    }
 
-        Ok(floats)
+    #[test]
+    fn test_proto_to_embed_response_vector_formats() {
+        let floats = proto::FeatureVector {
+            float_array: vec![0.5, -1.0],
+            ..Default::default()
+        };
+        let base64 = proto::FeatureVector {
+            base64_array: base64::engine::general_purpose::STANDARD
+                .encode([0.5f32.to_le_bytes(), (-1.0f32).to_le_bytes()].concat()),
+            ..Default::default()
+        };
+
+        for vector in [floats, base64] {
+            let response =
+                GrokClient::proto_to_embed_response(embed_response(vector.clone()), None).unwrap();
+            assert_eq!(response.embeddings[0].vector, [0.5, -1.0]);
+
+            let response =
+                GrokClient::proto_to_embed_response(embed_response(vector), Some(VectorFormat::I8))
+                    .unwrap();
+            let embedding = &response.embeddings[0];
+            assert!(embedding.vector.is_empty());
+            let compact = embedding.compact.as_ref().unwrap();
+            assert_eq!(compact.format(), VectorFormat::I8);
+            assert_eq!(compact.as_bytes(), [64, (-127i8) as u8]);
+        }
     }
 }
@@ -0,0 +1,151 @@
+//! Tower middleware applied to every gRPC call.
+//!
+//! [`GrokClient`](crate::GrokClient) sends all of its service clients (chat,
+//! models, embeddings, ...) through one HTTP service built from the channel
+//! and the [`MiddlewareStack`] configured on
+//! [`GrokConfig`](crate::GrokConfig). Any [`tower::Layer`] can be added to
+//! the stack to add metadata, rewrite or sign requests, inspect responses or
+//! answer calls without reaching the network.
+
+use std::{fmt, sync::Arc};
+use tonic::{
+    body::Body,
+    codegen::http::{Request, Response},
+    transport::Channel,
+};
+use tower::{util::BoxCloneSyncService, Layer, Service, ServiceExt};
+
+/// The type-erased HTTP service that gRPC calls are sent through.
+///
+/// This is the service type every layer of a [`MiddlewareStack`] wraps.
+pub type GrpcService = BoxCloneSyncService<Request<Body>, Response<Body>, tower::BoxError>;
+
+type BoxedLayer = Arc<dyn Fn(GrpcService) -> GrpcService + Send + Sync>;
+
+/// An ordered stack of [`tower::Layer`]s applied to every gRPC call.
+///
+/// The first layer added is the outermost one: it sees each request first and
+/// each response last. Requests reach the layers with the `authorization`
+/// metadata already set, so layers can sign them, but should take care not to
+/// log that header.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xai_grpc_client::{GrokClient, GrokConfig, MiddlewareStack};
+/// use tonic::codegen::http::{HeaderValue, Request};
+/// use tower::util::MapRequestLayer;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let middleware = MiddlewareStack::new().layer(MapRequestLayer::new(
+///     |mut request: Request<tonic::body::Body>| {
+///         request
+///             .headers_mut()
+///             .insert("x-team", HeaderValue::from_static("search"));
+///         request
+///     },
+/// ));
+///
+/// let client = GrokClient::new(GrokConfig {
+///     api_key: std::env::var("XAI_API_KEY")?.into(),
+///     middleware,
+///     ..Default::default()
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MiddlewareStack {
+    layers: Vec<BoxedLayer>,
+}
+
+impl MiddlewareStack {
+    /// Create an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer below the layers already in the stack.
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<GrpcService> + Send + Sync + 'static,
+        L::Service:
+            Service<Request<Body>, Response = Response<Body>> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request<Body>>>::Future: Send + 'static,
+        <L::Service as Service<Request<Body>>>::Error: Into<tower::BoxError>,
+    {
+        self.layers.push(Arc::new(move |service| {
+            BoxCloneSyncService::new(layer.layer(service).map_err(Into::into))
+        }));
+        self
+    }
+
+    /// Number of layers in the stack.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns true if the stack has no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Wrap `channel` in every layer of the stack.
+    pub(crate) fn apply(&self, channel: Channel) -> GrpcService {
+        let service = BoxCloneSyncService::new(channel.map_err(Into::into));
+
+        // Wrap innermost first so that the first layer added ends up outermost
+        self.layers
+            .iter()
+            .rev()
+            .fold(service, |service, layer| layer(service))
+    }
+}
+
+impl fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareStack")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tower::util::MapRequestLayer;
+
+    #[test]
+    fn test_empty_stack() {
+        let stack = MiddlewareStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_layers_run_in_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let record = |name: &'static str| {
+            let order = order.clone();
+            MapRequestLayer::new(move |request: Request<Body>| {
+                order.lock().unwrap().push(name);
+                request
+            })
+        };
+
+        let stack = MiddlewareStack::new()
+            .layer(record("outer"))
+            .layer(record("inner"));
+        assert_eq!(stack.len(), 2);
+
+        let channel = Channel::from_static("http://127.0.0.1:1").connect_lazy();
+        let service = stack.apply(channel);
+
+        // The call fails to connect, but only after going through both layers
+        let _ = service.oneshot(Request::new(Body::empty())).await;
+        assert_eq!(*order.lock().unwrap(), vec!["outer", "inner"]);
+    }
+}
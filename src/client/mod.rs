@@ -8,7 +8,16 @@
 
 mod config;
 mod conversions;
+mod middleware;
 mod operations;
+mod rate_limit;
+mod retry;
+mod telemetry;
 
 // Re-export public API
 pub use config::{GrokClient, GrokConfig};
+pub use middleware::{GrpcService, MiddlewareStack};
+pub use rate_limit::{RateLimit, RateLimitMode, RateLimiter, TokenEstimate};
+pub use retry::RetryPolicy;
+
+pub(crate) use rate_limit::estimate_tokens;
@@ -1,11 +1,24 @@
-use super::config::GrokClient;
+use super::{
+    config::GrokClient,
+    rate_limit::{estimate_tokens, RateLimiter, TokenEstimate},
+    retry::with_retry,
+    telemetry::RpcSpan,
+};
 use crate::{
     error::{GrokError, Result},
     proto,
-    request::ChatRequest,
-    response::{ChatChunk, ChatResponse},
+    request::{ChatRequest, Message},
+    response::{ChatChunk, ChatResponse, TokenUsage},
+    sample::SampleResponse,
+    usage::UsageTracker,
+};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
 };
-use std::{future::Future, pin::Pin, time::Duration};
 use tokio_stream::{Stream, StreamExt};
 
 /// Helper function to wrap an async operation with timeout.
@@ -26,34 +39,423 @@ where
         .map_err(Into::into)
 }
 
+/// Run `task` on every input with at most `concurrency` (at least one) in
+/// flight, returning the outputs in input order.
+///
+/// Stops at the first error; the tasks still running are aborted.
+async fn run_ordered<I, T, E, F, Fut>(
+    inputs: Vec<I>,
+    concurrency: usize,
+    task: F,
+) -> std::result::Result<Vec<T>, E>
+where
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    let mut outputs: Vec<_> = inputs.iter().map(|_| None).collect();
+    let mut inputs = inputs.into_iter().enumerate();
+    let mut tasks = tokio::task::JoinSet::new();
+
+    loop {
+        while tasks.len() < concurrency.max(1) {
+            let Some((index, input)) = inputs.next() else {
+                break;
+            };
+            let future = task(input);
+            tasks.spawn(async move { (index, future.await) });
+        }
+
+        match tasks.join_next().await {
+            Some(Ok((index, Ok(output)))) => outputs[index] = Some(output),
+            Some(Ok((_, Err(error)))) => return Err(error),
+            Some(Err(error)) => std::panic::resume_unwind(error.into_panic()),
+            None => break,
+        }
+    }
+
+    Ok(outputs
+        .into_iter()
+        .map(|output| output.expect("every task completed"))
+        .collect())
+}
+
+/// How long [`GrokClient::start_deferred`] keeps what it needs to record the
+/// usage of a deferred completion that is never polled to the end.
+const DEFERRED_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What [`GrokClient::start_deferred`] keeps to record the usage of a
+/// deferred completion once it is done.
+#[derive(Debug)]
+pub(super) struct PendingDeferred {
+    model: String,
+    user: Option<String>,
+    estimated: u32,
+    started: tokio::time::Instant,
+}
+
+/// A chat stream that records its usage once it ends.
+///
+/// With several choices, each one sends its own finish reason, and the final
+/// usage may come in a later chunk: the last usage received is recorded when
+/// the stream ends, or when it is dropped before that.
+struct ChatUsageStream {
+    inner: Pin<Box<dyn Stream<Item = Result<ChatChunk>> + Send>>,
+    span: RpcSpan,
+    rate_limiter: Option<RateLimiter>,
+    usage_tracker: Option<UsageTracker>,
+    model: String,
+    user: Option<String>,
+    estimated: u32,
+    /// The last non-empty usage, and the model that sent it.
+    usage: Option<(TokenUsage, String)>,
+    received: bool,
+    settled: bool,
+}
+
+impl ChatUsageStream {
+    fn settle(&mut self) {
+        if std::mem::replace(&mut self.settled, true) {
+            return;
+        }
+        let Some((usage, response_model)) = self.usage.take() else {
+            // Nothing was generated: give the estimated tokens back
+            if let (Some(limiter), false) = (&self.rate_limiter, self.received) {
+                limiter.record_usage(&self.model, self.estimated, 0);
+            }
+            self.span.finish_stream(None, &self.model);
+            return;
+        };
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.record_usage(&self.model, self.estimated, usage.total_tokens);
+        }
+        if let Some(tracker) = &self.usage_tracker {
+            tracker.record(
+                usage_model(&self.model, &response_model),
+                self.user.as_deref(),
+                &usage,
+            );
+        }
+        self.span.finish_stream(Some(&usage), &response_model);
+    }
+}
+
+impl Stream for ChatUsageStream {
+    type Item = Result<ChatChunk>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = std::task::ready!(self.inner.as_mut().poll_next(cx));
+        match &item {
+            Some(chunk) => {
+                if let Ok(chunk) = chunk {
+                    self.received = true;
+                    if chunk.cumulative_usage.total_tokens > 0 {
+                        self.usage = Some((chunk.cumulative_usage.clone(), chunk.model.clone()));
+                    }
+                }
+                self.span.record_chunk(chunk);
+            }
+            None => self.settle(),
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl Drop for ChatUsageStream {
+    fn drop(&mut self) {
+        self.settle();
+    }
+}
+
+/// A text sampling stream that settles its rate limit once it ends, with the
+/// last token count received, or when it is dropped before that.
+struct SampleUsageStream {
+    inner: Pin<Box<dyn Stream<Item = Result<SampleResponse>> + Send>>,
+    span: RpcSpan,
+    rate_limiter: Option<RateLimiter>,
+    model: String,
+    estimated: u32,
+    total_tokens: Option<u32>,
+    received: bool,
+    settled: bool,
+}
+
+impl SampleUsageStream {
+    fn settle(&mut self) {
+        if std::mem::replace(&mut self.settled, true) {
+            return;
+        }
+        let Some(limiter) = &self.rate_limiter else {
+            return;
+        };
+        match self.total_tokens {
+            Some(total_tokens) => limiter.record_usage(&self.model, self.estimated, total_tokens),
+            // Nothing was generated: give the estimated tokens back
+            None if !self.received => limiter.record_usage(&self.model, self.estimated, 0),
+            None => {}
+        }
+    }
+}
+
+impl Stream for SampleUsageStream {
+    type Item = Result<SampleResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = std::task::ready!(self.inner.as_mut().poll_next(cx));
+        match &item {
+            Some(chunk) => {
+                if let Ok(chunk) = chunk {
+                    self.received = true;
+                    if chunk.total_tokens > 0 {
+                        self.total_tokens = u32::try_from(chunk.total_tokens).ok();
+                    }
+                }
+                self.span.record_sample_chunk(chunk);
+            }
+            None => self.settle(),
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl Drop for SampleUsageStream {
+    fn drop(&mut self) {
+        self.settle();
+    }
+}
+
+/// The model usage is recorded under: the one that answered, if known.
+fn usage_model<'a>(request_model: &'a str, response_model: &'a str) -> &'a str {
+    if response_model.is_empty() {
+        request_model
+    } else {
+        response_model
+    }
+}
+
+/// The text of `messages`, for estimating their token count.
+fn messages_text(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(Message::text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl GrokClient {
+    /// Open the tracing span of `operation`, optionally sent to `model`.
+    fn rpc_span(&self, operation: &'static str, model: Option<&str>) -> RpcSpan {
+        RpcSpan::new(operation, model, self.config.trace_content)
+    }
+
+    /// The model used by requests that do not name one.
+    pub(crate) fn default_model(&self) -> &str {
+        &self.config.default_model
+    }
+
+    /// Take one request to `model` from the rate limit, along with the
+    /// estimated tokens of `prompt` if tokens are limited too. Returns the
+    /// number of tokens taken.
+    async fn acquire_rate_limit(
+        &self,
+        model: &str,
+        prompt: impl FnOnce() -> String,
+    ) -> Result<u32> {
+        let Some(limiter) = &self.config.rate_limiter else {
+            return Ok(0);
+        };
+        self.acquire_rate_limit_with(model, limiter.token_estimate(), prompt)
+            .await
+    }
+
+    /// Like [`acquire_rate_limit`](Self::acquire_rate_limit), for embedding and
+    /// image generation models: `tokenize` only counts for language models, so
+    /// their tokens are always estimated from the characters.
+    async fn acquire_rate_limit_by_characters(
+        &self,
+        model: &str,
+        prompt: impl FnOnce() -> String,
+    ) -> Result<u32> {
+        self.acquire_rate_limit_with(model, TokenEstimate::Characters, prompt)
+            .await
+    }
+
+    async fn acquire_rate_limit_with(
+        &self,
+        model: &str,
+        estimate: TokenEstimate,
+        prompt: impl FnOnce() -> String,
+    ) -> Result<u32> {
+        let Some(limiter) = &self.config.rate_limiter else {
+            return Ok(0);
+        };
+
+        let tokens = if limiter.limits_tokens(model) {
+            let prompt = prompt();
+            match estimate {
+                TokenEstimate::Characters => estimate_tokens(&prompt),
+                TokenEstimate::Tokenize => {
+                    let request = crate::tokenize::TokenizeRequest::new(model).with_text(&prompt);
+                    match self.tokenize(request).await {
+                        Ok(response) => u32::try_from(response.token_count()).unwrap_or(u32::MAX),
+                        // Counting is best effort: fall back rather than fail the request
+                        Err(_) => estimate_tokens(&prompt),
+                    }
+                }
+            }
+        } else {
+            0
+        };
+
+        limiter.acquire(model, tokens).await?;
+        Ok(tokens)
+    }
+
+    /// Settle the tokens taken from the rate limit of `model` with the
+    /// tokens actually used.
+    fn record_rate_limit_usage(&self, model: &str, estimated: u32, actual: u32) {
+        if let Some(limiter) = &self.config.rate_limiter {
+            limiter.record_usage(model, estimated, actual);
+        }
+    }
+
+    /// Await `call`, giving back the `estimated` tokens taken from the rate
+    /// limit of `model` if it fails.
+    async fn refund_on_error<T>(
+        &self,
+        model: &str,
+        estimated: u32,
+        call: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let result = call.await;
+        if result.is_err() {
+            self.record_rate_limit_usage(model, estimated, 0);
+        }
+        result
+    }
+
+    /// Fail if the usage budget has been spent. With a budget, loads the
+    /// model pricing on first use; without one, prices are only loaded by
+    /// [`UsageTracker::load_prices`].
+    async fn check_usage_budget(&self) -> Result<()> {
+        let Some(tracker) = &self.config.usage_tracker else {
+            return Ok(());
+        };
+
+        if tracker.has_budget() {
+            tracker.load_prices(self).await?;
+            tracker.check_budget()?;
+        }
+        Ok(())
+    }
+
+    /// Record the usage of a chat completion with the usage tracker.
+    fn record_chat_usage(
+        &self,
+        request_model: &str,
+        response_model: &str,
+        user: Option<&str>,
+        usage: &TokenUsage,
+    ) {
+        if let Some(tracker) = &self.config.usage_tracker {
+            tracker.record(usage_model(request_model, response_model), user, usage);
+        }
+    }
+
+    /// Record the usage of the deferred completion `request_id`, now done,
+    /// if it was started by this client and not recorded yet.
+    fn record_deferred_usage(&self, request_id: &str, response: &ChatResponse) {
+        let Some(pending) = self.deferred.lock().unwrap().remove(request_id) else {
+            return;
+        };
+        self.record_rate_limit_usage(
+            &pending.model,
+            pending.estimated,
+            response.usage.total_tokens,
+        );
+        self.record_chat_usage(
+            &pending.model,
+            &response.model,
+            pending.user.as_deref(),
+            &response.usage,
+        );
+    }
+
     /// Blocking completion (for simple queries)
-    pub async fn complete_chat(&mut self, request: ChatRequest) -> Result<ChatResponse> {
+    pub async fn complete_chat(&self, request: ChatRequest) -> Result<ChatResponse> {
         let proto_request = self.to_proto_request(&request)?;
+        let span = self.rpc_span("complete_chat", Some(&proto_request.model));
+        span.record_prompt(&request.messages());
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.inner.get_completion(proto_request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            self.check_usage_budget().await?;
+            let estimated = self
+                .acquire_rate_limit(&proto_request.model, || messages_text(request.messages()))
+                .await?;
 
-        self.proto_to_response(response)
+            let response = self
+                .refund_on_error(&proto_request.model, estimated, async {
+                    let response = with_retry(&self.config.retry, self.config.timeout, || {
+                        let mut client = self.inner.clone();
+                        let request = proto_request.clone();
+                        async move { client.get_completion(request).await }
+                    })
+                    .await?
+                    .into_inner();
+                    self.proto_to_response(response)
+                })
+                .await?;
+            self.record_rate_limit_usage(
+                &proto_request.model,
+                estimated,
+                response.usage.total_tokens,
+            );
+            self.record_chat_usage(
+                &proto_request.model,
+                &response.model,
+                request.user(),
+                &response.usage,
+            );
+            span.record_response(&response);
+            Ok(response)
+        })
+        .await
     }
 
     /// Stream chat completion (PRIMARY for REPL)
     pub async fn stream_chat(
-        &mut self,
+        &self,
         request: ChatRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatChunk>> + Send>>> {
         let proto_request = self.to_proto_request(&request)?;
+        let span = self
+            .rpc_span("stream_chat", Some(&proto_request.model))
+            .streaming();
+        span.record_prompt(&request.messages());
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.inner.get_completion_chunk(proto_request),
-        )
-        .await?
-        .into_inner();
+        let (response, estimated) = span
+            .run(async {
+                self.check_usage_budget().await?;
+                let estimated = self
+                    .acquire_rate_limit(&proto_request.model, || messages_text(request.messages()))
+                    .await?;
+
+                let response = self
+                    .refund_on_error(
+                        &proto_request.model,
+                        estimated,
+                        with_retry(&self.config.retry, self.config.timeout, || {
+                            let mut client = self.inner.clone();
+                            let request = proto_request.clone();
+                            async move { client.get_completion_chunk(request).await }
+                        }),
+                    )
+                    .await?;
+                Ok((response.into_inner(), estimated))
+            })
+            .await?;
 
         let timeout_duration = self.config.timeout;
         let stream = response
@@ -70,62 +472,125 @@ impl GrokClient {
                 ))),
             });
 
-        Ok(Box::pin(stream))
+        Ok(Box::pin(ChatUsageStream {
+            inner: Box::pin(stream),
+            span,
+            rate_limiter: self.config.rate_limiter.clone(),
+            usage_tracker: self.config.usage_tracker.clone(),
+            model: proto_request.model,
+            user: request.user().map(str::to_string),
+            estimated,
+            usage: None,
+            received: false,
+            settled: false,
+        }))
     }
 
     /// Start a deferred completion (async polling mode)
     /// Returns a request_id that can be used to poll for results
-    pub async fn start_deferred(&mut self, request: ChatRequest) -> Result<String> {
+    pub async fn start_deferred(&self, request: ChatRequest) -> Result<String> {
         let proto_request = self.to_proto_request(&request)?;
+        let span = self.rpc_span("start_deferred", Some(&proto_request.model));
+        span.record_prompt(&request.messages());
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.inner.start_deferred_completion(proto_request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            self.check_usage_budget().await?;
+            let estimated = self
+                .acquire_rate_limit(&proto_request.model, || messages_text(request.messages()))
+                .await?;
 
-        Ok(response.request_id)
+            let response = self
+                .refund_on_error(
+                    &proto_request.model,
+                    estimated,
+                    with_retry(&self.config.retry, self.config.timeout, || {
+                        let mut client = self.inner.clone();
+                        let request = proto_request.clone();
+                        async move { client.start_deferred_completion(request).await }
+                    }),
+                )
+                .await?
+                .into_inner();
+
+            span.record_request_id(&response.request_id);
+            let mut deferred = self.deferred.lock().unwrap();
+            // Forget completions that were never polled to the end
+            deferred.retain(|_, pending| pending.started.elapsed() < DEFERRED_MAX_AGE);
+            deferred.insert(
+                response.request_id.clone(),
+                PendingDeferred {
+                    model: proto_request.model.clone(),
+                    user: request.user().map(str::to_string),
+                    estimated,
+                    started: tokio::time::Instant::now(),
+                },
+            );
+            Ok(response.request_id)
+        })
+        .await
     }
 
     /// Poll for deferred completion results
     /// Returns None if still pending, Some(response) if complete
-    pub async fn poll_deferred(&mut self, request_id: String) -> Result<Option<ChatResponse>> {
-        let proto_request = proto::GetDeferredRequest { request_id };
-
-        let response = with_timeout(
-            self.config.timeout,
-            self.inner.get_deferred_completion(proto_request),
-        )
-        .await?
-        .into_inner();
-
-        // Check status
-        let status = proto::DeferredStatus::try_from(response.status)
-            .unwrap_or(proto::DeferredStatus::InvalidDeferredStatus);
-
-        match status {
-            proto::DeferredStatus::Done => {
-                // Response is ready
-                if let Some(completion_response) = response.response {
-                    Ok(Some(self.proto_to_response(completion_response)?))
-                } else {
+    ///
+    /// The first poll that finds a completion done records its usage with the
+    /// rate limiter and usage tracker, under the model and user of the
+    /// request given to [`start_deferred`](Self::start_deferred). Completions
+    /// started by another client, or more than a day ago, are not recorded.
+    pub async fn poll_deferred(&self, request_id: String) -> Result<Option<ChatResponse>> {
+        let span = self.rpc_span("poll_deferred", None);
+        span.record_request_id(&request_id);
+        let proto_request = proto::GetDeferredRequest {
+            request_id: request_id.clone(),
+        };
+
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.inner.clone();
+                let request = proto_request.clone();
+                async move { client.get_deferred_completion(request).await }
+            })
+            .await?
+            .into_inner();
+
+            // Check status
+            let status = proto::DeferredStatus::try_from(response.status)
+                .unwrap_or(proto::DeferredStatus::InvalidDeferredStatus);
+
+            match status {
+                proto::DeferredStatus::Done => {
+                    // Response is ready
+                    if let Some(completion_response) = response.response {
+                        let response = self.proto_to_response(completion_response)?;
+                        self.record_deferred_usage(&request_id, &response);
+                        span.record_response(&response);
+                        Ok(Some(response))
+                    } else {
+                        Err(GrokError::InvalidRequest(
+                            "Deferred request marked as done but no response".to_string(),
+                        ))
+                    }
+                }
+                proto::DeferredStatus::Pending => {
+                    // Still processing
+                    Ok(None)
+                }
+                proto::DeferredStatus::Expired => {
+                    // Nothing was generated: give the estimated tokens back
+                    let pending = self.deferred.lock().unwrap().remove(&request_id);
+                    if let Some(pending) = pending {
+                        self.record_rate_limit_usage(&pending.model, pending.estimated, 0);
+                    }
                     Err(GrokError::InvalidRequest(
-                        "Deferred request marked as done but no response".to_string(),
+                        "Deferred request has expired".to_string(),
                     ))
                 }
+                proto::DeferredStatus::InvalidDeferredStatus => Err(GrokError::InvalidRequest(
+                    "Invalid deferred status".to_string(),
+                )),
             }
-            proto::DeferredStatus::Pending => {
-                // Still processing
-                Ok(None)
-            }
-            proto::DeferredStatus::Expired => Err(GrokError::InvalidRequest(
-                "Deferred request has expired".to_string(),
-            )),
-            proto::DeferredStatus::InvalidDeferredStatus => Err(GrokError::InvalidRequest(
-                "Invalid deferred status".to_string(),
-            )),
-        }
+        })
+        .await
     }
 
     /// Wait for deferred completion to finish (blocking with polling)
@@ -142,55 +607,77 @@ impl GrokClient {
     /// timeouts, account for both the polling frequency and the per-request
     /// timeout to avoid unexpected behavior.
     pub async fn wait_for_deferred(
-        &mut self,
+        &self,
         request_id: String,
         poll_interval: std::time::Duration,
         timeout: std::time::Duration,
     ) -> Result<ChatResponse> {
         use tokio::time::{sleep, Instant};
 
+        let span = self.rpc_span("wait_for_deferred", None);
+        span.record_request_id(&request_id);
         let start = Instant::now();
 
-        loop {
-            if start.elapsed() > timeout {
-                return Err(GrokError::InvalidRequest(
-                    "Deferred request timed out".to_string(),
-                ));
-            }
+        span.run(async {
+            loop {
+                if start.elapsed() > timeout {
+                    return Err(GrokError::InvalidRequest(
+                        "Deferred request timed out".to_string(),
+                    ));
+                }
 
-            match self.poll_deferred(request_id.clone()).await? {
-                Some(response) => return Ok(response),
-                None => sleep(poll_interval).await,
+                match self.poll_deferred(request_id.clone()).await? {
+                    Some(response) => {
+                        span.record_response(&response);
+                        return Ok(response);
+                    }
+                    None => sleep(poll_interval).await,
+                }
             }
-        }
+        })
+        .await
     }
 
     /// Retrieve a stored completion by response ID
     /// Used when store_messages was set to true in the original request
-    pub async fn get_stored_completion(&mut self, response_id: String) -> Result<ChatResponse> {
+    pub async fn get_stored_completion(&self, response_id: String) -> Result<ChatResponse> {
+        let span = self.rpc_span("get_stored_completion", None);
+        span.record_request_id(&response_id);
         let proto_request = proto::GetStoredCompletionRequest { response_id };
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.inner.get_stored_completion(proto_request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.inner.clone();
+                let request = proto_request.clone();
+                async move { client.get_stored_completion(request).await }
+            })
+            .await?
+            .into_inner();
 
-        self.proto_to_response(response)
+            let response = self.proto_to_response(response)?;
+            span.record_response(&response);
+            Ok(response)
+        })
+        .await
     }
 
     /// Delete a stored completion by response ID
-    pub async fn delete_stored_completion(&mut self, response_id: String) -> Result<()> {
+    pub async fn delete_stored_completion(&self, response_id: String) -> Result<()> {
+        let span = self.rpc_span("delete_stored_completion", None);
+        span.record_request_id(&response_id);
         let proto_request = proto::DeleteStoredCompletionRequest { response_id };
 
-        with_timeout(
-            self.config.timeout,
-            self.inner.delete_stored_completion(proto_request),
-        )
-        .await?;
+        span.run(async {
+            with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.inner.clone();
+                let request = proto_request.clone();
+                async move { client.delete_stored_completion(request).await }
+            })
+            .await?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// List all available language models
@@ -202,7 +689,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let models = client.list_models().await?;
     ///
     ///     for model in models {
@@ -212,15 +699,20 @@ impl GrokClient {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn list_models(&mut self) -> Result<Vec<crate::models::LanguageModel>> {
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.list_language_models(()),
-        )
-        .await?
-        .into_inner();
+    pub async fn list_models(&self) -> Result<Vec<crate::models::LanguageModel>> {
+        let span = self.rpc_span("list_models", None);
+
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                async move { client.list_language_models(()).await }
+            })
+            .await?
+            .into_inner();
 
-        Ok(response.models.into_iter().map(Into::into).collect())
+            Ok(response.models.into_iter().map(Into::into).collect())
+        })
+        .await
     }
 
     /// Get detailed information about a specific model by name
@@ -232,7 +724,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let model = client.get_model("grok-2-1212").await?;
     ///
     ///     println!("Model: {}", model.name);
@@ -242,20 +734,23 @@ impl GrokClient {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn get_model(
-        &mut self,
-        name: impl Into<String>,
-    ) -> Result<crate::models::LanguageModel> {
-        let request = proto::GetModelRequest { name: name.into() };
+    pub async fn get_model(&self, name: impl Into<String>) -> Result<crate::models::LanguageModel> {
+        let span = self.rpc_span("get_model", None);
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.get_language_model(request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let request = proto::GetModelRequest { name: name.into() };
 
-        Ok(response.into())
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                let request = request.clone();
+                async move { client.get_language_model(request).await }
+            })
+            .await?
+            .into_inner();
+
+            Ok(response.into())
+        })
+        .await
     }
 
     /// List all available embedding models
@@ -267,7 +762,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let models = client.list_embedding_models().await?;
     ///
     ///     for model in models {
@@ -276,15 +771,20 @@ impl GrokClient {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn list_embedding_models(&mut self) -> Result<Vec<crate::models::EmbeddingModel>> {
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.list_embedding_models(()),
-        )
-        .await?
-        .into_inner();
+    pub async fn list_embedding_models(&self) -> Result<Vec<crate::models::EmbeddingModel>> {
+        let span = self.rpc_span("list_embedding_models", None);
+
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                async move { client.list_embedding_models(()).await }
+            })
+            .await?
+            .into_inner();
 
-        Ok(response.models.into_iter().map(Into::into).collect())
+            Ok(response.models.into_iter().map(Into::into).collect())
+        })
+        .await
     }
 
     /// Get detailed information about a specific embedding model by name
@@ -296,7 +796,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let model = client.get_embedding_model("embed-large-v1").await?;
     ///
     ///     println!("Model: {}", model.name);
@@ -306,19 +806,25 @@ impl GrokClient {
     /// }
     /// ```
     pub async fn get_embedding_model(
-        &mut self,
+        &self,
         name: impl Into<String>,
     ) -> Result<crate::models::EmbeddingModel> {
-        let request = proto::GetModelRequest { name: name.into() };
+        let span = self.rpc_span("get_embedding_model", None);
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.get_embedding_model(request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let request = proto::GetModelRequest { name: name.into() };
 
-        Ok(response.into())
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                let request = request.clone();
+                async move { client.get_embedding_model(request).await }
+            })
+            .await?
+            .into_inner();
+
+            Ok(response.into())
+        })
+        .await
     }
 
     /// List all available image generation models
@@ -330,7 +836,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let models = client.list_image_generation_models().await?;
     ///
     ///     for model in models {
@@ -341,16 +847,21 @@ impl GrokClient {
     /// }
     /// ```
     pub async fn list_image_generation_models(
-        &mut self,
+        &self,
     ) -> Result<Vec<crate::models::ImageGenerationModel>> {
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.list_image_generation_models(()),
-        )
-        .await?
-        .into_inner();
+        let span = self.rpc_span("list_image_generation_models", None);
 
-        Ok(response.models.into_iter().map(Into::into).collect())
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                async move { client.list_image_generation_models(()).await }
+            })
+            .await?
+            .into_inner();
+
+            Ok(response.models.into_iter().map(Into::into).collect())
+        })
+        .await
     }
 
     /// Get detailed information about a specific image generation model by name
@@ -362,7 +873,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let model = client.get_image_generation_model("image-gen-1").await?;
     ///
     ///     println!("Model: {}", model.name);
@@ -371,19 +882,25 @@ impl GrokClient {
     /// }
     /// ```
     pub async fn get_image_generation_model(
-        &mut self,
+        &self,
         name: impl Into<String>,
     ) -> Result<crate::models::ImageGenerationModel> {
-        let request = proto::GetModelRequest { name: name.into() };
+        let span = self.rpc_span("get_image_generation_model", None);
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.models_client.get_image_generation_model(request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let request = proto::GetModelRequest { name: name.into() };
 
-        Ok(response.into())
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.models_client.clone();
+                let request = request.clone();
+                async move { client.get_image_generation_model(request).await }
+            })
+            .await?
+            .into_inner();
+
+            Ok(response.into())
+        })
+        .await
     }
 
     /// Generate embeddings from text or images.
@@ -395,7 +912,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///
     ///     let request = EmbedRequest::new("embed-large-v1")
     ///         .add_text("Hello, world!")
@@ -411,19 +928,126 @@ impl GrokClient {
     /// }
     /// ```
     pub async fn embed(
-        &mut self,
+        &self,
         request: crate::embedding::EmbedRequest,
     ) -> Result<crate::embedding::EmbedResponse> {
-        let proto_request = self.embed_request_to_proto(&request);
+        let span = self.rpc_span("embed", Some(&request.model));
+
+        span.run(async {
+            let estimated = self
+                .acquire_rate_limit_by_characters(&request.model, || {
+                    request
+                        .inputs
+                        .iter()
+                        .filter_map(|input| match input {
+                            crate::embedding::EmbedInput::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .await?;
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.embedder_client.embed(proto_request),
-        )
-        .await?
-        .into_inner();
+            let proto_request = self.embed_request_to_proto(&request);
 
-        Self::proto_to_embed_response(response)
+            let response = self
+                .refund_on_error(
+                    &request.model,
+                    estimated,
+                    with_retry(&self.config.retry, self.config.timeout, || {
+                        let mut client = self.embedder_client.clone();
+                        let request = proto_request.clone();
+                        async move { client.embed(request).await }
+                    }),
+                )
+                .await?
+                .into_inner();
+
+            span.record_request_id(&response.id);
+            Self::proto_to_embed_response(response, request.vector_format)
+        })
+        .await
+    }
+
+    /// Embed any number of inputs, split into batches sent concurrently.
+    ///
+    /// The inputs are split into batches of
+    /// [`batch_size`](crate::EmbedBatching::with_batch_size), each sent with
+    /// [`embed`](Self::embed) (and so retried and rate limited on its own),
+    /// with at most [`concurrency`](crate::EmbedBatching::with_concurrency)
+    /// batches in flight. The merged response has one embedding per input,
+    /// sorted by [`index`](crate::Embedding::index) into the original inputs,
+    /// and the usage of every batch added up. Its `id`, `model` and
+    /// `system_fingerprint` are those of the first batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of any batch; the batches still running are
+    /// cancelled. For very large jobs, call it on slices of the inputs so a
+    /// failure only loses one slice.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xai_grpc_client::{EmbedBatching, EmbedRequest, GrokClient};
+    ///
+    /// # async fn example(passages: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = GrokClient::from_env().await?;
+    ///
+    /// let request = passages
+    ///     .into_iter()
+    ///     .fold(EmbedRequest::new("embed-large-v1"), EmbedRequest::add_text);
+    /// let response = client
+    ///     .embed_many(request, EmbedBatching::new().with_concurrency(8))
+    ///     .await?;
+    ///
+    /// println!("{} embeddings", response.embeddings.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn embed_many(
+        &self,
+        mut request: crate::embedding::EmbedRequest,
+        batching: crate::embedding::EmbedBatching,
+    ) -> Result<crate::embedding::EmbedResponse> {
+        let batch_size = batching.batch_size();
+        if request.inputs.len() <= batch_size {
+            return self.embed(request).await;
+        }
+
+        let mut inputs = std::mem::take(&mut request.inputs).into_iter();
+        let mut batches = Vec::new();
+        loop {
+            let batch: Vec<_> = inputs.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                break;
+            }
+            batches.push(crate::embedding::EmbedRequest {
+                inputs: batch,
+                ..request.clone()
+            });
+        }
+
+        let responses = run_ordered(batches, batching.concurrency(), |batch| {
+            let client = self.clone();
+            async move { client.embed(batch).await }
+        })
+        .await?;
+
+        let mut responses = responses.into_iter().enumerate();
+        let (_, mut merged) = responses.next().expect("more inputs than one batch");
+        for (number, response) in responses {
+            let offset = number * batch_size;
+            merged.usage += &response.usage;
+            merged
+                .embeddings
+                .extend(response.embeddings.into_iter().map(|mut embedding| {
+                    embedding.index += offset;
+                    embedding
+                }));
+        }
+        merged.embeddings.sort_by_key(|embedding| embedding.index);
+        Ok(merged)
     }
 
     /// Tokenize text to count tokens and understand token boundaries.
@@ -441,7 +1065,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///
     ///     let request = TokenizeRequest::new("grok-2-1212")
     ///         .with_text("Hello, world! How are you today?");
@@ -456,36 +1080,81 @@ impl GrokClient {
     /// }
     /// ```
     pub async fn tokenize(
-        &mut self,
+        &self,
         request: crate::tokenize::TokenizeRequest,
     ) -> Result<crate::tokenize::TokenizeResponse> {
-        let proto_request = proto::TokenizeTextRequest {
-            text: request.text,
-            model: request.model,
-            user: request.user.unwrap_or_default(),
-        };
+        let span = self.rpc_span("tokenize", Some(&request.model));
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.tokenize_client.tokenize_text(proto_request),
-        )
-        .await?
-        .into_inner();
-
-        let tokens = response
-            .tokens
-            .into_iter()
-            .map(|t| crate::tokenize::Token {
-                token_id: t.token_id,
-                string_token: t.string_token,
-                token_bytes: t.token_bytes,
+        span.run(async {
+            let proto_request = proto::TokenizeTextRequest {
+                text: request.text,
+                model: request.model,
+                user: request.user.unwrap_or_default(),
+            };
+
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.tokenize_client.clone();
+                let request = proto_request.clone();
+                async move { client.tokenize_text(request).await }
             })
-            .collect();
+            .await?
+            .into_inner();
+
+            let tokens = response
+                .tokens
+                .into_iter()
+                .map(|t| crate::tokenize::Token {
+                    token_id: t.token_id,
+                    string_token: t.string_token,
+                    token_bytes: t.token_bytes,
+                })
+                .collect();
 
-        Ok(crate::tokenize::TokenizeResponse {
-            tokens,
-            model: response.model,
+            Ok(crate::tokenize::TokenizeResponse {
+                tokens,
+                model: response.model,
+            })
         })
+        .await
+    }
+
+    /// Tokenize many requests concurrently, returning the results in input order.
+    ///
+    /// At most `concurrency` requests (at least one) are in flight at a time.
+    /// Each request is retried like [`tokenize`](Self::tokenize), and a failed
+    /// request does not stop the others. Use a
+    /// [`TokenCounter`](crate::TokenCounter) to only count tokens, with a cache.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xai_grpc_client::{GrokClient, TokenizeRequest};
+    ///
+    /// # async fn example(documents: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = GrokClient::from_env().await?;
+    ///
+    /// let requests = documents
+    ///     .into_iter()
+    ///     .map(|text| TokenizeRequest::new("grok-4").with_text(text))
+    ///     .collect();
+    ///
+    /// for response in client.tokenize_batch(requests, 16).await {
+    ///     println!("{} tokens", response?.token_count());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tokenize_batch(
+        &self,
+        requests: Vec<crate::tokenize::TokenizeRequest>,
+        concurrency: usize,
+    ) -> Vec<Result<crate::tokenize::TokenizeResponse>> {
+        let Ok(results) = run_ordered(requests, concurrency, |request| {
+            let client = self.clone();
+            async move { Ok::<_, Infallible>(client.tokenize(request).await) }
+        })
+        .await;
+        results
     }
 
     /// Get information about the current API key.
@@ -503,7 +1172,7 @@ impl GrokClient {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let mut client = GrokClient::from_env().await?;
+    ///     let client = GrokClient::from_env().await?;
     ///     let api_key_info = client.get_api_key_info().await?;
     ///
     ///     println!("API Key: {}", api_key_info.redacted_api_key);
@@ -522,12 +1191,20 @@ impl GrokClient {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn get_api_key_info(&mut self) -> Result<crate::api_key::ApiKeyInfo> {
-        let response = with_timeout(self.config.timeout, self.auth_client.get_api_key_info(()))
+    pub async fn get_api_key_info(&self) -> Result<crate::api_key::ApiKeyInfo> {
+        let span = self.rpc_span("get_api_key_info", None);
+
+        span.run(async {
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.auth_client.clone();
+                async move { client.get_api_key_info(()).await }
+            })
             .await?
             .into_inner();
 
-        Ok(response.into())
+            Ok(response.into())
+        })
+        .await
     }
 
     /// Sample text using the Sample API (alternative to Chat API).
@@ -535,40 +1212,68 @@ impl GrokClient {
     /// This is a simpler API for basic text completion without conversation structure.
     /// For most use cases, `complete_chat()` is recommended.
     pub async fn sample_text(
-        &mut self,
+        &self,
         request: crate::sample::SampleRequest,
     ) -> Result<crate::sample::SampleResponse> {
-        let proto_request = proto::SampleTextRequest {
-            prompt: request.prompts,
-            model: request.model,
-            n: request.n,
-            max_tokens: request.max_tokens,
-            seed: request.seed,
-            stop: request.stop,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            frequency_penalty: request.frequency_penalty,
-            logprobs: request.logprobs,
-            presence_penalty: request.presence_penalty,
-            top_logprobs: request.top_logprobs,
-            user: request.user.unwrap_or_default(),
-        };
+        let span = self.rpc_span("sample_text", Some(&request.model));
+        span.record_prompt(&request.prompts);
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.sample_client.sample_text(proto_request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let estimated = self
+                .acquire_rate_limit(&request.model, || request.prompts.join("\n"))
+                .await?;
 
-        Ok(response.into())
+            let proto_request = proto::SampleTextRequest {
+                prompt: request.prompts,
+                model: request.model.clone(),
+                n: request.n,
+                max_tokens: request.max_tokens,
+                seed: request.seed,
+                stop: request.stop,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                frequency_penalty: request.frequency_penalty,
+                logprobs: request.logprobs,
+                presence_penalty: request.presence_penalty,
+                top_logprobs: request.top_logprobs,
+                user: request.user.unwrap_or_default(),
+            };
+
+            let response = self
+                .refund_on_error(
+                    &request.model,
+                    estimated,
+                    with_retry(&self.config.retry, self.config.timeout, || {
+                        let mut client = self.sample_client.clone();
+                        let request = proto_request.clone();
+                        async move { client.sample_text(request).await }
+                    }),
+                )
+                .await?
+                .into_inner();
+
+            let response: crate::sample::SampleResponse = response.into();
+            self.record_rate_limit_usage(
+                &request.model,
+                estimated,
+                u32::try_from(response.total_tokens).unwrap_or(0),
+            );
+            span.record_sample_response(&response);
+            Ok(response)
+        })
+        .await
     }
 
     /// Stream text sampling (alternative to streaming chat).
     pub async fn sample_text_streaming(
-        &mut self,
+        &self,
         request: crate::sample::SampleRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<crate::sample::SampleResponse>> + Send>>> {
+        let span = self
+            .rpc_span("sample_text_streaming", Some(&request.model))
+            .streaming();
+        span.record_prompt(&request.prompts);
+
         let proto_request = proto::SampleTextRequest {
             prompt: request.prompts,
             model: request.model,
@@ -585,12 +1290,26 @@ impl GrokClient {
             user: request.user.unwrap_or_default(),
         };
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.sample_client.sample_text_streaming(proto_request),
-        )
-        .await?
-        .into_inner();
+        let (response, estimated) = span
+            .run(async {
+                let estimated = self
+                    .acquire_rate_limit(&proto_request.model, || proto_request.prompt.join("\n"))
+                    .await?;
+
+                let response = self
+                    .refund_on_error(
+                        &proto_request.model,
+                        estimated,
+                        with_retry(&self.config.retry, self.config.timeout, || {
+                            let mut client = self.sample_client.clone();
+                            let request = proto_request.clone();
+                            async move { client.sample_text_streaming(request).await }
+                        }),
+                    )
+                    .await?;
+                Ok((response.into_inner(), estimated))
+            })
+            .await?;
 
         let timeout_duration = self.config.timeout;
         let stream = response
@@ -605,68 +1324,99 @@ impl GrokClient {
                 ))),
             });
 
-        Ok(Box::pin(stream))
+        Ok(Box::pin(SampleUsageStream {
+            inner: Box::pin(stream),
+            span,
+            rate_limiter: self.config.rate_limiter.clone(),
+            model: proto_request.model,
+            estimated,
+            total_tokens: None,
+            received: false,
+            settled: false,
+        }))
     }
 
     /// Generate images from text prompts.
     pub async fn generate_image(
-        &mut self,
+        &self,
         request: crate::image::ImageGenerationRequest,
     ) -> Result<crate::image::ImageGenerationResponse> {
-        let proto_request = proto::GenerateImageRequest {
-            prompt: request.prompt,
-            image: request.image_url.map(|url| proto::ImageUrlContent {
-                image_url: url,
-                detail: proto::ImageDetail::DetailAuto as i32,
-            }),
-            model: request.model,
-            n: request.n,
-            user: request.user.unwrap_or_default(),
-            format: match request.format {
-                crate::image::ImageFormat::Base64 => proto::ImageFormat::ImgFormatBase64 as i32,
-                crate::image::ImageFormat::Url => proto::ImageFormat::ImgFormatUrl as i32,
-            },
-        };
+        let span = self.rpc_span("generate_image", Some(&request.model));
+        span.record_prompt(&request.prompt);
+
+        span.run(async {
+            let estimated = self
+                .acquire_rate_limit_by_characters(&request.model, || request.prompt.clone())
+                .await?;
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.image_client.generate_image(proto_request),
-        )
-        .await?
-        .into_inner();
+            let proto_request = proto::GenerateImageRequest {
+                prompt: request.prompt,
+                image: request.image_url.map(|url| proto::ImageUrlContent {
+                    image_url: url,
+                    detail: proto::ImageDetail::DetailAuto as i32,
+                }),
+                model: request.model.clone(),
+                n: request.n,
+                user: request.user.unwrap_or_default(),
+                format: match request.format {
+                    crate::image::ImageFormat::Base64 => proto::ImageFormat::ImgFormatBase64 as i32,
+                    crate::image::ImageFormat::Url => proto::ImageFormat::ImgFormatUrl as i32,
+                },
+            };
 
-        Ok(response.into())
+            let response = self
+                .refund_on_error(
+                    &request.model,
+                    estimated,
+                    with_retry(&self.config.retry, self.config.timeout, || {
+                        let mut client = self.image_client.clone();
+                        let request = proto_request.clone();
+                        async move { client.generate_image(request).await }
+                    }),
+                )
+                .await?
+                .into_inner();
+
+            Ok(response.into())
+        })
+        .await
     }
 
     /// Search documents in collections for RAG applications.
     pub async fn search_documents(
-        &mut self,
+        &self,
         request: crate::documents::DocumentSearchRequest,
     ) -> Result<crate::documents::DocumentSearchResponse> {
-        let proto_request = proto::SearchRequest {
-            query: request.query,
-            source: Some(proto::DocumentsSource {
-                collection_ids: request.collection_ids,
-            }),
-            limit: request.limit,
-            ranking_metric: Some(match request.ranking_metric {
-                crate::documents::RankingMetric::L2Distance => {
-                    proto::RankingMetric::L2Distance as i32
-                }
-                crate::documents::RankingMetric::CosineSimilarity => {
-                    proto::RankingMetric::CosineSimilarity as i32
-                }
-            }),
-            instructions: request.instructions,
-        };
+        let span = self.rpc_span("search_documents", None);
 
-        let response = with_timeout(
-            self.config.timeout,
-            self.documents_client.search(proto_request),
-        )
-        .await?
-        .into_inner();
+        span.run(async {
+            let proto_request = proto::SearchRequest {
+                query: request.query,
+                source: Some(proto::DocumentsSource {
+                    collection_ids: request.collection_ids,
+                }),
+                limit: request.limit,
+                ranking_metric: Some(match request.ranking_metric {
+                    crate::documents::RankingMetric::L2Distance => {
+                        proto::RankingMetric::L2Distance as i32
+                    }
+                    crate::documents::RankingMetric::CosineSimilarity => {
+                        proto::RankingMetric::CosineSimilarity as i32
+                    }
+                }),
+                instructions: request.instructions,
+            };
+
+            let response = with_retry(&self.config.retry, self.config.timeout, || {
+                let mut client = self.documents_client.clone();
+                let request = proto_request.clone();
+                async move { client.search(request).await }
+            })
+            .await?
+            .into_inner();
 
-        Ok(response.into())
+            Ok(response.into())
+        })
+        .await
     }
 }
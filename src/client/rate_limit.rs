@@ -0,0 +1,473 @@
+//! Client-side rate limiting with token buckets.
+//!
+//! A [`RateLimiter`] set on [`GrokConfig::rate_limiter`](crate::GrokConfig::rate_limiter)
+//! paces the requests of a [`GrokClient`](crate::GrokClient) to stay within a
+//! requests-per-minute and tokens-per-minute budget per model, instead of
+//! running into `ResourceExhausted` errors. Its buckets are shared by every
+//! clone of the limiter, and so by every clone of the client.
+
+use crate::error::{GrokError, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Requests-per-minute and tokens-per-minute budget for one model.
+///
+/// A `None` limit is not enforced.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Maximum number of requests per minute.
+    pub requests_per_minute: Option<u32>,
+    /// Maximum number of tokens (prompt and completion) per minute.
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    /// Create a budget with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of requests per minute.
+    pub fn with_requests_per_minute(mut self, requests: u32) -> Self {
+        self.requests_per_minute = Some(requests);
+        self
+    }
+
+    /// Limit the number of tokens per minute.
+    pub fn with_tokens_per_minute(mut self, tokens: u32) -> Self {
+        self.tokens_per_minute = Some(tokens);
+        self
+    }
+}
+
+/// What happens to a request that would exceed the budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Wait until the budget allows the request (default).
+    #[default]
+    Wait,
+    /// Fail immediately with [`GrokError::RateLimit`].
+    Reject,
+}
+
+/// How the prompt size of a request is estimated before it is sent.
+///
+/// The estimate is charged against the token budget up front; once the
+/// response arrives, the difference with the actual [`TokenUsage`](crate::TokenUsage)
+/// is settled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenEstimate {
+    /// About four characters per token (default, free).
+    #[default]
+    Characters,
+    /// Count the tokens with [`GrokClient::tokenize`](crate::GrokClient::tokenize),
+    /// at the cost of one extra RPC per request.
+    ///
+    /// Only language models can be counted: embedding and image generation
+    /// requests are always estimated from the characters.
+    Tokenize,
+}
+
+/// Client-side token-bucket rate limiter keyed by model.
+///
+/// Each model gets a bucket for requests and one for tokens, refilled
+/// continuously so that a full bucket holds one minute of budget.
+///
+/// # Examples
+///
+/// ```
+/// use xai_grpc_client::{GrokConfig, RateLimit, RateLimiter, RateLimitMode};
+///
+/// let limiter = RateLimiter::new(RateLimit::new().with_requests_per_minute(60))
+///     .with_model_limit(
+///         "grok-4",
+///         RateLimit::new()
+///             .with_requests_per_minute(30)
+///             .with_tokens_per_minute(100_000),
+///     )
+///     .with_mode(RateLimitMode::Reject);
+///
+/// let config = GrokConfig {
+///     rate_limiter: Some(limiter),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    default_limit: RateLimit,
+    model_limits: HashMap<String, RateLimit>,
+    mode: RateLimitMode,
+    token_estimate: TokenEstimate,
+    buckets: Arc<Mutex<HashMap<String, ModelBuckets>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter applying `default_limit` to every model without a
+    /// limit of its own.
+    pub fn new(default_limit: RateLimit) -> Self {
+        Self {
+            default_limit,
+            model_limits: HashMap::new(),
+            mode: RateLimitMode::default(),
+            token_estimate: TokenEstimate::default(),
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Set the budget of `model`.
+    pub fn with_model_limit(mut self, model: impl Into<String>, limit: RateLimit) -> Self {
+        self.model_limits.insert(model.into(), limit);
+        self
+    }
+
+    /// Set whether requests over budget wait or fail.
+    pub fn with_mode(mut self, mode: RateLimitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set how prompt sizes are estimated.
+    pub fn with_token_estimate(mut self, estimate: TokenEstimate) -> Self {
+        self.token_estimate = estimate;
+        self
+    }
+
+    /// How prompt sizes are estimated.
+    pub fn token_estimate(&self) -> TokenEstimate {
+        self.token_estimate
+    }
+
+    /// The budget applied to `model`.
+    pub fn limit_for(&self, model: &str) -> &RateLimit {
+        self.model_limits.get(model).unwrap_or(&self.default_limit)
+    }
+
+    /// Returns true if requests to `model` are limited by tokens per minute.
+    pub(crate) fn limits_tokens(&self, model: &str) -> bool {
+        self.limit_for(model).tokens_per_minute.is_some()
+    }
+
+    /// Take one request and `tokens` tokens from the budget of `model`,
+    /// waiting or failing according to the mode when it is exhausted.
+    pub(crate) async fn acquire(&self, model: &str, tokens: u32) -> Result<()> {
+        loop {
+            let wait = self.try_acquire(model, tokens, Instant::now());
+
+            match (wait, self.mode) {
+                (None, _) => return Ok(()),
+                (Some(wait), RateLimitMode::Wait) => tokio::time::sleep(wait).await,
+                (Some(wait), RateLimitMode::Reject) => {
+                    return Err(GrokError::RateLimit {
+                        retry_after_secs: wait.as_secs_f64().ceil() as u64,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Settle the token budget of `model` once the actual usage is known.
+    pub(crate) fn record_usage(&self, model: &str, estimated: u32, actual: u32) {
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(tokens) = buckets.get_mut(model).and_then(|b| b.tokens.as_mut()) {
+            tokens.charge(f64::from(actual) - f64::from(estimated), Instant::now());
+        }
+    }
+
+    /// Take from the buckets if they allow it, otherwise return how long to
+    /// wait before trying again.
+    fn try_acquire(&self, model: &str, tokens: u32, now: Instant) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let buckets = buckets
+            .entry(model.to_string())
+            .or_insert_with(|| ModelBuckets::new(self.limit_for(model), now));
+
+        let wait = [
+            buckets.requests.as_mut().map(|b| b.wait_for(1.0, now)),
+            buckets
+                .tokens
+                .as_mut()
+                .map(|b| b.wait_for(f64::from(tokens), now)),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or_default();
+
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+
+        if let Some(requests) = buckets.requests.as_mut() {
+            requests.charge(1.0, now);
+        }
+        if let Some(bucket) = buckets.tokens.as_mut() {
+            bucket.charge(f64::from(tokens), now);
+        }
+        None
+    }
+}
+
+/// The request and token buckets of one model.
+#[derive(Debug)]
+struct ModelBuckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+impl ModelBuckets {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            requests: limit.requests_per_minute.map(|n| Bucket::new(n, now)),
+            tokens: limit.tokens_per_minute.map(|n| Bucket::new(n, now)),
+        }
+    }
+}
+
+/// A token bucket holding one minute of budget, refilled continuously.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    per_second: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = f64::from(per_minute.max(1));
+        Self {
+            capacity,
+            per_second: capacity / 60.0,
+            available: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// How long until `amount` is available. Amounts larger than the bucket
+    /// only wait for a full bucket, so they are not blocked forever.
+    fn wait_for(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_second)
+        }
+    }
+
+    /// Remove `amount` from the bucket (or return it, if negative). The
+    /// bucket may go into debt, which delays the next requests.
+    fn charge(&mut self, amount: f64, now: Instant) {
+        self.refill(now);
+        self.available = (self.available - amount).min(self.capacity);
+    }
+}
+
+/// Estimate the number of tokens in `text` at about four characters per token.
+pub(crate) fn estimate_tokens(text: &str) -> u32 {
+    let chars = text.chars().count();
+    u32::try_from(chars.div_ceil(4)).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_for_falls_back_to_default() {
+        let limiter = RateLimiter::new(RateLimit::new().with_requests_per_minute(10))
+            .with_model_limit("grok-4", RateLimit::new().with_tokens_per_minute(1000));
+
+        assert_eq!(limiter.limit_for("grok-4").tokens_per_minute, Some(1000));
+        assert_eq!(limiter.limit_for("grok-3").requests_per_minute, Some(10));
+        assert!(limiter.limits_tokens("grok-4"));
+        assert!(!limiter.limits_tokens("grok-3"));
+    }
+
+    #[test]
+    fn test_request_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(RateLimit::new().with_requests_per_minute(2));
+        let start = Instant::now();
+
+        assert_eq!(limiter.try_acquire("grok-4", 0, start), None);
+        assert_eq!(limiter.try_acquire("grok-4", 0, start), None);
+        let wait = limiter.try_acquire("grok-4", 0, start).unwrap();
+        assert_eq!(wait, Duration::from_secs(30));
+
+        // Other models have their own buckets
+        assert_eq!(limiter.try_acquire("grok-3", 0, start), None);
+
+        let later = start + Duration::from_secs(30);
+        assert_eq!(limiter.try_acquire("grok-4", 0, later), None);
+    }
+
+    #[test]
+    fn test_token_bucket_and_usage_settlement() {
+        let limiter = RateLimiter::new(RateLimit::new().with_tokens_per_minute(600));
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire("grok-4", 500, now), None);
+        // The response used more than estimated: 100 left - 300 extra = -200
+        limiter.record_usage("grok-4", 500, 800);
+
+        let wait = limiter.try_acquire("grok-4", 100, Instant::now()).unwrap();
+        // 300 tokens missing at 10 tokens per second
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_oversized_request_waits_for_full_bucket() {
+        let limiter = RateLimiter::new(RateLimit::new().with_tokens_per_minute(60));
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire("grok-4", 1000, now), None);
+    }
+
+    #[tokio::test]
+    async fn test_reject_mode_fails_fast() {
+        let limiter = RateLimiter::new(RateLimit::new().with_requests_per_minute(1))
+            .with_mode(RateLimitMode::Reject);
+
+        limiter.acquire("grok-4", 0).await.unwrap();
+        match limiter.acquire("grok-4", 0).await {
+            Err(GrokError::RateLimit { retry_after_secs }) => assert_eq!(retry_after_secs, 60),
+            other => panic!("Expected rate limit error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_clones_share_buckets() {
+        let limiter = RateLimiter::new(RateLimit::new().with_requests_per_minute(1));
+        let clone = limiter.clone();
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire("grok-4", 0, now), None);
+        assert!(clone.try_acquire("grok-4", 0, now).is_some());
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_embed_does_not_tokenize() {
+        use crate::{
+            embedding::{EmbedRequest, EmbedResponse, Embedding, EmbeddingUsage},
+            mock::{MockGrokServer, MockMethod},
+            GrokClient, GrokConfig,
+        };
+
+        let server = MockGrokServer::new();
+        server.push_embed_response(EmbedResponse {
+            id: "emb-1".to_string(),
+            embeddings: vec![Embedding {
+                index: 0,
+                vector: vec![0.5],
+                compact: None,
+            }],
+            usage: EmbeddingUsage {
+                num_text_embeddings: 1,
+                num_image_embeddings: 0,
+            },
+            model: "embed-large-v1".to_string(),
+            system_fingerprint: "fp".to_string(),
+        });
+
+        let handle = server.start().await.unwrap();
+        let limiter = RateLimiter::new(RateLimit::new().with_tokens_per_minute(1_000))
+            .with_token_estimate(TokenEstimate::Tokenize);
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                rate_limiter: Some(limiter),
+                ..Default::default()
+            },
+        );
+
+        client
+            .embed(EmbedRequest::new("embed-large-v1").add_text("Hello"))
+            .await
+            .unwrap();
+        assert_eq!(server.call_count(MockMethod::Embed), 1);
+        assert_eq!(server.call_count(MockMethod::TokenizeText), 0);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_failed_request_refunds_estimate() {
+        use crate::{
+            mock::{MockGrokServer, MockMethod},
+            request::ChatRequest,
+            GrokClient, GrokConfig,
+        };
+
+        let server = MockGrokServer::new();
+        server.push_error(
+            MockMethod::GetCompletion,
+            tonic::Status::invalid_argument("bad request"),
+        );
+
+        let handle = server.start().await.unwrap();
+        let limiter = RateLimiter::new(RateLimit::new().with_tokens_per_minute(600));
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                rate_limiter: Some(limiter.clone()),
+                ..Default::default()
+            },
+        );
+
+        // About 500 tokens, given back when the request fails
+        let request = ChatRequest::new()
+            .user_message("x".repeat(2000))
+            .with_model("grok-4");
+        assert!(client.complete_chat(request).await.is_err());
+        assert_eq!(limiter.try_acquire("grok-4", 550, Instant::now()), None);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_sample_text_settles_usage() {
+        use crate::{
+            mock::MockGrokServer,
+            sample::{SampleRequest, SampleResponse},
+            GrokClient, GrokConfig,
+        };
+
+        let server = MockGrokServer::new();
+        server.push_sample_response(SampleResponse {
+            id: "sample-1".to_string(),
+            choices: vec![],
+            model: "grok-4".to_string(),
+            total_tokens: 20,
+        });
+
+        let handle = server.start().await.unwrap();
+        let limiter = RateLimiter::new(RateLimit::new().with_tokens_per_minute(600));
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                rate_limiter: Some(limiter.clone()),
+                ..Default::default()
+            },
+        );
+
+        // About 500 tokens estimated, 20 used
+        let request = SampleRequest::new("grok-4").add_prompt("x".repeat(2000));
+        client.sample_text(request).await.unwrap();
+        assert_eq!(limiter.try_acquire("grok-4", 550, Instant::now()), None);
+    }
+}
@@ -0,0 +1,347 @@
+//! Retry policy with exponential backoff and jitter.
+//!
+//! Every RPC issued by [`GrokClient`](crate::GrokClient) goes through [`with_retry`],
+//! which re-issues the call according to the [`RetryPolicy`] configured on
+//! [`GrokConfig`](crate::GrokConfig).
+
+use super::{operations::with_timeout, telemetry::record_retry};
+use crate::error::{GrokError, Result};
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Policy controlling automatic retries of failed RPCs.
+///
+/// Retries use exponential backoff: the delay before retry `n` is
+/// `base_delay * 2^(n - 1)`, capped at `max_delay`. When `jitter` is enabled the
+/// delay is randomized between half and the full computed value, so that many
+/// clients failing at the same time do not retry in lockstep.
+///
+/// If the server tells the client how long to wait (see
+/// [`GrokError::retry_after`]), the longer of the two delays is used.
+///
+/// # Retrying timeouts
+///
+/// An attempt that exceeds [`GrokConfig::timeout`](crate::GrokConfig::timeout)
+/// fails with `DeadlineExceeded` on the client, but the server may still
+/// finish it, and bills every generation it finishes. `DeadlineExceeded` is
+/// therefore not retried by default: adding it to
+/// [`retryable_codes`](Self::retryable_codes) can send a slow
+/// `complete_chat`, `start_deferred`, `sample_text` or `generate_image` up to
+/// [`max_attempts`](Self::max_attempts) times and pay for each of them.
+///
+/// # Examples
+///
+/// ```
+/// use xai_grpc_client::{GrokConfig, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let config = GrokConfig {
+///     retry: RetryPolicy::new()
+///         .with_max_attempts(5)
+///         .with_base_delay(Duration::from_millis(200))
+///         .with_max_delay(Duration::from_secs(10)),
+///     ..Default::default()
+/// };
+///
+/// // Disable retries entirely
+/// let config = GrokConfig {
+///     retry: RetryPolicy::disabled(),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one (default: 3).
+    ///
+    /// A value of `1` disables retries.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry (default: 500ms).
+    pub base_delay: Duration,
+
+    /// Upper bound for the backoff delay (default: 30 seconds).
+    pub max_delay: Duration,
+
+    /// Randomize each delay to avoid synchronized retries (default: true).
+    pub jitter: bool,
+
+    /// gRPC status codes that are considered retryable.
+    ///
+    /// Defaults to `Unavailable` and `ResourceExhausted`. Transport errors are
+    /// always retried. See [Retrying timeouts](Self#retrying-timeouts) before
+    /// adding `DeadlineExceeded`.
+    pub retryable_codes: Vec<tonic::Code>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retryable_codes: vec![tonic::Code::Unavailable, tonic::Code::ResourceExhausted],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a retry policy with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a policy that never retries.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Set the maximum number of attempts (including the first one).
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the upper bound for the backoff delay.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Enable or disable jitter.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the gRPC status codes that should be retried.
+    pub fn with_retryable_codes(mut self, codes: Vec<tonic::Code>) -> Self {
+        self.retryable_codes = codes;
+        self
+    }
+
+    /// Returns true if `error` should be retried under this policy.
+    pub fn should_retry(&self, error: &GrokError) -> bool {
+        if !error.is_retryable() {
+            return false;
+        }
+
+        match error.status_code() {
+            Some(code) => self.retryable_codes.contains(&code),
+            // Transport errors carry no status code and are always retried
+            None => true,
+        }
+    }
+
+    /// Compute the backoff delay before retry number `retry` (starting at 1).
+    ///
+    /// The returned value does not include jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        self.base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay)
+    }
+
+    /// Compute the actual delay to wait before retry number `retry`,
+    /// taking jitter and server-provided retry hints into account.
+    fn delay_for(&self, retry: u32, error: &GrokError) -> Duration {
+        let mut delay = self.backoff(retry);
+
+        if self.jitter {
+            // Equal jitter: keep half of the delay, randomize the other half
+            let half = delay / 2;
+            delay = half + half.mul_f64(random_fraction());
+        }
+
+        match error.retry_after() {
+            Some(secs) => delay.max(Duration::from_secs(secs)),
+            None => delay,
+        }
+    }
+}
+
+/// Returns a pseudo-random number in `[0, 1)`.
+///
+/// `RandomState` is seeded randomly per instance, which is plenty for jitter
+/// and avoids pulling in a dedicated RNG dependency.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Run an RPC with the per-attempt timeout and retry it according to `policy`.
+///
+/// `operation` is invoked once per attempt and must build a fresh request
+/// each time, since tonic consumes the request when it is sent.
+pub(crate) async fn with_retry<F, Fut, T>(
+    policy: &RetryPolicy,
+    timeout_duration: Duration,
+    mut operation: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, tonic::Status>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        match with_timeout(timeout_duration, operation()).await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < policy.max_attempts && policy.should_retry(&error) => {
+                let delay = policy.delay_for(attempt, &error);
+                record_retry(attempt, &error, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new()
+            .with_max_attempts(max_attempts)
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.max_attempts, 3);
+        assert!(policy.jitter);
+        assert!(policy
+            .retryable_codes
+            .contains(&tonic::Code::ResourceExhausted));
+        // Client-side timeouts may still be billed by the server
+        assert!(!policy.should_retry(&GrokError::Status(tonic::Status::deadline_exceeded("x"))));
+    }
+
+    #[test]
+    fn test_disabled_policy() {
+        let policy = RetryPolicy::disabled();
+        assert_eq!(policy.max_attempts, 1);
+    }
+
+    #[test]
+    fn test_max_attempts_at_least_one() {
+        let policy = RetryPolicy::new().with_max_attempts(0);
+        assert_eq!(policy.max_attempts, 1);
+    }
+
+    #[test]
+    fn test_backoff_exponential_and_capped() {
+        let policy = RetryPolicy::new()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(1000));
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(100), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new().with_base_delay(Duration::from_millis(100));
+        let error = GrokError::Status(tonic::Status::unavailable("down"));
+
+        for _ in 0..100 {
+            let delay = policy.delay_for(1, &error);
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn test_delay_respects_retry_after() {
+        let policy = fast_policy(3);
+        let error = GrokError::RateLimit {
+            retry_after_secs: 2,
+        };
+        assert_eq!(policy.delay_for(1, &error), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_should_retry_allowlist() {
+        let policy = RetryPolicy::new().with_retryable_codes(vec![tonic::Code::Unavailable]);
+
+        assert!(policy.should_retry(&GrokError::Status(tonic::Status::unavailable("x"))));
+        assert!(!policy.should_retry(&GrokError::Status(tonic::Status::deadline_exceeded("x"))));
+        assert!(!policy.should_retry(&GrokError::RateLimit {
+            retry_after_secs: 1
+        }));
+        assert!(!policy.should_retry(&GrokError::InvalidRequest("bad".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_succeeds_after_failures() {
+        let attempts = AtomicU32::new(0);
+
+        let result = with_retry(&fast_policy(3), Duration::from_secs(1), || {
+            let n = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if n < 3 {
+                    Err(tonic::Status::unavailable("try again"))
+                } else {
+                    Ok(n)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_gives_up_after_max_attempts() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<()> = with_retry(&fast_policy(2), Duration::from_secs(1), || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(tonic::Status::unavailable("down")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_does_not_retry_permanent_errors() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<()> = with_retry(&fast_policy(5), Duration::from_secs(1), || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(tonic::Status::invalid_argument("bad request")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,369 @@
+//! OpenTelemetry GenAI client metrics.
+//!
+//! With the `otel` feature, chat, embedding, text sampling and image
+//! generation calls record the histograms of the OpenTelemetry GenAI semantic
+//! conventions through the global meter provider:
+//!
+//! - `gen_ai.client.operation.duration` (seconds);
+//! - `gen_ai.client.token.usage` (tokens, split by `gen_ai.token.type`);
+//! - `gen_ai.client.operation.time_to_first_chunk` (seconds, streams only).
+//!
+//! The instruments are created on first use, so the meter provider must be
+//! installed before the first request. Without the feature, [`RpcMetrics`] is
+//! a no-op and compiles away.
+
+use crate::{
+    error::Result,
+    response::{ChatChunk, TokenUsage},
+    sample::SampleResponse,
+};
+
+/// Value of `gen_ai.provider.name` for the xAI API.
+#[cfg(feature = "otel")]
+const PROVIDER_NAME: &str = "x_ai";
+
+/// Bucket boundaries recommended for `gen_ai.client.operation.duration`.
+#[cfg(feature = "otel")]
+const DURATION_BUCKETS: [f64; 14] = [
+    0.01, 0.02, 0.04, 0.08, 0.16, 0.32, 0.64, 1.28, 2.56, 5.12, 10.24, 20.48, 40.96, 81.92,
+];
+
+/// Bucket boundaries recommended for `gen_ai.client.token.usage`.
+#[cfg(feature = "otel")]
+const TOKEN_BUCKETS: [f64; 14] = [
+    1.0, 4.0, 16.0, 64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0,
+    16777216.0, 67108864.0,
+];
+
+/// Bucket boundaries for `gen_ai.client.operation.time_to_first_chunk`.
+#[cfg(feature = "otel")]
+const FIRST_CHUNK_BUCKETS: [f64; 16] = [
+    0.001, 0.005, 0.01, 0.02, 0.04, 0.06, 0.08, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// The histograms shared by every client.
+#[cfg(feature = "otel")]
+struct Instruments {
+    duration: opentelemetry::metrics::Histogram<f64>,
+    token_usage: opentelemetry::metrics::Histogram<u64>,
+    time_to_first_chunk: opentelemetry::metrics::Histogram<f64>,
+}
+
+#[cfg(feature = "otel")]
+fn instruments() -> &'static Instruments {
+    static INSTRUMENTS: std::sync::OnceLock<Instruments> = std::sync::OnceLock::new();
+
+    INSTRUMENTS.get_or_init(|| {
+        let scope = opentelemetry::InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
+            .with_version(env!("CARGO_PKG_VERSION"))
+            .build();
+        let meter = opentelemetry::global::meter_with_scope(scope);
+
+        Instruments {
+            duration: meter
+                .f64_histogram("gen_ai.client.operation.duration")
+                .with_unit("s")
+                .with_description("GenAI operation duration")
+                .with_boundaries(DURATION_BUCKETS.to_vec())
+                .build(),
+            token_usage: meter
+                .u64_histogram("gen_ai.client.token.usage")
+                .with_unit("{token}")
+                .with_description("Number of input and output tokens used")
+                .with_boundaries(TOKEN_BUCKETS.to_vec())
+                .build(),
+            time_to_first_chunk: meter
+                .f64_histogram("gen_ai.client.operation.time_to_first_chunk")
+                .with_unit("s")
+                .with_description("Time to receive the first chunk of a streamed response")
+                .with_boundaries(FIRST_CHUNK_BUCKETS.to_vec())
+                .build(),
+        }
+    })
+}
+
+/// The `gen_ai.operation.name` of a client operation, or `None` if it is not
+/// a GenAI call (model listing, tokenization, ...).
+#[cfg(feature = "otel")]
+fn genai_operation(operation: &str) -> Option<&'static str> {
+    match operation {
+        "complete_chat" | "stream_chat" => Some("chat"),
+        "embed" => Some("embeddings"),
+        "sample_text" | "sample_text_streaming" => Some("text_completion"),
+        "generate_image" => Some("generate_content"),
+        _ => None,
+    }
+}
+
+/// The `error.type` of a failed call: the gRPC status code, or `_OTHER`.
+#[cfg(feature = "otel")]
+fn error_type(error: &crate::error::GrokError) -> String {
+    match error.status_code() {
+        Some(code) => format!("{code:?}"),
+        None => "_OTHER".to_string(),
+    }
+}
+
+/// The `output` token count of `usage`: reasoning tokens are billed as
+/// completion tokens but not counted in `completion_tokens`.
+#[cfg(feature = "otel")]
+fn output_tokens(usage: &TokenUsage) -> u64 {
+    u64::from(usage.completion_tokens) + u64::from(usage.reasoning_tokens)
+}
+
+/// The GenAI metrics of one client operation.
+pub(crate) struct RpcMetrics {
+    #[cfg(feature = "otel")]
+    operation: Option<&'static str>,
+    #[cfg(feature = "otel")]
+    request_model: Option<String>,
+    #[cfg(feature = "otel")]
+    response_model: std::sync::OnceLock<String>,
+    #[cfg(feature = "otel")]
+    start: std::time::Instant,
+    #[cfg(feature = "otel")]
+    streaming: bool,
+    #[cfg(feature = "otel")]
+    chunks: u64,
+    #[cfg(feature = "otel")]
+    finished: bool,
+}
+
+#[cfg(feature = "otel")]
+impl RpcMetrics {
+    pub(crate) fn new(operation: &'static str, model: Option<&str>) -> Self {
+        Self {
+            operation: genai_operation(operation),
+            request_model: model.map(str::to_string),
+            response_model: std::sync::OnceLock::new(),
+            start: std::time::Instant::now(),
+            streaming: false,
+            chunks: 0,
+            finished: false,
+        }
+    }
+
+    pub(crate) fn set_streaming(&mut self) {
+        self.streaming = true;
+    }
+
+    /// Record the duration of a unary call, or of a stream that failed to open.
+    pub(crate) fn record_result<T>(&self, result: &Result<T>) {
+        if !self.streaming || result.is_err() {
+            self.record_duration(result.as_ref().err());
+        }
+    }
+
+    /// Record the input and output token counts of a response.
+    pub(crate) fn record_usage(&self, usage: &TokenUsage, response_model: &str) {
+        let Some(attributes) = self.attributes_with_model(response_model) else {
+            return;
+        };
+
+        let token_usage = &instruments().token_usage;
+        for (token_type, count) in [
+            ("input", u64::from(usage.prompt_tokens)),
+            ("output", output_tokens(usage)),
+        ] {
+            let mut attributes = attributes.clone();
+            attributes.push(opentelemetry::KeyValue::new(
+                "gen_ai.token.type",
+                token_type,
+            ));
+            token_usage.record(count, &attributes);
+        }
+    }
+
+    pub(crate) fn record_chunk(&mut self, chunk: &Result<ChatChunk>) {
+        match chunk {
+            Ok(_) => self.record_chunk_received(),
+            Err(error) => self.finish(Some(error)),
+        }
+    }
+
+    /// Record the end of a chat stream, with the last usage it reported.
+    pub(crate) fn finish_stream(&mut self, usage: Option<&TokenUsage>, response_model: &str) {
+        if let Some(usage) = usage {
+            self.record_usage(usage, response_model);
+        }
+        self.finish(None);
+    }
+
+    pub(crate) fn record_sample_chunk(&mut self, chunk: &Result<SampleResponse>) {
+        match chunk {
+            Ok(chunk) => {
+                self.record_chunk_received();
+                if chunk.choices.iter().any(|c| !c.finish_reason.is_empty()) {
+                    let _ = self.response_model.set(chunk.model.clone());
+                    self.finish(None);
+                }
+            }
+            Err(error) => self.finish(Some(error)),
+        }
+    }
+
+    fn record_chunk_received(&mut self) {
+        self.chunks += 1;
+        if self.chunks > 1 {
+            return;
+        }
+        if let Some(attributes) = self.attributes() {
+            instruments()
+                .time_to_first_chunk
+                .record(self.start.elapsed().as_secs_f64(), &attributes);
+        }
+    }
+
+    /// Record the duration of a stream, once.
+    fn finish(&mut self, error: Option<&crate::error::GrokError>) {
+        if !self.finished {
+            self.finished = true;
+            self.record_duration(error);
+        }
+    }
+
+    fn record_duration(&self, error: Option<&crate::error::GrokError>) {
+        let Some(mut attributes) = self.attributes() else {
+            return;
+        };
+        if let Some(error) = error {
+            attributes.push(opentelemetry::KeyValue::new(
+                "error.type",
+                error_type(error),
+            ));
+        }
+        instruments()
+            .duration
+            .record(self.start.elapsed().as_secs_f64(), &attributes);
+    }
+
+    fn attributes_with_model(&self, response_model: &str) -> Option<Vec<opentelemetry::KeyValue>> {
+        if !response_model.is_empty() {
+            let _ = self.response_model.set(response_model.to_string());
+        }
+        self.attributes()
+    }
+
+    /// The common attributes, or `None` if the operation is not a GenAI call.
+    fn attributes(&self) -> Option<Vec<opentelemetry::KeyValue>> {
+        use opentelemetry::KeyValue;
+
+        let operation = self.operation?;
+        let mut attributes = vec![
+            KeyValue::new("gen_ai.operation.name", operation),
+            KeyValue::new("gen_ai.provider.name", PROVIDER_NAME),
+        ];
+        if let Some(model) = &self.request_model {
+            attributes.push(KeyValue::new("gen_ai.request.model", model.clone()));
+        }
+        if let Some(model) = self.response_model.get() {
+            attributes.push(KeyValue::new("gen_ai.response.model", model.clone()));
+        }
+        Some(attributes)
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+impl RpcMetrics {
+    pub(crate) fn new(_operation: &'static str, _model: Option<&str>) -> Self {
+        Self {}
+    }
+
+    pub(crate) fn set_streaming(&mut self) {}
+
+    pub(crate) fn record_result<T>(&self, _result: &Result<T>) {}
+
+    pub(crate) fn record_usage(&self, _usage: &TokenUsage, _response_model: &str) {}
+
+    pub(crate) fn record_chunk(&mut self, _chunk: &Result<ChatChunk>) {}
+
+    pub(crate) fn finish_stream(&mut self, _usage: Option<&TokenUsage>, _response_model: &str) {}
+
+    pub(crate) fn record_sample_chunk(&mut self, _chunk: &Result<SampleResponse>) {}
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use crate::error::GrokError;
+    use opentelemetry::{Key, Value};
+
+    fn attribute(attributes: &[opentelemetry::KeyValue], key: &'static str) -> Option<Value> {
+        attributes
+            .iter()
+            .find(|kv| kv.key == Key::from_static_str(key))
+            .map(|kv| kv.value.clone())
+    }
+
+    #[test]
+    fn test_genai_operation_names() {
+        assert_eq!(genai_operation("complete_chat"), Some("chat"));
+        assert_eq!(genai_operation("stream_chat"), Some("chat"));
+        assert_eq!(genai_operation("embed"), Some("embeddings"));
+        assert_eq!(genai_operation("sample_text"), Some("text_completion"));
+        assert_eq!(genai_operation("list_models"), None);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let metrics = RpcMetrics::new("complete_chat", Some("grok-4"));
+        let attributes = metrics.attributes_with_model("grok-4-0709").unwrap();
+
+        assert_eq!(
+            attribute(&attributes, "gen_ai.operation.name"),
+            Some(Value::from("chat"))
+        );
+        assert_eq!(
+            attribute(&attributes, "gen_ai.provider.name"),
+            Some(Value::from("x_ai"))
+        );
+        assert_eq!(
+            attribute(&attributes, "gen_ai.request.model"),
+            Some(Value::from("grok-4"))
+        );
+        assert_eq!(
+            attribute(&attributes, "gen_ai.response.model"),
+            Some(Value::from("grok-4-0709"))
+        );
+
+        assert!(RpcMetrics::new("tokenize", Some("grok-4"))
+            .attributes()
+            .is_none());
+    }
+
+    #[test]
+    fn test_error_type() {
+        let error = GrokError::Status(tonic::Status::unavailable("down"));
+        assert_eq!(error_type(&error), "Unavailable");
+        assert_eq!(
+            error_type(&GrokError::Auth("bad key".to_string())),
+            "_OTHER"
+        );
+    }
+
+    #[test]
+    fn test_output_tokens_include_reasoning() {
+        let usage = TokenUsage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 45,
+            reasoning_tokens: 30,
+            ..Default::default()
+        };
+        assert_eq!(output_tokens(&usage), 35);
+    }
+
+    #[test]
+    fn test_stream_finishes_once() {
+        let mut metrics = RpcMetrics::new("stream_chat", Some("grok-4"));
+        metrics.set_streaming();
+
+        metrics.record_result(&Ok(()));
+        assert!(!metrics.finished);
+
+        metrics.record_chunk(&Err(GrokError::Status(tonic::Status::unavailable("down"))));
+        assert!(metrics.finished);
+        metrics.record_chunk(&Err(GrokError::Status(tonic::Status::unavailable("down"))));
+        assert!(metrics.finished);
+    }
+}
@@ -0,0 +1,89 @@
+//! Instrumentation of client operations.
+//!
+//! Every [`GrokClient`](crate::GrokClient) operation runs through an [`RpcSpan`],
+//! which forwards what it observes to the `tracing` span ([`spans`], `tracing`
+//! feature) and the OpenTelemetry GenAI metrics ([`metrics`], `otel` feature).
+//! With neither feature enabled it compiles away.
+
+mod metrics;
+mod spans;
+
+pub(crate) use spans::record_retry;
+
+use crate::{
+    error::Result,
+    response::{ChatChunk, ChatResponse, TokenUsage},
+    sample::SampleResponse,
+};
+use metrics::RpcMetrics;
+use spans::TraceSpan;
+use std::{fmt::Debug, future::Future};
+
+/// The instrumentation of one client operation.
+pub(crate) struct RpcSpan {
+    trace: TraceSpan,
+    metrics: RpcMetrics,
+}
+
+impl RpcSpan {
+    /// Start instrumenting `operation`, optionally sent to `model`.
+    pub(crate) fn new(operation: &'static str, model: Option<&str>, record_content: bool) -> Self {
+        Self {
+            trace: TraceSpan::new(operation, model, record_content),
+            metrics: RpcMetrics::new(operation, model),
+        }
+    }
+
+    /// Mark the operation as streaming: it ends with its last chunk instead
+    /// of when [`run`](Self::run) returns.
+    pub(crate) fn streaming(mut self) -> Self {
+        self.metrics.set_streaming();
+        self
+    }
+
+    /// Run `future` inside the span and record its outcome.
+    pub(crate) async fn run<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        let result = self.trace.run(future).await;
+        self.metrics.record_result(&result);
+        result
+    }
+
+    /// Record the prompt, if content recording is enabled.
+    pub(crate) fn record_prompt(&self, prompt: &dyn Debug) {
+        self.trace.record_prompt(prompt);
+    }
+
+    /// Record the request id returned by the server.
+    pub(crate) fn record_request_id(&self, request_id: &str) {
+        self.trace.record_request_id(request_id);
+    }
+
+    /// Record a completed chat response.
+    pub(crate) fn record_response(&self, response: &ChatResponse) {
+        self.trace.record_response(response);
+        self.metrics.record_usage(&response.usage, &response.model);
+    }
+
+    /// Record one chunk of a chat stream.
+    pub(crate) fn record_chunk(&mut self, chunk: &Result<ChatChunk>) {
+        self.trace.record_chunk(chunk);
+        self.metrics.record_chunk(chunk);
+    }
+
+    /// Record the end of a chat stream, with the last usage it reported.
+    pub(crate) fn finish_stream(&mut self, usage: Option<&TokenUsage>, response_model: &str) {
+        self.trace.finish_stream(usage);
+        self.metrics.finish_stream(usage, response_model);
+    }
+
+    /// Record one chunk of a text sampling stream.
+    pub(crate) fn record_sample_chunk(&mut self, chunk: &Result<SampleResponse>) {
+        self.trace.record_sample_chunk(chunk);
+        self.metrics.record_sample_chunk(chunk);
+    }
+
+    /// Record a completed text sampling response.
+    pub(crate) fn record_sample_response(&self, response: &SampleResponse) {
+        self.trace.record_sample_response(response);
+    }
+}
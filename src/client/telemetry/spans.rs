@@ -0,0 +1,396 @@
+//! `tracing` spans for RPCs.
+//!
+//! With the `tracing` feature, every [`GrokClient`](crate::GrokClient) operation
+//! runs inside a `grok.rpc` span that records the model, request id, token
+//! usage (with reasoning tokens in their own field), finish reason, latency,
+//! retry attempts and, on failure, the gRPC status code. Streaming operations emit one event per chunk.
+//!
+//! Prompt and completion text are only recorded when
+//! [`GrokConfig::trace_content`](crate::GrokConfig::trace_content) is set.
+//!
+//! Without the feature, [`TraceSpan`] is a no-op and compiles away.
+
+use crate::{
+    error::{GrokError, Result},
+    response::{ChatChunk, ChatResponse, TokenUsage},
+    sample::SampleResponse,
+};
+use std::{fmt::Debug, future::Future, time::Duration};
+
+/// The `tracing` span of one client operation.
+pub(crate) struct TraceSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+    #[cfg(feature = "tracing")]
+    record_content: bool,
+    #[cfg(feature = "tracing")]
+    chunks: u64,
+}
+
+#[cfg(feature = "tracing")]
+impl TraceSpan {
+    /// Open the span of `operation`, optionally sent to `model`.
+    pub(crate) fn new(operation: &'static str, model: Option<&str>, record_content: bool) -> Self {
+        use tracing::field::Empty;
+
+        let span = tracing::info_span!(
+            "grok.rpc",
+            operation,
+            model,
+            request_id = Empty,
+            prompt_tokens = Empty,
+            completion_tokens = Empty,
+            reasoning_tokens = Empty,
+            total_tokens = Empty,
+            finish_reason = Empty,
+            latency_ms = Empty,
+            first_chunk_ms = Empty,
+            chunks = Empty,
+            retries = Empty,
+            grpc.code = Empty,
+            error = Empty,
+            prompt = Empty,
+            completion = Empty,
+        );
+
+        Self {
+            span,
+            start: std::time::Instant::now(),
+            record_content,
+            chunks: 0,
+        }
+    }
+
+    /// Run `future` inside the span and record its outcome.
+    pub(crate) async fn run<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        use tracing::Instrument;
+
+        let result = future.instrument(self.span.clone()).await;
+        if let Err(error) = &result {
+            self.record_error(error);
+        }
+        self.record_latency();
+        result
+    }
+
+    /// Record the prompt, if content recording is enabled.
+    pub(crate) fn record_prompt(&self, prompt: &dyn Debug) {
+        if self.record_content {
+            self.span.record("prompt", tracing::field::debug(prompt));
+        }
+    }
+
+    /// Record the request id returned by the server.
+    pub(crate) fn record_request_id(&self, request_id: &str) {
+        self.span.record("request_id", request_id);
+    }
+
+    /// Record a completed chat response.
+    pub(crate) fn record_response(&self, response: &ChatResponse) {
+        self.record_request_id(&response.request_id);
+        self.record_usage(&response.usage);
+        self.span.record(
+            "finish_reason",
+            tracing::field::display(&response.finish_reason),
+        );
+        if self.record_content {
+            self.span.record("completion", response.content.as_str());
+        }
+    }
+
+    /// Record one chunk of a chat stream.
+    pub(crate) fn record_chunk(&mut self, chunk: &Result<ChatChunk>) {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error) => return self.record_stream_error(error),
+        };
+
+        self.record_chunk_received();
+        if self.record_content {
+            tracing::trace!(
+                parent: &self.span,
+                index = chunk.index,
+                delta = chunk.delta.as_str(),
+                "chunk"
+            );
+        } else {
+            tracing::trace!(
+                parent: &self.span,
+                index = chunk.index,
+                delta_len = chunk.delta.len(),
+                "chunk"
+            );
+        }
+
+        if self.chunks == 1 {
+            self.record_request_id(&chunk.request_id);
+        }
+        if let Some(finish_reason) = &chunk.finish_reason {
+            self.span
+                .record("finish_reason", tracing::field::display(finish_reason));
+        }
+    }
+
+    /// Record the end of a chat stream, with the last usage it reported.
+    pub(crate) fn finish_stream(&self, usage: Option<&TokenUsage>) {
+        if let Some(usage) = usage {
+            self.record_usage(usage);
+        }
+        self.record_latency();
+    }
+
+    /// Record one chunk of a text sampling stream.
+    pub(crate) fn record_sample_chunk(&mut self, chunk: &Result<SampleResponse>) {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error) => return self.record_stream_error(error),
+        };
+
+        self.record_chunk_received();
+        if self.record_content {
+            let text: Vec<_> = chunk.choices.iter().map(|c| c.text.as_str()).collect();
+            tracing::trace!(parent: &self.span, text = ?text, "chunk");
+        } else {
+            tracing::trace!(parent: &self.span, choices = chunk.choices.len(), "chunk");
+        }
+
+        if self.chunks == 1 {
+            self.record_request_id(&chunk.id);
+        }
+        if chunk.choices.iter().any(|c| !c.finish_reason.is_empty()) {
+            self.record_sample_response(chunk);
+            self.record_latency();
+        }
+    }
+
+    /// Record a completed text sampling response.
+    pub(crate) fn record_sample_response(&self, response: &SampleResponse) {
+        self.record_request_id(&response.id);
+        self.span.record("total_tokens", response.total_tokens);
+        if let Some(choice) = response
+            .choices
+            .iter()
+            .find(|c| !c.finish_reason.is_empty())
+        {
+            self.span
+                .record("finish_reason", choice.finish_reason.as_str());
+        }
+        if self.record_content {
+            let text: Vec<_> = response.choices.iter().map(|c| c.text.as_str()).collect();
+            self.span.record("completion", tracing::field::debug(text));
+        }
+    }
+
+    fn record_chunk_received(&mut self) {
+        self.chunks += 1;
+        self.span.record("chunks", self.chunks);
+        if self.chunks == 1 {
+            self.span
+                .record("first_chunk_ms", self.start.elapsed().as_millis() as u64);
+        }
+    }
+
+    fn record_usage(&self, usage: &TokenUsage) {
+        self.span.record("prompt_tokens", usage.prompt_tokens);
+        self.span
+            .record("completion_tokens", usage.completion_tokens);
+        // Billed as completion tokens, but not counted in `completion_tokens`
+        if usage.reasoning_tokens > 0 {
+            self.span.record("reasoning_tokens", usage.reasoning_tokens);
+        }
+        self.span.record("total_tokens", usage.total_tokens);
+    }
+
+    fn record_latency(&self) {
+        self.span
+            .record("latency_ms", self.start.elapsed().as_millis() as u64);
+    }
+
+    fn record_error(&self, error: &GrokError) {
+        if let Some(code) = error.status_code() {
+            self.span.record("grpc.code", tracing::field::debug(code));
+        }
+        self.span.record("error", tracing::field::display(error));
+        tracing::warn!(parent: &self.span, %error, "RPC failed");
+    }
+
+    fn record_stream_error(&self, error: &GrokError) {
+        self.record_error(error);
+        self.record_latency();
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl TraceSpan {
+    pub(crate) fn new(
+        _operation: &'static str,
+        _model: Option<&str>,
+        _record_content: bool,
+    ) -> Self {
+        Self {}
+    }
+
+    pub(crate) async fn run<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        future.await
+    }
+
+    pub(crate) fn record_prompt(&self, _prompt: &dyn Debug) {}
+
+    pub(crate) fn record_request_id(&self, _request_id: &str) {}
+
+    pub(crate) fn record_response(&self, _response: &ChatResponse) {}
+
+    pub(crate) fn record_chunk(&mut self, _chunk: &Result<ChatChunk>) {}
+
+    pub(crate) fn finish_stream(&self, _usage: Option<&TokenUsage>) {}
+
+    pub(crate) fn record_sample_chunk(&mut self, _chunk: &Result<SampleResponse>) {}
+
+    pub(crate) fn record_sample_response(&self, _response: &SampleResponse) {}
+}
+
+/// Record a retry of the RPC running in the current span.
+pub(crate) fn record_retry(attempt: u32, error: &GrokError, delay: Duration) {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Span::current().record("retries", attempt);
+        tracing::warn!(
+            attempt,
+            grpc.code = ?error.status_code(),
+            delay_ms = delay.as_millis() as u64,
+            %error,
+            "retrying RPC"
+        );
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, error, delay);
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+
+    /// Collects every field recorded on any span, as `name=value` strings.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &tracing::Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    impl Recorder {
+        fn fields(&self) -> Vec<String> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn response() -> ChatResponse {
+        ChatResponse {
+            usage: TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+                ..Default::default()
+            },
+            ..ChatResponse::test_reply("req_1", "secret answer")
+        }
+    }
+
+    #[test]
+    fn test_records_response_without_content() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let span = TraceSpan::new("complete_chat", Some("grok-2"), false);
+            span.record_prompt(&"secret prompt");
+            span.record_response(&response());
+        });
+
+        let fields = recorder.fields();
+        assert!(fields.contains(&"operation=\"complete_chat\"".to_string()));
+        assert!(fields.contains(&"model=\"grok-2\"".to_string()));
+        assert!(fields.contains(&"request_id=\"req_1\"".to_string()));
+        assert!(fields.contains(&"total_tokens=15".to_string()));
+        assert!(fields.contains(&"finish_reason=stop".to_string()));
+        assert!(!fields.iter().any(|f| f.starts_with("reasoning_tokens=")));
+        assert!(!fields.iter().any(|f| f.contains("secret")));
+    }
+
+    #[test]
+    fn test_records_reasoning_tokens() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let span = TraceSpan::new("complete_chat", Some("grok-4"), false);
+            let mut response = response();
+            response.usage.reasoning_tokens = 30;
+            response.usage.total_tokens = 45;
+            span.record_response(&response);
+        });
+
+        let fields = recorder.fields();
+        assert!(fields.contains(&"completion_tokens=5".to_string()));
+        assert!(fields.contains(&"reasoning_tokens=30".to_string()));
+        assert!(fields.contains(&"total_tokens=45".to_string()));
+    }
+
+    #[test]
+    fn test_records_content_when_enabled() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let span = TraceSpan::new("complete_chat", Some("grok-2"), true);
+            span.record_prompt(&"secret prompt");
+            span.record_response(&response());
+        });
+
+        let fields = recorder.fields();
+        assert!(fields
+            .iter()
+            .any(|f| f.starts_with("prompt=") && f.contains("secret prompt")));
+        assert!(fields.contains(&"completion=\"secret answer\"".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_records_error_code() {
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let span = TraceSpan::new("list_models", None, false);
+        let result: Result<()> = span
+            .run(async { Err(GrokError::Status(tonic::Status::unavailable("down"))) })
+            .await;
+
+        assert!(result.is_err());
+        let fields = recorder.fields();
+        assert!(fields.contains(&"grpc.code=Unavailable".to_string()));
+        assert!(fields.iter().any(|f| f.starts_with("latency_ms=")));
+    }
+}
@@ -0,0 +1,596 @@
+//! Fitting chat requests into a model's context window.
+//!
+//! A [`ContextManager`] counts the tokens of a request's messages and, when
+//! they do not fit in the model's window (minus the tokens reserved for the
+//! reply), shortens the history with a [`TruncationStrategy`] before the
+//! request is sent.
+//!
+//! The history is split into turns: each turn starts with a user message and
+//! holds the assistant replies and tool results that follow it, so tool calls
+//! are never separated from their results. The leading system messages and
+//! the last turn are always kept.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{ChatRequest, ContextManager, GrokClient, TruncationStrategy};
+//!
+//! # async fn example(history: ChatRequest) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//! let context = ContextManager::new(TruncationStrategy::DropOldest);
+//!
+//! let request = context.fit(&client, history.with_max_tokens(1024)).await?;
+//! let response = client.complete_chat(request).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::{estimate_tokens, GrokClient, TokenEstimate},
+    error::{GrokError, Result},
+    models::LanguageModel,
+    request::{ChatRequest, Message},
+    tokenize::TokenCounter,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+/// Tokens added to every message for its role and separators.
+///
+/// The exact framing is not published, so this is an approximation.
+pub const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// Number of token counts cached by default with [`TokenEstimate::Tokenize`].
+const TOKEN_CACHE_CAPACITY: usize = 4096;
+
+/// Prefix of the system message holding a summary of dropped turns.
+const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:\n";
+
+/// Boxed future returned by a [`Summarizer`].
+type SummaryFuture = Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+
+/// Callback that summarizes the turns dropped by [`TruncationStrategy::Summarize`].
+///
+/// It receives the dropped messages, oldest first, and returns the text of
+/// the summary. It usually asks a model, and can use any client to do so.
+#[derive(Clone)]
+pub struct Summarizer(Arc<dyn Fn(Vec<Message>) -> SummaryFuture + Send + Sync>);
+
+impl Summarizer {
+    /// Wrap an async callback.
+    pub fn new<F, Fut>(summarize: F) -> Self
+    where
+        F: Fn(Vec<Message>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        Self(Arc::new(move |messages| Box::pin(summarize(messages))))
+    }
+
+    async fn summarize(&self, messages: Vec<Message>) -> Result<String> {
+        (self.0)(messages).await
+    }
+}
+
+impl fmt::Debug for Summarizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Summarizer")
+    }
+}
+
+/// How a [`ContextManager`] shortens a history that does not fit.
+#[derive(Clone, Debug, Default)]
+pub enum TruncationStrategy {
+    /// Drop the oldest turns until the request fits.
+    #[default]
+    DropOldest,
+    /// Keep the system prompt and at most the last `n` turns (at least one),
+    /// even when more would fit. Older turns among those are still dropped
+    /// if they do not fit.
+    KeepLastTurns(usize),
+    /// Replace the turns that do not fit with a summary, sent as a system
+    /// message after the system prompt.
+    ///
+    /// If the request still does not fit with the summary, more turns are
+    /// dropped without being summarized.
+    Summarize(Summarizer),
+}
+
+impl TruncationStrategy {
+    /// Summarize dropped turns with an async callback; see [`Summarize`](Self::Summarize).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xai_grpc_client::{ChatRequest, GrokClient, Message, TruncationStrategy};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = GrokClient::from_env().await?;
+    ///
+    /// let strategy = TruncationStrategy::summarize(move |messages: Vec<Message>| {
+    ///     let client = client.clone();
+    ///     async move {
+    ///         let request = ChatRequest::from_messages(messages)
+    ///             .user_message("Summarize this conversation in a few sentences.")
+    ///             .with_max_tokens(200);
+    ///         Ok(client.complete_chat(request).await?.content)
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summarize<F, Fut>(summarize: F) -> Self
+    where
+        F: Fn(Vec<Message>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        Self::Summarize(Summarizer::new(summarize))
+    }
+}
+
+/// Fits chat requests into the context window of their model.
+///
+/// The window is the model's
+/// [`max_prompt_length`](LanguageModel::max_prompt_length), fetched once per
+/// model with [`GrokClient::get_model`] unless set with
+/// [`with_window`](Self::with_window) or [`with_models`](Self::with_models).
+/// The request's `max_tokens` (or the [reply reserve](Self::with_reply_reserve))
+/// is subtracted from it to leave room for the reply.
+///
+/// Only message text is counted: images, file attachments, tool definitions
+/// and history stored on the server (`previous_response_id`) are not, so keep
+/// some margin when using them.
+///
+/// The manager is cheap to clone; clones share their caches.
+#[derive(Clone, Debug)]
+pub struct ContextManager {
+    strategy: TruncationStrategy,
+    token_estimate: TokenEstimate,
+    counter: TokenCounter,
+    window: Option<u32>,
+    reply_reserve: u32,
+    windows: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl Default for ContextManager {
+    fn default() -> Self {
+        Self {
+            strategy: TruncationStrategy::default(),
+            token_estimate: TokenEstimate::default(),
+            counter: TokenCounter::new().with_cache(TOKEN_CACHE_CAPACITY),
+            window: None,
+            reply_reserve: 0,
+            windows: Arc::default(),
+        }
+    }
+}
+
+impl ContextManager {
+    /// Create a manager that shortens histories with `strategy`.
+    pub fn new(strategy: TruncationStrategy) -> Self {
+        Self {
+            strategy,
+            ..Default::default()
+        }
+    }
+
+    /// How tokens are counted (default: [`TokenEstimate::Characters`]).
+    ///
+    /// With [`TokenEstimate::Tokenize`], messages are counted concurrently
+    /// with [`GrokClient::tokenize`] and the counts are cached, so a growing
+    /// history only tokenizes its new messages. If counting fails, the
+    /// character estimate is used instead.
+    pub fn with_token_estimate(mut self, token_estimate: TokenEstimate) -> Self {
+        self.token_estimate = token_estimate;
+        self
+    }
+
+    /// Count tokens with `counter` when using [`TokenEstimate::Tokenize`]
+    /// (default: 8 concurrent requests and a cache of 4096 counts).
+    ///
+    /// Pass a clone of another counter to share its cache.
+    pub fn with_token_counter(mut self, counter: TokenCounter) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    /// Use a fixed context window of `tokens` for every model.
+    pub fn with_window(mut self, tokens: u32) -> Self {
+        self.window = Some(tokens);
+        self
+    }
+
+    /// Use the context windows of `models` (and their aliases) instead of
+    /// fetching them.
+    pub fn with_models(self, models: Vec<LanguageModel>) -> Self {
+        {
+            let mut windows = self.windows.lock().unwrap();
+            for model in models {
+                let window = u32::try_from(model.max_prompt_length).unwrap_or(0);
+                for alias in model.aliases {
+                    windows.insert(alias, window);
+                }
+                windows.insert(model.name, window);
+            }
+        }
+        self
+    }
+
+    /// Tokens kept for the reply when a request does not set `max_tokens`
+    /// (default: 0).
+    pub fn with_reply_reserve(mut self, tokens: u32) -> Self {
+        self.reply_reserve = tokens;
+        self
+    }
+
+    /// The truncation strategy.
+    pub fn strategy(&self) -> &TruncationStrategy {
+        &self.strategy
+    }
+
+    /// The context window of `model`, in tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`GrokClient::get_model`] if the window is not
+    /// known yet and cannot be fetched.
+    pub async fn window(&self, client: &GrokClient, model: &str) -> Result<u32> {
+        if let Some(window) = self.window {
+            return Ok(window);
+        }
+        if let Some(window) = self.windows.lock().unwrap().get(model) {
+            return Ok(*window);
+        }
+
+        let window = u32::try_from(client.get_model(model).await?.max_prompt_length).unwrap_or(0);
+        self.windows
+            .lock()
+            .unwrap()
+            .insert(model.to_string(), window);
+        Ok(window)
+    }
+
+    /// Count the tokens of `messages` for `model`, including
+    /// [`MESSAGE_OVERHEAD_TOKENS`] per message.
+    pub async fn count_tokens(
+        &self,
+        client: &GrokClient,
+        model: &str,
+        messages: &[Message],
+    ) -> u32 {
+        self.message_tokens(client, model, messages)
+            .await
+            .into_iter()
+            .fold(0, u32::saturating_add)
+    }
+
+    /// The tokens of each of `messages`, including the message overhead.
+    async fn message_tokens(
+        &self,
+        client: &GrokClient,
+        model: &str,
+        messages: &[Message],
+    ) -> Vec<u32> {
+        let texts: Vec<String> = messages.iter().map(Message::text).collect();
+
+        let counts = match self.token_estimate {
+            TokenEstimate::Characters => None,
+            // Counting is best effort: fall back rather than fail the request
+            TokenEstimate::Tokenize => self
+                .counter
+                .count_batch(client, model, texts.iter().map(String::as_str))
+                .await
+                .ok(),
+        };
+
+        counts
+            .unwrap_or_else(|| texts.iter().map(|text| estimate_tokens(text)).collect())
+            .into_iter()
+            .map(|tokens| tokens.saturating_add(MESSAGE_OVERHEAD_TOKENS))
+            .collect()
+    }
+
+    /// Shorten the history of `request` so it fits in its model's window,
+    /// leaving room for the reply.
+    ///
+    /// Requests that already fit are returned unchanged, except with
+    /// [`TruncationStrategy::KeepLastTurns`].
+    ///
+    /// # Errors
+    ///
+    /// - [`GrokError::ContextOverflow`] if the system prompt and the last turn
+    ///   alone do not fit
+    /// - The error from [`GrokClient::get_model`] if the window cannot be fetched
+    /// - The error from the [`Summarizer`], if any
+    pub async fn fit(&self, client: &GrokClient, mut request: ChatRequest) -> Result<ChatRequest> {
+        let model = request
+            .model()
+            .unwrap_or(client.default_model())
+            .to_string();
+        let window = self.window(client, &model).await?;
+        let limit = window.saturating_sub(request.max_tokens().unwrap_or(self.reply_reserve));
+
+        let messages = request.take_messages();
+        let mut tokens = self
+            .message_tokens(client, &model, &messages)
+            .await
+            .into_iter();
+
+        let (preamble, turns) = split_turns(messages);
+        let mut total = tokens
+            .by_ref()
+            .take(preamble.len())
+            .fold(0, u32::saturating_add);
+        let mut sizes = Vec::with_capacity(turns.len());
+        for turn in &turns {
+            let size = tokens
+                .by_ref()
+                .take(turn.len())
+                .fold(0, u32::saturating_add);
+            total = total.saturating_add(size);
+            sizes.push(size);
+        }
+
+        let mut dropped = 0;
+        if let TruncationStrategy::KeepLastTurns(n) = self.strategy {
+            while turns.len() - dropped > n.max(1) {
+                total -= sizes[dropped];
+                dropped += 1;
+            }
+        }
+        drop_until_fits(&sizes, &mut dropped, &mut total, limit);
+
+        let mut summary = None;
+        if let TruncationStrategy::Summarize(summarizer) = &self.strategy {
+            if dropped > 0 {
+                let text = summarizer.summarize(turns[..dropped].concat()).await?;
+                let message = Message::System(format!("{SUMMARY_PREFIX}{text}"));
+                let size = self
+                    .count_tokens(client, &model, std::slice::from_ref(&message))
+                    .await;
+                total = total.saturating_add(size);
+                summary = Some(message);
+                drop_until_fits(&sizes, &mut dropped, &mut total, limit);
+            }
+        }
+
+        if total > limit {
+            return Err(GrokError::ContextOverflow {
+                tokens: total,
+                limit,
+            });
+        }
+
+        Ok(preamble
+            .into_iter()
+            .chain(summary)
+            .chain(turns.into_iter().skip(dropped).flatten())
+            .fold(request, ChatRequest::add_message))
+    }
+}
+
+/// Drop the oldest turns until `total` fits in `limit`, always keeping the last turn.
+fn drop_until_fits(sizes: &[u32], dropped: &mut usize, total: &mut u32, limit: u32) {
+    while *total > limit && *dropped + 1 < sizes.len() {
+        *total -= sizes[*dropped];
+        *dropped += 1;
+    }
+}
+
+/// Split `messages` into the leading system messages and the turns after them.
+///
+/// Each turn starts with a user message and holds the messages up to the next one.
+fn split_turns(messages: Vec<Message>) -> (Vec<Message>, Vec<Vec<Message>>) {
+    let mut messages = messages.into_iter().peekable();
+
+    let mut preamble = Vec::new();
+    while let Some(message) = messages.next_if(|message| matches!(message, Message::System(_))) {
+        preamble.push(message);
+    }
+
+    let mut turns: Vec<Vec<Message>> = Vec::new();
+    for message in messages {
+        match turns.last_mut() {
+            Some(turn) if !matches!(message, Message::User(_)) => turn.push(message),
+            _ => turns.push(vec![message]),
+        }
+    }
+    (preamble, turns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A text of 36 characters: 9 estimated tokens, 13 with the message overhead.
+    fn text(label: &str) -> String {
+        format!("{label:-<36}")
+    }
+
+    /// A system prompt followed by `turns` user/assistant exchanges (26 tokens each).
+    fn history(turns: usize) -> ChatRequest {
+        (0..turns).fold(
+            ChatRequest::new()
+                .with_model("grok-4")
+                .system_message(text("system")),
+            |request, turn| {
+                request
+                    .user_message(text(&format!("question {turn}")))
+                    .assistant_message(text(&format!("answer {turn}")))
+            },
+        )
+    }
+
+    fn texts(request: &ChatRequest) -> Vec<String> {
+        request
+            .messages()
+            .iter()
+            .map(|message| message.text().trim_end_matches('-').to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_turns_keeps_tool_results_with_their_turn() {
+        let messages = vec![
+            Message::System("a".into()),
+            Message::System("b".into()),
+            Message::User("c".into()),
+            Message::Assistant("d".into()),
+            Message::Tool {
+                tool_call_id: "call-1".into(),
+                content: "e".into(),
+            },
+            Message::User("f".into()),
+        ];
+
+        let (preamble, turns) = split_turns(messages);
+        assert_eq!(preamble.len(), 2);
+        assert_eq!(turns.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_fitting_request_is_unchanged() {
+        let context = ContextManager::new(TruncationStrategy::DropOldest).with_window(1000);
+        let request = context
+            .fit(&GrokClient::test_client(), history(3))
+            .await
+            .unwrap();
+        assert_eq!(request.messages().len(), 7);
+        assert_eq!(request.model(), Some("grok-4"));
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_reserves_reply_tokens() {
+        // 13 (system) + 3 * 26 = 91 tokens; 100 - 30 leaves room for two turns
+        let context = ContextManager::new(TruncationStrategy::DropOldest).with_window(100);
+        let request = context
+            .fit(&GrokClient::test_client(), history(3).with_max_tokens(30))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            texts(&request),
+            ["system", "question 1", "answer 1", "question 2", "answer 2"]
+        );
+        assert_eq!(request.max_tokens(), Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_keep_last_turns() {
+        let context = ContextManager::new(TruncationStrategy::KeepLastTurns(1)).with_window(1000);
+        let request = context
+            .fit(&GrokClient::test_client(), history(3))
+            .await
+            .unwrap();
+        assert_eq!(texts(&request), ["system", "question 2", "answer 2"]);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_when_last_turn_does_not_fit() {
+        let context = ContextManager::new(TruncationStrategy::DropOldest)
+            .with_window(50)
+            .with_reply_reserve(20);
+
+        let error = context
+            .fit(&GrokClient::test_client(), history(2))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            GrokError::ContextOverflow {
+                tokens: 39,
+                limit: 30
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_replaces_dropped_turns() {
+        let context = ContextManager::new(TruncationStrategy::summarize(
+            |messages: Vec<Message>| async move { Ok(format!("{} messages", messages.len())) },
+        ))
+        .with_window(85);
+
+        let request = context
+            .fit(&GrokClient::test_client(), history(3))
+            .await
+            .unwrap();
+        assert_eq!(
+            texts(&request),
+            [
+                "system",
+                "Summary of the earlier conversation:\n2 messages",
+                "question 1",
+                "answer 1",
+                "question 2",
+                "answer 2"
+            ]
+        );
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_tokenize_counts_are_cached() {
+        use crate::mock::{MockGrokServer, MockMethod};
+        use crate::models::Modality;
+        use crate::tokenize::{Token, TokenizeResponse};
+
+        let tokens = |count: usize| TokenizeResponse {
+            tokens: vec![
+                Token {
+                    token_id: 1,
+                    string_token: "x".to_string(),
+                    token_bytes: vec![],
+                };
+                count
+            ],
+            model: "grok-4".to_string(),
+        };
+
+        let server = MockGrokServer::new();
+        server.set_language_models(vec![LanguageModel {
+            name: "grok-4".to_string(),
+            aliases: vec![],
+            version: "1.0".to_string(),
+            input_modalities: vec![Modality::Text],
+            output_modalities: vec![Modality::Text],
+            prompt_text_token_price: 0,
+            prompt_image_token_price: 0,
+            cached_prompt_token_price: 0,
+            completion_text_token_price: 0,
+            search_price: 0,
+            max_prompt_length: 100,
+            system_fingerprint: "fp".to_string(),
+        }]);
+        server
+            .push_tokenize_response(tokens(6))
+            .push_tokenize_response(tokens(50));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let context = ContextManager::new(TruncationStrategy::DropOldest)
+            .with_token_estimate(TokenEstimate::Tokenize)
+            .with_token_counter(TokenCounter::new().with_concurrency(1).with_cache(10));
+        let request = ChatRequest::new()
+            .with_model("grok-4")
+            .user_message("first")
+            .user_message("second");
+
+        // 10 + 54 tokens fit in the window of 100
+        let fitted = context.fit(&client, request.clone()).await.unwrap();
+        assert_eq!(fitted.messages().len(), 2);
+
+        // Counted from the cache: the second request fails if tokenized again
+        let fitted = context
+            .fit(&client, request.with_max_tokens(40))
+            .await
+            .unwrap();
+        assert_eq!(fitted.messages().len(), 1);
+        assert_eq!(server.call_count(MockMethod::TokenizeText), 2);
+        assert_eq!(server.call_count(MockMethod::GetLanguageModel), 1);
+    }
+}
@@ -0,0 +1,440 @@
+//! Multi-turn conversations with automatic history management.
+//!
+//! A [`Conversation`] keeps the system prompt, the message history and the
+//! default request options of a chat. Each [`send`](Conversation::send)
+//! appends the user message and the assistant reply (with its tool calls), so
+//! the next turn carries the whole history without rebuilding requests by hand.
+//!
+//! With [server-side history](Conversation::with_server_side_history), the
+//! history is stored by xAI and each turn only sends the new messages along
+//! with the `previous_response_id` of the last reply.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{ChatRequest, Conversation, GrokClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//!
+//! let mut conversation = Conversation::from_request(
+//!     ChatRequest::new().with_model("grok-4").with_temperature(0.2),
+//! )
+//! .with_system_prompt("You are a terse assistant.");
+//!
+//! let reply = conversation.send(&client, "Name a prime number.").await?;
+//! println!("{}", reply.content);
+//!
+//! // Try a different follow-up from the same point
+//! let mut alternative = conversation.clone();
+//! alternative.send(&client, "Another one?").await?;
+//! conversation.send(&client, "Is it even?").await?;
+//!
+//! // Drop the last exchange
+//! conversation.undo();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::GrokClient,
+    error::Result,
+    request::{ChatRequest, Message, MessageContent},
+    response::ChatResponse,
+};
+
+/// A chat session that records every turn.
+///
+/// Cloning a conversation branches it: the copies share their past and evolve
+/// independently from then on.
+///
+/// With the `serde` feature, the whole conversation (history, options and
+/// server-side state) can be saved and restored as JSON. Without it,
+/// [`transcript`](Self::transcript) exports the messages, which
+/// [`from_request`](Self::from_request) can import again.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Conversation {
+    system_prompt: Option<String>,
+    defaults: ChatRequest,
+    messages: Vec<Message>,
+    turns: Vec<Turn>,
+    /// Number of messages that belong to completed turns (or to the
+    /// starting history); messages after it are waiting to be sent.
+    exchanged: usize,
+    server_side: bool,
+    /// The last reply stored by xAI, when using server-side history.
+    response_id: Option<String>,
+    /// Number of messages already stored by xAI.
+    stored: usize,
+}
+
+/// The state of a conversation before one turn, to undo it.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Turn {
+    start: usize,
+    response_id: Option<String>,
+    stored: usize,
+}
+
+impl Conversation {
+    /// Start an empty conversation with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a conversation from `request`.
+    ///
+    /// The request's messages become the starting history, which
+    /// [`undo`](Self::undo) never removes, and its other options (model,
+    /// temperature, tools, ...) are used for every turn.
+    pub fn from_request(mut request: ChatRequest) -> Self {
+        let messages = request.take_messages();
+        Self {
+            exchanged: messages.len(),
+            messages,
+            defaults: request,
+            ..Default::default()
+        }
+    }
+
+    /// Set the system prompt, sent before the history.
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Keep the history on xAI's servers instead of resending it every turn.
+    ///
+    /// Requests are sent with `store_messages`, and each turn only sends the
+    /// messages added since the last reply, with that reply's id as
+    /// `previous_response_id`. The first turn after enabling it sends the
+    /// whole history. Changing the system prompt afterwards has no effect on
+    /// the stored history.
+    pub fn with_server_side_history(mut self, enabled: bool) -> Self {
+        if self.server_side != enabled {
+            self.server_side = enabled;
+            self.response_id = None;
+            self.stored = 0;
+        }
+        self
+    }
+
+    /// The system prompt, if any.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.system_prompt.as_deref()
+    }
+
+    /// The options used for every turn.
+    pub fn defaults(&self) -> &ChatRequest {
+        &self.defaults
+    }
+
+    /// The history, without the system prompt.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Number of completed turns.
+    pub fn turns(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Id of the last reply stored on xAI's servers, when using server-side history.
+    pub fn last_response_id(&self) -> Option<&str> {
+        self.response_id.as_deref()
+    }
+
+    /// Returns true if the history is kept on xAI's servers.
+    pub fn is_server_side(&self) -> bool {
+        self.server_side
+    }
+
+    /// The system prompt followed by the history.
+    pub fn transcript(&self) -> Vec<Message> {
+        self.system_prompt
+            .iter()
+            .map(|prompt| Message::System(prompt.clone()))
+            .chain(self.messages.iter().cloned())
+            .collect()
+    }
+
+    /// Append a message without sending it.
+    ///
+    /// It is sent with the next [`complete`](Self::complete) or
+    /// [`send`](Self::send), as part of that turn.
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Append the result of a tool call from the last reply, without sending it.
+    pub fn add_tool_result(&mut self, tool_call_id: impl Into<String>, content: impl Into<String>) {
+        self.push(Message::Tool {
+            tool_call_id: tool_call_id.into(),
+            content: content.into(),
+        });
+    }
+
+    /// Send a user message and record the reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`GrokClient::complete_chat`], leaving the
+    /// conversation as it was before the call.
+    pub async fn send(
+        &mut self,
+        client: &GrokClient,
+        content: impl Into<MessageContent>,
+    ) -> Result<ChatResponse> {
+        let len = self.messages.len();
+        self.push(Message::User(content.into()));
+
+        let result = self.complete(client).await;
+        if result.is_err() {
+            self.messages.truncate(len);
+        }
+        result
+    }
+
+    /// Send the messages added since the last reply (such as tool results)
+    /// and record the reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`GrokClient::complete_chat`], leaving the
+    /// conversation unchanged.
+    pub async fn complete(&mut self, client: &GrokClient) -> Result<ChatResponse> {
+        let response = client.complete_chat(self.request()).await?;
+        self.record_reply(&response);
+        Ok(response)
+    }
+
+    /// The request for the next turn.
+    pub(crate) fn request(&self) -> ChatRequest {
+        let mut request = self.defaults.clone();
+
+        let pending = match (&self.response_id, self.server_side) {
+            (Some(response_id), true) => {
+                request = request.with_previous_response_id(response_id);
+                &self.messages[self.stored..]
+            }
+            _ => {
+                if let Some(prompt) = &self.system_prompt {
+                    request = request.system_message(prompt);
+                }
+                &self.messages[..]
+            }
+        };
+
+        if self.server_side {
+            request = request.with_store_messages(true);
+        }
+        pending
+            .iter()
+            .cloned()
+            .fold(request, |request, message| request.add_message(message))
+    }
+
+    /// Append `response` to the history, closing the current turn.
+    fn record_reply(&mut self, response: &ChatResponse) {
+        self.turns.push(Turn {
+            start: self.exchanged,
+            response_id: self.response_id.clone(),
+            stored: self.stored,
+        });
+
+        self.messages.push(response.to_message());
+        self.exchanged = self.messages.len();
+        if self.server_side {
+            self.response_id = Some(response.request_id.clone());
+            self.stored = self.messages.len();
+        }
+    }
+
+    /// Remove the last turn: its messages, its reply and any message added
+    /// since. Returns false if there was no turn to undo.
+    ///
+    /// With server-side history, the next turn continues from the reply
+    /// before the removed one.
+    pub fn undo(&mut self) -> bool {
+        let Some(turn) = self.turns.pop() else {
+            return false;
+        };
+
+        self.messages.truncate(turn.start);
+        self.exchanged = turn.start;
+        self.response_id = turn.response_id;
+        self.stored = turn.stored;
+        true
+    }
+
+    /// A copy of the conversation as it was after its first `turns` turns.
+    pub fn branch_at(&self, turns: usize) -> Conversation {
+        let mut branch = self.clone();
+        while branch.turns.len() > turns {
+            branch.undo();
+        }
+        branch
+    }
+
+    /// Remove every turn, keeping the starting history and options.
+    pub fn clear(&mut self) {
+        *self = self.branch_at(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(id: &str, content: &str) -> ChatResponse {
+        ChatResponse {
+            model: "grok-4".to_string(),
+            ..ChatResponse::test_reply(id, content)
+        }
+    }
+
+    fn texts(messages: &[Message]) -> Vec<String> {
+        messages.iter().map(Message::text).collect()
+    }
+
+    /// Simulate one successful turn without a server.
+    fn exchange(conversation: &mut Conversation, user: &str, id: &str, answer: &str) {
+        conversation.push(Message::User(user.into()));
+        conversation.record_reply(&reply(id, answer));
+    }
+
+    #[test]
+    fn test_request_carries_history_and_defaults() {
+        let mut conversation =
+            Conversation::from_request(ChatRequest::new().with_model("grok-4").with_max_tokens(50))
+                .with_system_prompt("Be brief");
+        exchange(&mut conversation, "Hi", "resp-1", "Hello");
+        conversation.push(Message::User("Bye".into()));
+
+        let request = conversation.request();
+        assert_eq!(request.model(), Some("grok-4"));
+        assert_eq!(request.max_tokens(), Some(50));
+        assert!(!request.store_messages());
+        assert!(request.previous_response_id().is_none());
+        assert_eq!(
+            texts(request.messages()),
+            ["Be brief", "Hi", "Hello", "Bye"]
+        );
+        assert!(matches!(
+            request.messages()[2],
+            Message::AssistantReply { .. }
+        ));
+    }
+
+    #[test]
+    fn test_server_side_history_sends_only_new_messages() {
+        let mut conversation = Conversation::new()
+            .with_system_prompt("Be brief")
+            .with_server_side_history(true);
+
+        conversation.push(Message::User("Hi".into()));
+        let first = conversation.request();
+        assert!(first.store_messages());
+        assert!(first.previous_response_id().is_none());
+        assert_eq!(texts(first.messages()), ["Be brief", "Hi"]);
+
+        conversation.record_reply(&reply("resp-1", "Hello"));
+        conversation.add_tool_result("call-1", "42");
+        let second = conversation.request();
+        assert_eq!(second.previous_response_id(), Some("resp-1"));
+        assert_eq!(texts(second.messages()), ["42"]);
+    }
+
+    #[test]
+    fn test_undo_restores_previous_turn() {
+        let mut conversation = Conversation::from_request(ChatRequest::new().user_message("Start"))
+            .with_server_side_history(true);
+        exchange(&mut conversation, "One", "resp-1", "1");
+        exchange(&mut conversation, "Two", "resp-2", "2");
+        conversation.push(Message::User("Unsent".into()));
+
+        assert!(conversation.undo());
+        assert_eq!(conversation.turns(), 1);
+        assert_eq!(texts(conversation.messages()), ["Start", "One", "1"]);
+        assert_eq!(conversation.last_response_id(), Some("resp-1"));
+
+        assert!(conversation.undo());
+        assert!(!conversation.undo());
+        // The starting history is kept
+        assert_eq!(texts(conversation.messages()), ["Start"]);
+        assert!(conversation.last_response_id().is_none());
+    }
+
+    #[test]
+    fn test_branch_at() {
+        let mut conversation = Conversation::new();
+        exchange(&mut conversation, "One", "resp-1", "1");
+        exchange(&mut conversation, "Two", "resp-2", "2");
+
+        let mut branch = conversation.branch_at(1);
+        exchange(&mut branch, "Other", "resp-3", "3");
+
+        assert_eq!(texts(branch.messages()), ["One", "1", "Other", "3"]);
+        assert_eq!(conversation.turns(), 2);
+        assert_eq!(texts(conversation.messages())[2], "Two");
+
+        conversation.clear();
+        assert!(conversation.messages().is_empty());
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let mut conversation = Conversation::new().with_system_prompt("Be brief");
+        exchange(&mut conversation, "Hi", "resp-1", "Hello");
+
+        let transcript = conversation.transcript();
+        assert_eq!(texts(&transcript), ["Be brief", "Hi", "Hello"]);
+
+        let restored = Conversation::from_request(ChatRequest::from_messages(transcript));
+        assert_eq!(
+            texts(restored.request().messages()),
+            ["Be brief", "Hi", "Hello"]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut conversation = Conversation::from_request(ChatRequest::new().with_model("grok-4"))
+            .with_server_side_history(true);
+        exchange(&mut conversation, "Hi", "resp-1", "Hello");
+
+        let json = serde_json::to_string(&conversation).unwrap();
+        let mut restored: Conversation = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.last_response_id(), Some("resp-1"));
+        assert_eq!(restored.defaults().model(), Some("grok-4"));
+        assert!(restored.undo());
+        assert!(restored.messages().is_empty());
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_send_records_replies() {
+        use crate::mock::{MockGrokServer, MockMethod, Status};
+
+        let server = MockGrokServer::new();
+        server.push_chat_response(reply("resp-1", "Hello"));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let mut conversation = Conversation::new();
+        let response = conversation.send(&client, "Hi").await.unwrap();
+        assert_eq!(response.content, "Hello");
+        assert_eq!(texts(conversation.messages()), ["Hi", "Hello"]);
+
+        // A failed turn leaves the conversation untouched
+        server.push_error(MockMethod::GetCompletion, Status::invalid_argument("bad"));
+        assert!(conversation.send(&client, "Again").await.is_err());
+        assert_eq!(conversation.messages().len(), 2);
+        assert_eq!(conversation.turns(), 1);
+    }
+}
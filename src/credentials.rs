@@ -0,0 +1,345 @@
+//! Credential providers.
+//!
+//! The API key sent with each request comes from a [`CredentialProvider`],
+//! which is asked for it on every call. Swapping the key a provider returns
+//! takes effect on the next request, without rebuilding the
+//! [`GrokClient`](crate::GrokClient) or interrupting streams already running.
+//!
+//! Built-in providers:
+//!
+//! - [`StaticKey`]: one fixed key (what [`GrokConfig::api_key`](crate::GrokConfig::api_key) uses);
+//! - [`EnvKey`]: an environment variable, read on every request;
+//! - [`FileKey`]: a file, reloaded when its modification time changes;
+//! - [`KeyPool`]: several keys used in turn.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use xai_grpc_client::{credentials::FileKey, GrokClient, GrokConfig};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! // Rotated weekly by writing a new key to the file
+//! let client = GrokClient::new(GrokConfig {
+//!     credentials: Some(Arc::new(FileKey::new("/run/secrets/xai-api-key"))),
+//!     ..Default::default()
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{GrokError, Result};
+use secrecy::{ExposeSecret, SecretString};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        RwLock,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Source of the API key sent with each request.
+///
+/// `api_key` is called synchronously for every RPC, so implementations should
+/// return quickly, caching the key rather than fetching it remotely each time.
+pub trait CredentialProvider: Send + Sync {
+    /// The API key to use for the next request.
+    ///
+    /// # Errors
+    ///
+    /// An error fails the request with [`GrokError::Auth`].
+    fn api_key(&self) -> Result<SecretString>;
+}
+
+/// A single API key that never changes.
+#[derive(Clone)]
+pub struct StaticKey(SecretString);
+
+impl StaticKey {
+    /// Use `api_key` for every request.
+    pub fn new(api_key: SecretString) -> Self {
+        Self(api_key)
+    }
+}
+
+impl CredentialProvider for StaticKey {
+    fn api_key(&self) -> Result<SecretString> {
+        Ok(self.0.clone())
+    }
+}
+
+/// An API key read from an environment variable on every request.
+#[derive(Clone, Debug)]
+pub struct EnvKey {
+    var: String,
+}
+
+impl EnvKey {
+    /// Read the key from the environment variable `var`.
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl Default for EnvKey {
+    /// Read the key from `XAI_API_KEY`.
+    fn default() -> Self {
+        Self::new("XAI_API_KEY")
+    }
+}
+
+impl CredentialProvider for EnvKey {
+    fn api_key(&self) -> Result<SecretString> {
+        let key = std::env::var(&self.var)
+            .map_err(|e| GrokError::Auth(format!("Cannot read {}: {e}", self.var)))?;
+        Ok(SecretString::from(key))
+    }
+}
+
+/// An API key read from a file and reloaded when the file changes.
+///
+/// The file's modification time is checked at most once per
+/// [`check_interval`](Self::with_check_interval) (default: 1 second); when it
+/// changed, the key is read again. Surrounding whitespace is trimmed. If the
+/// file cannot be read after a successful load, the last key keeps being used.
+pub struct FileKey {
+    path: PathBuf,
+    check_interval: Duration,
+    state: RwLock<Option<FileKeyState>>,
+}
+
+struct FileKeyState {
+    key: SecretString,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl FileKey {
+    /// Read the key from the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            check_interval: Duration::from_secs(1),
+            state: RwLock::new(None),
+        }
+    }
+
+    /// Set how often the file is checked for changes.
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    fn load(&self) -> Result<FileKeyState> {
+        let error = |e: std::io::Error| {
+            GrokError::Auth(format!(
+                "Cannot read API key file {}: {e}",
+                self.path.display()
+            ))
+        };
+
+        let modified = std::fs::metadata(&self.path)
+            .map_err(error)?
+            .modified()
+            .ok();
+        let key = std::fs::read_to_string(&self.path).map_err(error)?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(GrokError::Auth(format!(
+                "API key file {} is empty",
+                self.path.display()
+            )));
+        }
+
+        Ok(FileKeyState {
+            key: SecretString::from(key.to_string()),
+            modified,
+            checked: Instant::now(),
+        })
+    }
+}
+
+impl CredentialProvider for FileKey {
+    fn api_key(&self) -> Result<SecretString> {
+        if let Some(state) = self.state.read().unwrap().as_ref() {
+            if state.checked.elapsed() < self.check_interval {
+                return Ok(state.key.clone());
+            }
+        }
+
+        let mut state = self.state.write().unwrap();
+        match state.as_mut() {
+            None => {
+                let loaded = self.load()?;
+                let key = loaded.key.clone();
+                *state = Some(loaded);
+                Ok(key)
+            }
+            Some(current) => {
+                current.checked = Instant::now();
+                let modified = std::fs::metadata(&self.path)
+                    .and_then(|m| m.modified())
+                    .ok();
+                if modified.is_some() && modified != current.modified {
+                    if let Ok(loaded) = self.load() {
+                        *current = loaded;
+                    }
+                }
+                Ok(current.key.clone())
+            }
+        }
+    }
+}
+
+/// Several API keys used in turn, one per request.
+pub struct KeyPool {
+    keys: Vec<SecretString>,
+    next: AtomicUsize,
+}
+
+impl KeyPool {
+    /// Rotate through `keys` in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::Config`] if `keys` is empty or contains an empty key.
+    pub fn new(keys: Vec<SecretString>) -> Result<Self> {
+        if keys.is_empty() || keys.iter().any(|k| k.expose_secret().is_empty()) {
+            return Err(GrokError::Config(
+                "Key pool needs at least one non-empty key".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            keys,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Number of keys in the pool.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Always false: a pool holds at least one key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl CredentialProvider for KeyPool {
+    fn api_key(&self) -> Result<SecretString> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.keys.len();
+        Ok(self.keys[index].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: &str) -> SecretString {
+        SecretString::from(value.to_string())
+    }
+
+    fn expose(provider: &dyn CredentialProvider) -> String {
+        provider.api_key().unwrap().expose_secret().to_string()
+    }
+
+    #[test]
+    fn test_static_key() {
+        assert_eq!(expose(&StaticKey::new(key("xai-1"))), "xai-1");
+    }
+
+    #[test]
+    fn test_env_key_missing() {
+        let provider = EnvKey::new("XAI_GRPC_CLIENT_TEST_UNSET_KEY");
+        assert!(matches!(provider.api_key(), Err(GrokError::Auth(_))));
+    }
+
+    #[test]
+    fn test_key_pool_round_robin() {
+        let pool = KeyPool::new(vec![key("a"), key("b"), key("c")]).unwrap();
+        let used: Vec<_> = (0..4).map(|_| expose(&pool)).collect();
+        assert_eq!(used, ["a", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_key_pool_rejects_empty() {
+        assert!(KeyPool::new(vec![]).is_err());
+        assert!(KeyPool::new(vec![key("a"), key("")]).is_err());
+    }
+
+    #[test]
+    fn test_file_key_reloads_on_change() {
+        let path = std::env::temp_dir().join(format!(
+            "xai-grpc-client-key-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        std::fs::write(&path, "xai-old\n").unwrap();
+
+        let provider = FileKey::new(&path).with_check_interval(Duration::ZERO);
+        assert_eq!(expose(&provider), "xai-old");
+
+        // Make sure the modification time moves even on coarse filesystems
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&path, "xai-new").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(expose(&provider), "xai-new");
+
+        // A missing file keeps the last key
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(expose(&provider), "xai-new");
+    }
+
+    #[test]
+    fn test_file_key_missing_file() {
+        let provider = FileKey::new("/nonexistent/xai-api-key");
+        assert!(matches!(provider.api_key(), Err(GrokError::Auth(_))));
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_client_rotates_keys_per_request() {
+        use crate::{
+            mock::{MockGrokServer, MockMethod},
+            GrokClient, GrokConfig,
+        };
+        use std::sync::Arc;
+
+        let server = MockGrokServer::new();
+        let handle = server.start().await.unwrap();
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                credentials: Some(Arc::new(
+                    KeyPool::new(vec![key("xai-first"), key("xai-second")]).unwrap(),
+                )),
+                ..Default::default()
+            },
+        );
+
+        let mut used = Vec::new();
+        for _ in 0..2 {
+            client.list_models().await.unwrap();
+            let metadata = server
+                .last_metadata(MockMethod::ListLanguageModels)
+                .unwrap();
+            used.push(
+                metadata
+                    .get("authorization")
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+            );
+        }
+
+        assert_eq!(used, ["Bearer xai-first", "Bearer xai-second"]);
+    }
+}
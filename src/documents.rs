@@ -6,6 +6,7 @@ use crate::proto;
 
 /// Request for document search
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DocumentSearchRequest {
     /// Search query
     pub query: String,
@@ -21,6 +22,8 @@ pub struct DocumentSearchRequest {
 
 /// Ranking metric for search results
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum RankingMetric {
     /// L2 distance (lower is better)
     L2Distance,
@@ -28,6 +31,31 @@ pub enum RankingMetric {
     CosineSimilarity,
 }
 
+impl RankingMetric {
+    /// Score `candidate` against `query`: the cosine similarity or the L2
+    /// distance of the two vectors.
+    pub fn score(self, query: &[f32], candidate: &[f32]) -> f32 {
+        match self {
+            Self::L2Distance => crate::embedding::l2_distance(query, candidate),
+            Self::CosineSimilarity => crate::embedding::cosine_similarity(query, candidate),
+        }
+    }
+
+    /// Returns true if higher scores are better matches.
+    pub fn higher_is_better(self) -> bool {
+        matches!(self, Self::CosineSimilarity)
+    }
+
+    /// Order two scores from best to worst match.
+    pub fn compare(self, a: f32, b: f32) -> std::cmp::Ordering {
+        if self.higher_is_better() {
+            b.total_cmp(&a)
+        } else {
+            a.total_cmp(&b)
+        }
+    }
+}
+
 impl DocumentSearchRequest {
     /// Create a new document search request
     pub fn new(query: impl Into<String>) -> Self {
@@ -67,6 +95,7 @@ impl DocumentSearchRequest {
 
 /// Response from document search
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DocumentSearchResponse {
     /// Matching document chunks
     pub matches: Vec<SearchMatch>,
@@ -74,6 +103,7 @@ pub struct DocumentSearchResponse {
 
 /// A matching document chunk
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SearchMatch {
     /// Document file ID
     pub file_id: String,
@@ -153,6 +183,23 @@ mod tests {
         assert_ne!(RankingMetric::L2Distance, RankingMetric::CosineSimilarity);
     }
 
+    #[test]
+    fn test_ranking_metric_score_and_compare() {
+        let query = [1.0, 0.0];
+        let near = [1.0, 1.0];
+        let far = [-1.0, 0.0];
+
+        let cosine = RankingMetric::CosineSimilarity;
+        assert!(cosine.higher_is_better());
+        assert!(cosine.score(&query, &near) > cosine.score(&query, &far));
+        assert_eq!(cosine.compare(0.9, 0.1), std::cmp::Ordering::Less);
+
+        let l2 = RankingMetric::L2Distance;
+        assert!(!l2.higher_is_better());
+        assert!(l2.score(&query, &near) < l2.score(&query, &far));
+        assert_eq!(l2.compare(0.9, 0.1), std::cmp::Ordering::Greater);
+    }
+
     #[test]
     fn test_search_match_from_proto() {
         let proto_match = proto::SearchMatch {
@@ -14,7 +14,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     let request = EmbedRequest::new("embed-large-v1")
 //!         .add_text("Hello, world!")
@@ -37,7 +37,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     let request = EmbedRequest::new("embed-vision-v1")
 //!         .add_image("https://example.com/image.jpg");
@@ -47,14 +47,49 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Keeping vectors compact
+//!
+//! With [`EmbedRequest::with_vector_format`], each embedding is kept as a
+//! [`CompactVector`] (raw `f32`, `f16` or `i8` quantized) instead of a
+//! `Vec<f32>`, to shrink in-memory vector stores by up to four times.
+//!
+//! ```no_run
+//! use xai_grpc_client::{EmbedEncodingFormat, EmbedRequest, GrokClient, VectorFormat};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//!
+//! let request = EmbedRequest::new("embed-large-v1")
+//!     .add_text("Hello, world!")
+//!     .with_encoding_format(EmbedEncodingFormat::Base64)
+//!     .with_vector_format(VectorFormat::I8);
+//!
+//! let response = client.embed(request).await?;
+//! let compact = response.embeddings[0].compact.as_ref().unwrap();
+//! println!("{} dimensions in {} bytes", compact.len(), compact.as_bytes().len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    documents::RankingMetric,
+    error::{GrokError, Result},
+    proto,
+    request::ImageDetail,
+};
+use std::borrow::Cow;
 
-use crate::{proto, request::ImageDetail};
+/// Maximum number of inputs the API embeds in a single request.
+pub const MAX_EMBED_BATCH_SIZE: usize = 128;
 
 /// Request for generating embeddings.
 ///
 /// Supports embedding text strings, images, or a mix of both depending on
-/// the model capabilities. You can embed up to 128 inputs in a single request.
+/// the model capabilities. You can embed up to 128 inputs in a single request;
+/// use [`GrokClient::embed_many`](crate::GrokClient::embed_many) for more.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmbedRequest {
     /// Inputs to embed (text or images).
     pub inputs: Vec<EmbedInput>,
@@ -64,6 +99,11 @@ pub struct EmbedRequest {
     pub encoding_format: EmbedEncodingFormat,
     /// Optional user identifier for tracking.
     pub user: Option<String>,
+    /// Keep the embeddings in this compact format instead of `Vec<f32>`.
+    ///
+    /// Applied on the client; see [`Embedding::compact`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vector_format: Option<VectorFormat>,
 }
 
 impl EmbedRequest {
@@ -82,6 +122,7 @@ impl EmbedRequest {
             model: model.into(),
             encoding_format: EmbedEncodingFormat::Float,
             user: None,
+            vector_format: None,
         }
     }
 
@@ -152,10 +193,91 @@ impl EmbedRequest {
         self.user = Some(user.into());
         self
     }
+
+    /// Keep the embeddings as [`CompactVector`]s in `format`.
+    ///
+    /// Combine it with [`EmbedEncodingFormat::Base64`] to also shrink the
+    /// response on the wire: with [`VectorFormat::F32`], the decoded bytes are
+    /// then kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xai_grpc_client::{EmbedRequest, VectorFormat};
+    ///
+    /// let request = EmbedRequest::new("embed-large-v1")
+    ///     .add_text("Hello, world!")
+    ///     .with_vector_format(VectorFormat::F16);
+    /// ```
+    pub fn with_vector_format(mut self, format: VectorFormat) -> Self {
+        self.vector_format = Some(format);
+        self
+    }
+}
+
+/// How [`GrokClient::embed_many`](crate::GrokClient::embed_many) splits and
+/// schedules a large request.
+///
+/// # Examples
+///
+/// ```
+/// use xai_grpc_client::EmbedBatching;
+///
+/// let batching = EmbedBatching::new().with_batch_size(64).with_concurrency(8);
+/// assert_eq!(batching.batch_size(), 64);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbedBatching {
+    batch_size: usize,
+    concurrency: usize,
+}
+
+impl Default for EmbedBatching {
+    fn default() -> Self {
+        Self {
+            batch_size: MAX_EMBED_BATCH_SIZE,
+            concurrency: 4,
+        }
+    }
+}
+
+impl EmbedBatching {
+    /// Batches of 128 inputs, 4 in flight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of inputs per request (default: 128).
+    ///
+    /// Values are clamped between 1 and [`MAX_EMBED_BATCH_SIZE`].
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_EMBED_BATCH_SIZE);
+        self
+    }
+
+    /// Set the maximum number of requests in flight (default: 4).
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Number of inputs per request.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Maximum number of requests in flight.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
 }
 
 /// Input to be embedded (text or image).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum EmbedInput {
     /// Text string to embed.
     Text(String),
@@ -170,6 +292,8 @@ pub enum EmbedInput {
 
 /// Encoding format for embedding vectors.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum EmbedEncodingFormat {
     /// Return embeddings as arrays of floats.
     Float,
@@ -179,6 +303,7 @@ pub enum EmbedEncodingFormat {
 
 /// Response from an embedding request.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmbedResponse {
     /// Request identifier.
     pub id: String,
@@ -194,15 +319,335 @@ pub struct EmbedResponse {
 
 /// A single embedding vector.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Embedding {
     /// Index of the input that generated this embedding.
     pub index: usize,
     /// The embedding vector.
+    ///
+    /// Empty when the request set a [`vector_format`](EmbedRequest::vector_format):
+    /// the vector is then only kept in [`compact`](Self::compact). Use
+    /// [`values`](Self::values) to read it either way.
     pub vector: Vec<f32>,
+    /// The vector in the format requested with
+    /// [`EmbedRequest::with_vector_format`], if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compact: Option<CompactVector>,
+}
+
+impl Embedding {
+    /// The embedding values, decoded from [`compact`](Self::compact) if the
+    /// vector is only kept in compact form.
+    pub fn values(&self) -> Cow<'_, [f32]> {
+        match &self.compact {
+            Some(compact) if self.vector.is_empty() => Cow::Owned(compact.to_f32()),
+            _ => Cow::Borrowed(&self.vector),
+        }
+    }
+
+    /// Dot product with `other`.
+    pub fn dot(&self, other: &Embedding) -> f32 {
+        dot(&self.values(), &other.values())
+    }
+
+    /// Cosine similarity with `other`, between -1 and 1 (higher is closer).
+    pub fn cosine_similarity(&self, other: &Embedding) -> f32 {
+        cosine_similarity(&self.values(), &other.values())
+    }
+
+    /// Euclidean distance to `other` (lower is closer).
+    pub fn l2_distance(&self, other: &Embedding) -> f32 {
+        l2_distance(&self.values(), &other.values())
+    }
+
+    /// Score `other` with `metric`, as the Documents service ranks matches.
+    pub fn similarity(&self, other: &Embedding, metric: RankingMetric) -> f32 {
+        metric.score(&self.values(), &other.values())
+    }
+
+    /// Scale the vector to unit length, so that the dot product equals the
+    /// cosine similarity. A zero vector is left unchanged.
+    ///
+    /// A [`compact`](Self::compact) vector is re-encoded in its format.
+    pub fn normalize(&mut self) {
+        if !self.vector.is_empty() {
+            normalize(&mut self.vector);
+        }
+        if let Some(compact) = &mut self.compact {
+            let mut values = compact.to_f32();
+            normalize(&mut values);
+            *compact = CompactVector::from_f32(&values, compact.format());
+        }
+    }
+}
+
+/// Dot product of `a` and `b`.
+///
+/// The vectors should have the same dimensions; extra values of the longer
+/// one are ignored.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Euclidean length of `vector`.
+pub fn norm(vector: &[f32]) -> f32 {
+    dot(vector, vector).sqrt()
+}
+
+/// Cosine similarity of `a` and `b`, or 0 if either is a zero vector.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot(a, b) / norms
+    }
+}
+
+/// Euclidean distance between `a` and `b`.
+pub fn l2_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Scale `vector` to unit length in place, leaving a zero vector unchanged.
+pub fn normalize(vector: &mut [f32]) {
+    let norm = norm(vector);
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+}
+
+/// Storage format of a [`CompactVector`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VectorFormat {
+    /// 32-bit floats, little-endian: lossless, 4 bytes per value.
+    F32,
+    /// IEEE 754 half-precision floats, little-endian: 2 bytes per value,
+    /// about three significant digits.
+    F16,
+    /// Signed bytes scaled by the largest absolute value of the vector:
+    /// 1 byte per value, 255 levels.
+    I8,
+}
+
+impl VectorFormat {
+    /// Number of bytes used by each value.
+    pub fn value_size(self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::F16 => 2,
+            Self::I8 => 1,
+        }
+    }
+}
+
+/// An embedding vector stored as raw bytes in a compact [`VectorFormat`].
+///
+/// Values are decoded as `stored * scale`; the scale is 1 except with
+/// [`VectorFormat::I8`]. The bytes can be read without copying with
+/// [`as_bytes`](Self::as_bytes), for instance to write them to disk, and
+/// restored with [`from_bytes`](Self::from_bytes).
+///
+/// # Examples
+///
+/// ```
+/// use xai_grpc_client::{CompactVector, VectorFormat};
+///
+/// let compact = CompactVector::from_f32(&[0.5, -1.0, 0.25], VectorFormat::I8);
+/// assert_eq!(compact.as_bytes().len(), 3);
+///
+/// let values = compact.to_f32();
+/// assert!((values[0] - 0.5).abs() < 0.01);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactVector {
+    format: VectorFormat,
+    scale: f32,
+    bytes: Vec<u8>,
+}
+
+impl CompactVector {
+    /// Encode `values` in `format`.
+    ///
+    /// With [`VectorFormat::I8`], values are scaled so that the largest
+    /// absolute value maps to 127; non-finite values are stored as 0.
+    pub fn from_f32(values: &[f32], format: VectorFormat) -> Self {
+        let (scale, bytes) = match format {
+            VectorFormat::F32 => (1.0, values.iter().flat_map(|v| v.to_le_bytes()).collect()),
+            VectorFormat::F16 => (
+                1.0,
+                values
+                    .iter()
+                    .flat_map(|v| f32_to_f16(*v).to_le_bytes())
+                    .collect(),
+            ),
+            VectorFormat::I8 => {
+                let max = values
+                    .iter()
+                    .filter(|v| v.is_finite())
+                    .fold(0f32, |max, v| max.max(v.abs()));
+                let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+                let bytes = values
+                    .iter()
+                    .map(|v| {
+                        let level = if v.is_finite() { v / scale } else { 0.0 };
+                        level.round().clamp(-127.0, 127.0) as i8 as u8
+                    })
+                    .collect();
+                (scale, bytes)
+            }
+        };
+        Self {
+            format,
+            scale,
+            bytes,
+        }
+    }
+
+    /// Restore a vector from bytes returned by [`as_bytes`](Self::as_bytes),
+    /// without copying them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::InvalidRequest`] if the length of `bytes` is not a
+    /// multiple of the [value size](VectorFormat::value_size) of `format`.
+    pub fn from_bytes(format: VectorFormat, scale: f32, bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() % format.value_size() != 0 {
+            return Err(GrokError::InvalidRequest(format!(
+                "{} bytes is not a whole number of {format:?} values",
+                bytes.len()
+            )));
+        }
+        Ok(Self {
+            format,
+            scale,
+            bytes,
+        })
+    }
+
+    /// The storage format.
+    pub fn format(&self) -> VectorFormat {
+        self.format
+    }
+
+    /// The factor applied to stored values when decoding.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        self.bytes.len() / self.format.value_size()
+    }
+
+    /// Returns true if the vector has no values.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The stored bytes, little-endian.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Take the stored bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Decode the values as floats.
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self.format {
+            VectorFormat::F32 => self
+                .bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+            VectorFormat::F16 => self
+                .bytes
+                .chunks_exact(2)
+                .map(|chunk| f16_to_f32(u16::from_le_bytes([chunk[0], chunk[1]])))
+                .collect(),
+            VectorFormat::I8 => self
+                .bytes
+                .iter()
+                .map(|byte| f32::from(*byte as i8) * self.scale)
+                .collect(),
+        }
+    }
+}
+
+/// Convert to the bits of the nearest half-precision float (ties to even).
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Infinity, or NaN kept quiet
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // Drop `shift` low bits of `mantissa`, rounding to nearest, ties to even
+    let round = |mantissa: u32, shift: u32| {
+        let kept = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        if rest > halfway || (rest == halfway && kept & 1 == 1) {
+            kept + 1
+        } else {
+            kept
+        }
+    };
+
+    if exponent <= 0 {
+        // Subnormal, or too small and rounded to zero
+        if exponent < -10 {
+            return sign;
+        }
+        let shift = (14 - exponent) as u32;
+        return sign | round(mantissa | 0x0080_0000, shift) as u16;
+    }
+
+    // A carry out of the mantissa correctly bumps the exponent (up to infinity)
+    sign | round(((exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+/// Convert the bits of a half-precision float to `f32`.
+fn f16_to_f32(half: u16) -> f32 {
+    let negative = half & 0x8000 != 0;
+    let exponent = u32::from((half >> 10) & 0x1f);
+    let mantissa = u32::from(half & 0x03ff);
+
+    let magnitude = match exponent {
+        0 => mantissa as f32 * 2f32.powi(-24),
+        0x1f => f32::from_bits(0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(((exponent + 127 - 15) << 23) | (mantissa << 13)),
+    };
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
 }
 
 /// Usage statistics for an embedding request.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmbeddingUsage {
     /// Number of text embeddings generated.
     pub num_text_embeddings: u32,
@@ -210,6 +655,13 @@ pub struct EmbeddingUsage {
     pub num_image_embeddings: u32,
 }
 
+impl std::ops::AddAssign<&EmbeddingUsage> for EmbeddingUsage {
+    fn add_assign(&mut self, other: &EmbeddingUsage) {
+        self.num_text_embeddings += other.num_text_embeddings;
+        self.num_image_embeddings += other.num_image_embeddings;
+    }
+}
+
 impl From<proto::EmbeddingUsage> for EmbeddingUsage {
     fn from(proto: proto::EmbeddingUsage) -> Self {
         Self {
@@ -287,4 +739,296 @@ mod tests {
         assert_eq!(usage.num_text_embeddings, 5);
         assert_eq!(usage.num_image_embeddings, 2);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_embed_response_serde_round_trip() {
+        let response = EmbedResponse {
+            id: "emb_1".to_string(),
+            embeddings: vec![Embedding {
+                index: 0,
+                vector: vec![0.5, -0.25],
+                compact: None,
+            }],
+            usage: EmbeddingUsage {
+                num_text_embeddings: 1,
+                num_image_embeddings: 0,
+            },
+            model: "embed-large-v1".to_string(),
+            system_fingerprint: "fp".to_string(),
+        };
+
+        let value = serde_json::to_value(&response).unwrap();
+        let restored: EmbedResponse = serde_json::from_value(value.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&restored).unwrap(), value);
+        assert_eq!(restored.embeddings[0].vector, vec![0.5, -0.25]);
+    }
+
+    #[test]
+    fn test_embed_batching_bounds() {
+        let batching = EmbedBatching::new();
+        assert_eq!(batching.batch_size(), MAX_EMBED_BATCH_SIZE);
+        assert_eq!(batching.concurrency(), 4);
+
+        let batching = batching.with_batch_size(1000).with_concurrency(0);
+        assert_eq!(batching.batch_size(), MAX_EMBED_BATCH_SIZE);
+        assert_eq!(batching.concurrency(), 1);
+        assert_eq!(EmbedBatching::new().with_batch_size(0).batch_size(), 1);
+    }
+
+    #[test]
+    fn test_embedding_usage_add_assign() {
+        let mut usage = EmbeddingUsage {
+            num_text_embeddings: 2,
+            num_image_embeddings: 1,
+        };
+        usage += &EmbeddingUsage {
+            num_text_embeddings: 3,
+            num_image_embeddings: 0,
+        };
+
+        assert_eq!(usage.num_text_embeddings, 5);
+        assert_eq!(usage.num_image_embeddings, 1);
+    }
+
+    #[cfg(feature = "mock-server")]
+    fn batch_response(id: &str, values: &[f32]) -> EmbedResponse {
+        EmbedResponse {
+            id: id.to_string(),
+            embeddings: values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| Embedding {
+                    index,
+                    vector: vec![*value],
+                    compact: None,
+                })
+                .collect(),
+            usage: EmbeddingUsage {
+                num_text_embeddings: values.len() as u32,
+                num_image_embeddings: 0,
+            },
+            model: "embed-large-v1".to_string(),
+            system_fingerprint: "fp".to_string(),
+        }
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_embed_many_rebases_indices() {
+        use crate::mock::{MockGrokServer, MockMethod};
+
+        let server = MockGrokServer::new();
+        server
+            .push_embed_response(batch_response("batch-1", &[0.0, 1.0]))
+            .push_embed_response(batch_response("batch-2", &[2.0, 3.0]))
+            .push_embed_response(batch_response("batch-3", &[4.0]));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let request = (0..5).fold(EmbedRequest::new("embed-large-v1"), |request, i| {
+            request.add_text(format!("passage {i}"))
+        });
+        let batching = EmbedBatching::new().with_batch_size(2).with_concurrency(1);
+        let response = client.embed_many(request, batching).await.unwrap();
+
+        assert_eq!(server.call_count(MockMethod::Embed), 3);
+        assert_eq!(response.id, "batch-1");
+        assert_eq!(response.usage.num_text_embeddings, 5);
+        for (position, embedding) in response.embeddings.iter().enumerate() {
+            assert_eq!(embedding.index, position);
+            assert_eq!(embedding.vector, [position as f32]);
+        }
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_embed_many_fails_on_batch_error() {
+        use crate::mock::{MockGrokServer, MockMethod, Status};
+
+        let server = MockGrokServer::new();
+        server
+            .push_embed_response(batch_response("batch-1", &[0.0]))
+            .push_error(MockMethod::Embed, Status::invalid_argument("too long"));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let request = EmbedRequest::new("embed-large-v1")
+            .add_text("a")
+            .add_text("b");
+        let batching = EmbedBatching::new().with_batch_size(1).with_concurrency(1);
+        let error = client.embed_many(request, batching).await.unwrap_err();
+
+        assert!(matches!(error, crate::error::GrokError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn test_f16_conversion() {
+        for value in [
+            0.0,
+            -0.0,
+            1.0,
+            -2.5,
+            0.333_251_95,
+            65504.0,
+            6.103_515_6e-5,
+            5.960_464_5e-8,
+        ] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value, "{value}");
+        }
+
+        // Ties round to even, overflow goes to infinity, tiny values to zero
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+        assert_eq!(f16_to_f32(f32_to_f16(70000.0)), f32::INFINITY);
+        assert_eq!(f16_to_f32(f32_to_f16(-1e-9)), 0.0);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        let value = 0.1f32;
+        assert!((f16_to_f32(f32_to_f16(value)) - value).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_compact_vector_formats() {
+        let values = [0.5, -1.0, 0.25, 0.0];
+
+        let f32s = CompactVector::from_f32(&values, VectorFormat::F32);
+        assert_eq!(f32s.as_bytes().len(), 16);
+        assert_eq!(f32s.to_f32(), values);
+
+        let f16s = CompactVector::from_f32(&values, VectorFormat::F16);
+        assert_eq!(f16s.as_bytes().len(), 8);
+        assert_eq!(f16s.to_f32(), values);
+
+        let i8s = CompactVector::from_f32(&values, VectorFormat::I8);
+        assert_eq!(i8s.len(), 4);
+        assert_eq!(i8s.as_bytes()[1] as i8, -127);
+        for (decoded, value) in i8s.to_f32().iter().zip(values) {
+            assert!((decoded - value).abs() <= i8s.scale() / 2.0);
+        }
+
+        let zeros = CompactVector::from_f32(&[0.0, 0.0], VectorFormat::I8);
+        assert_eq!(zeros.to_f32(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_compact_vector_from_bytes() {
+        let compact = CompactVector::from_f32(&[1.0, 2.0], VectorFormat::F16);
+        let restored = CompactVector::from_bytes(
+            compact.format(),
+            compact.scale(),
+            compact.as_bytes().to_vec(),
+        )
+        .unwrap();
+        assert_eq!(restored, compact);
+
+        assert!(CompactVector::from_bytes(VectorFormat::F32, 1.0, vec![0; 6]).is_err());
+    }
+
+    #[test]
+    fn test_embedding_values() {
+        let compact = CompactVector::from_f32(&[1.0, -1.0], VectorFormat::F16);
+        let embedding = Embedding {
+            index: 0,
+            vector: Vec::new(),
+            compact: Some(compact),
+        };
+        assert_eq!(embedding.values().as_ref(), [1.0, -1.0]);
+
+        let embedding = Embedding {
+            index: 0,
+            vector: vec![0.5],
+            compact: None,
+        };
+        assert!(matches!(embedding.values(), Cow::Borrowed(values) if values == [0.5]));
+    }
+
+    #[test]
+    fn test_vector_math() {
+        let a = [3.0, 4.0];
+        let b = [4.0, 3.0];
+
+        assert_eq!(dot(&a, &b), 24.0);
+        assert_eq!(norm(&a), 5.0);
+        assert!((cosine_similarity(&a, &b) - 0.96).abs() < 1e-6);
+        assert!((cosine_similarity(&a, &[-3.0, -4.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&a, &[0.0, 0.0]), 0.0);
+        assert!((l2_distance(&a, &b) - 2f32.sqrt()).abs() < 1e-6);
+        assert_eq!(l2_distance(&a, &a), 0.0);
+    }
+
+    #[test]
+    fn test_embedding_similarity() {
+        let a = Embedding {
+            index: 0,
+            vector: vec![1.0, 0.0],
+            compact: None,
+        };
+        let b = Embedding {
+            index: 1,
+            vector: Vec::new(),
+            compact: Some(CompactVector::from_f32(&[0.0, 2.0], VectorFormat::F32)),
+        };
+
+        assert_eq!(a.dot(&b), 0.0);
+        assert_eq!(a.cosine_similarity(&b), 0.0);
+        assert!((a.l2_distance(&b) - 5f32.sqrt()).abs() < 1e-6);
+        assert_eq!(
+            a.similarity(&b, RankingMetric::L2Distance),
+            a.l2_distance(&b)
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut vector = [3.0, 4.0];
+        normalize(&mut vector);
+        assert_eq!(vector, [0.6, 0.8]);
+
+        let mut zero = [0.0, 0.0];
+        normalize(&mut zero);
+        assert_eq!(zero, [0.0, 0.0]);
+
+        let mut embedding = Embedding {
+            index: 0,
+            vector: Vec::new(),
+            compact: Some(CompactVector::from_f32(&[0.0, -8.0], VectorFormat::F16)),
+        };
+        embedding.normalize();
+        assert!(embedding.vector.is_empty());
+        assert_eq!(embedding.values().as_ref(), [0.0, -1.0]);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_embed_base64_into_compact_vectors() {
+        use crate::mock::MockGrokServer;
+
+        let server = MockGrokServer::new();
+        server
+            .push_embed_response(batch_response("emb-1", &[0.25]))
+            .push_embed_response(batch_response("emb-2", &[0.75]));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let request = EmbedRequest::new("embed-large-v1")
+            .add_text("a")
+            .with_encoding_format(EmbedEncodingFormat::Base64);
+
+        let response = client.embed(request.clone()).await.unwrap();
+        assert_eq!(response.embeddings[0].vector, [0.25]);
+        assert!(response.embeddings[0].compact.is_none());
+
+        let response = client
+            .embed(request.with_vector_format(VectorFormat::F32))
+            .await
+            .unwrap();
+        let embedding = &response.embeddings[0];
+        assert!(embedding.vector.is_empty());
+        assert_eq!(
+            embedding.compact.as_ref().unwrap().as_bytes(),
+            0.75f32.to_le_bytes()
+        );
+    }
 }
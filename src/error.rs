@@ -36,13 +36,20 @@ pub enum GrokError {
     Transport(#[from] tonic::transport::Error),
 
     /// gRPC status error returned by the server.
+    ///
+    /// Status codes with a dedicated variant ([`Auth`](Self::Auth),
+    /// [`RateLimit`](Self::RateLimit), [`InvalidRequest`](Self::InvalidRequest))
+    /// are converted into that variant instead.
     #[error("gRPC status error: {0}")]
-    Status(#[from] tonic::Status),
+    Status(tonic::Status),
 
     /// Rate limit exceeded. The client should wait before retrying.
     #[error("Rate limit exceeded, retry after {retry_after_secs} seconds")]
     RateLimit {
         /// Number of seconds to wait before retrying.
+        ///
+        /// Taken from the `retry-after` or `grpc-retry-pushback-ms` metadata when
+        /// the server provides it, otherwise defaults to 1 second.
         retry_after_secs: u64,
     },
 
@@ -54,6 +61,45 @@ pub enum GrokError {
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    /// The model's reply did not match the requested output type.
+    #[error("Response did not match the expected schema: {message}")]
+    StructuredOutput {
+        /// Why the reply could not be deserialized.
+        message: String,
+        /// The raw text returned by the model.
+        raw: String,
+    },
+
+    /// A tool call's arguments did not match the expected argument type.
+    #[error("Invalid arguments for tool `{function}`: {message}")]
+    ToolArguments {
+        /// Name of the function that was called.
+        function: String,
+        /// Why the arguments could not be deserialized.
+        message: String,
+        /// The raw arguments sent by the model.
+        arguments: String,
+    },
+
+    /// The spending cap of the [`UsageTracker`](crate::UsageTracker) was reached.
+    #[error("Usage budget exceeded: spent ${spent_usd:.4} of ${budget_usd:.4}")]
+    BudgetExceeded {
+        /// Amount spent so far, in USD.
+        spent_usd: f64,
+        /// The configured budget, in USD.
+        budget_usd: f64,
+    },
+
+    /// The request does not fit in the model's context window, even after
+    /// truncation by the [`ContextManager`](crate::ContextManager).
+    #[error("Prompt needs {tokens} tokens but only {limit} fit in the context window")]
+    ContextOverflow {
+        /// Estimated tokens of the shortest prompt that could be sent.
+        tokens: u32,
+        /// Tokens available for the prompt, after the reply reserve.
+        limit: u32,
+    },
+
     /// Configuration error (e.g., missing required settings).
     #[error("Configuration error: {0}")]
     Config(String),
@@ -67,6 +113,46 @@ pub enum GrokError {
     InvalidHeaderValue(#[from] tonic::metadata::errors::InvalidMetadataValue),
 }
 
+/// Retry delay used for rate limit errors when the server gives no hint.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 1;
+
+impl From<tonic::Status> for GrokError {
+    /// Map a gRPC status into the most specific error variant.
+    ///
+    /// - `Unauthenticated` and `PermissionDenied` become [`GrokError::Auth`]
+    /// - `ResourceExhausted` becomes [`GrokError::RateLimit`]
+    /// - `InvalidArgument` becomes [`GrokError::InvalidRequest`]
+    /// - Any other code is kept as [`GrokError::Status`]
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::Unauthenticated | tonic::Code::PermissionDenied => {
+                Self::Auth(status.message().to_string())
+            }
+            tonic::Code::ResourceExhausted => Self::RateLimit {
+                retry_after_secs: retry_after_from_metadata(status.metadata())
+                    .unwrap_or(DEFAULT_RETRY_AFTER_SECS),
+            },
+            tonic::Code::InvalidArgument => Self::InvalidRequest(status.message().to_string()),
+            _ => Self::Status(status),
+        }
+    }
+}
+
+/// Extract the retry delay (in seconds) from response metadata or trailers.
+///
+/// Checks the HTTP-style `retry-after` header (seconds) first, then gRPC's
+/// `grpc-retry-pushback-ms` (milliseconds, rounded up to whole seconds).
+fn retry_after_from_metadata(metadata: &tonic::metadata::MetadataMap) -> Option<u64> {
+    let parse = |key: &str| -> Option<u64> {
+        metadata
+            .get(key)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+
+    parse("retry-after").or_else(|| parse("grpc-retry-pushback-ms").map(|ms| ms.div_ceil(1000)))
+}
+
 /// Result type alias using [`GrokError`].
 ///
 /// This is a convenience alias for `Result<T, GrokError>`.
@@ -95,6 +181,15 @@ impl GrokError {
             _ => None,
         }
     }
+
+    /// Get the gRPC status code associated with this error, if any
+    pub fn status_code(&self) -> Option<tonic::Code> {
+        match self {
+            Self::Status(status) => Some(status.code()),
+            Self::RateLimit { .. } => Some(tonic::Code::ResourceExhausted),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -172,6 +267,79 @@ mod tests {
         assert_eq!(error.retry_after(), None);
     }
 
+    #[test]
+    fn test_status_code() {
+        let error = GrokError::Status(tonic::Status::unavailable("down"));
+        assert_eq!(error.status_code(), Some(tonic::Code::Unavailable));
+
+        let error = GrokError::RateLimit {
+            retry_after_secs: 1,
+        };
+        assert_eq!(error.status_code(), Some(tonic::Code::ResourceExhausted));
+
+        let error = GrokError::Config("test".to_string());
+        assert_eq!(error.status_code(), None);
+    }
+
+    #[test]
+    fn test_from_status_unauthenticated() {
+        let error: GrokError = tonic::Status::unauthenticated("invalid api key").into();
+        assert!(matches!(error, GrokError::Auth(ref msg) if msg == "invalid api key"));
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_from_status_permission_denied() {
+        let error: GrokError = tonic::Status::permission_denied("forbidden").into();
+        assert!(matches!(error, GrokError::Auth(_)));
+    }
+
+    #[test]
+    fn test_from_status_invalid_argument() {
+        let error: GrokError = tonic::Status::invalid_argument("bad model").into();
+        assert!(matches!(error, GrokError::InvalidRequest(ref msg) if msg == "bad model"));
+    }
+
+    #[test]
+    fn test_from_status_resource_exhausted_default_delay() {
+        let error: GrokError = tonic::Status::resource_exhausted("quota exceeded").into();
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(DEFAULT_RETRY_AFTER_SECS));
+    }
+
+    #[test]
+    fn test_from_status_resource_exhausted_retry_after() {
+        let mut status = tonic::Status::resource_exhausted("quota exceeded");
+        status
+            .metadata_mut()
+            .insert("retry-after", "30".parse().unwrap());
+
+        let error: GrokError = status.into();
+        assert_eq!(error.retry_after(), Some(30));
+    }
+
+    #[test]
+    fn test_from_status_resource_exhausted_pushback_ms() {
+        let mut status = tonic::Status::resource_exhausted("quota exceeded");
+        status
+            .metadata_mut()
+            .insert("grpc-retry-pushback-ms", "1500".parse().unwrap());
+
+        let error: GrokError = status.into();
+        assert_eq!(error.retry_after(), Some(2));
+    }
+
+    #[test]
+    fn test_from_status_other_codes_kept() {
+        let error: GrokError = tonic::Status::unavailable("down").into();
+        assert!(matches!(error, GrokError::Status(_)));
+        assert!(error.is_retryable());
+
+        let error: GrokError = tonic::Status::internal("boom").into();
+        assert!(matches!(error, GrokError::Status(_)));
+        assert!(!error.is_retryable());
+    }
+
     #[test]
     fn test_error_display() {
         let error = GrokError::Auth("test auth error".to_string());
@@ -6,6 +6,7 @@ use crate::proto;
 
 /// Request for image generation
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageGenerationRequest {
     /// Text prompt describing the image
     pub prompt: String,
@@ -23,6 +24,8 @@ pub struct ImageGenerationRequest {
 
 /// Image output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ImageFormat {
     /// Base64-encoded string
     Base64,
@@ -70,6 +73,7 @@ impl ImageGenerationRequest {
 
 /// Response from image generation
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageGenerationResponse {
     /// Generated images
     pub images: Vec<GeneratedImage>,
@@ -79,6 +83,7 @@ pub struct ImageGenerationResponse {
 
 /// A generated image
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeneratedImage {
     /// Base64-encoded image data (if format is Base64)
     pub base64: Option<String>,
@@ -0,0 +1,372 @@
+//! In-memory vector index for local similarity search.
+//!
+//! [`EmbeddingIndex`] stores embeddings with an id and a payload of your choice
+//! (a passage, a struct, ...), and finds the closest ones to a query by brute
+//! force. It scores matches like the hosted Documents service
+//! ([`RankingMetric`]), which makes it a drop-in for prototypes and tests that
+//! do not need a collection.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{EmbedRequest, EmbeddingIndex, GrokClient, RankingMetric};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//! let passages = ["Rust is a systems language", "Paris is in France"];
+//!
+//! let request = passages
+//!     .iter()
+//!     .fold(EmbedRequest::new("embed-large-v1"), |request, passage| {
+//!         request.add_text(*passage)
+//!     });
+//! let response = client.embed(request).await?;
+//!
+//! let mut index = EmbeddingIndex::new(RankingMetric::CosineSimilarity);
+//! for embedding in &response.embeddings {
+//!     index.add(embedding.index.to_string(), embedding, passages[embedding.index])?;
+//! }
+//!
+//! let query = client
+//!     .embed(EmbedRequest::new("embed-large-v1").add_text("Which language?"))
+//!     .await?;
+//! for hit in index.top_k(&query.embeddings[0], 1)? {
+//!     println!("{:.3} {}", hit.score, hit.payload);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    documents::RankingMetric,
+    embedding::{dot, norm, Embedding},
+    error::{GrokError, Result},
+};
+use std::collections::HashMap;
+
+/// A brute-force index of embeddings, each with an id and a payload.
+///
+/// All embeddings must have the same dimensions, set by the first one added.
+/// Searching scans every entry, which is fast enough for tens of thousands of
+/// vectors; use the Documents service or a vector database beyond that.
+#[derive(Clone, Debug)]
+pub struct EmbeddingIndex<T> {
+    metric: RankingMetric,
+    dimensions: Option<usize>,
+    entries: Vec<Entry<T>>,
+    positions: HashMap<String, usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<T> {
+    id: String,
+    vector: Vec<f32>,
+    /// Length of `vector`, kept to score cosine similarity with one dot product.
+    norm: f32,
+    payload: T,
+}
+
+/// A search result of [`EmbeddingIndex::top_k`].
+#[derive(Debug, PartialEq)]
+pub struct IndexMatch<'a, T> {
+    /// Id of the matching entry.
+    pub id: &'a str,
+    /// Score under the index's [`RankingMetric`].
+    pub score: f32,
+    /// Payload of the matching entry.
+    pub payload: &'a T,
+}
+
+// Not derived: the payload is borrowed, so `T` need not be `Clone`.
+impl<T> Clone for IndexMatch<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for IndexMatch<'_, T> {}
+
+impl<T> Default for EmbeddingIndex<T> {
+    /// An empty index ranking by cosine similarity.
+    fn default() -> Self {
+        Self::new(RankingMetric::CosineSimilarity)
+    }
+}
+
+impl<T> EmbeddingIndex<T> {
+    /// Create an empty index ranking matches with `metric`.
+    pub fn new(metric: RankingMetric) -> Self {
+        Self {
+            metric,
+            dimensions: None,
+            entries: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// The ranking metric.
+    pub fn metric(&self) -> RankingMetric {
+        self.metric
+    }
+
+    /// Dimensions of the indexed vectors, once one was added.
+    pub fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the index has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if an entry has this id.
+    pub fn contains(&self, id: &str) -> bool {
+        self.positions.contains_key(id)
+    }
+
+    /// The payload of the entry with this id.
+    pub fn get(&self, id: &str) -> Option<&T> {
+        self.positions.get(id).map(|&i| &self.entries[i].payload)
+    }
+
+    /// Ids of the entries, in no particular order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.id.as_str())
+    }
+
+    /// Add `embedding` under `id`, returning the payload it replaces, if any.
+    ///
+    /// The vector is copied (decoded if it is only kept in compact form).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::InvalidRequest`] if the embedding is empty or its
+    /// dimensions differ from the indexed vectors.
+    pub fn add(
+        &mut self,
+        id: impl Into<String>,
+        embedding: &Embedding,
+        payload: T,
+    ) -> Result<Option<T>> {
+        self.add_vector(id, embedding.values().into_owned(), payload)
+    }
+
+    /// Add a raw vector under `id`, returning the payload it replaces, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::InvalidRequest`] if the vector is empty or its
+    /// dimensions differ from the indexed vectors.
+    pub fn add_vector(
+        &mut self,
+        id: impl Into<String>,
+        vector: Vec<f32>,
+        payload: T,
+    ) -> Result<Option<T>> {
+        self.check_dimensions(&vector)?;
+        self.dimensions = Some(vector.len());
+
+        let entry = Entry {
+            id: id.into(),
+            norm: norm(&vector),
+            vector,
+            payload,
+        };
+        match self.positions.get(&entry.id) {
+            Some(&i) => Ok(Some(std::mem::replace(&mut self.entries[i], entry).payload)),
+            None => {
+                self.positions.insert(entry.id.clone(), self.entries.len());
+                self.entries.push(entry);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Remove the entry with this id, returning its payload.
+    pub fn remove(&mut self, id: &str) -> Option<T> {
+        let position = self.positions.remove(id)?;
+        let entry = self.entries.swap_remove(position);
+        if let Some(moved) = self.entries.get(position) {
+            self.positions.insert(moved.id.clone(), position);
+        }
+        if self.entries.is_empty() {
+            self.dimensions = None;
+        }
+        Some(entry.payload)
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.positions.clear();
+        self.dimensions = None;
+    }
+
+    /// The `k` entries closest to `query`, best match first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::InvalidRequest`] if the query is empty or its
+    /// dimensions differ from the indexed vectors.
+    pub fn top_k(&self, query: &Embedding, k: usize) -> Result<Vec<IndexMatch<'_, T>>> {
+        self.top_k_vector(&query.values(), k)
+    }
+
+    /// The `k` entries closest to a raw `query` vector, best match first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::InvalidRequest`] if the query is empty or its
+    /// dimensions differ from the indexed vectors.
+    pub fn top_k_vector(&self, query: &[f32], k: usize) -> Result<Vec<IndexMatch<'_, T>>> {
+        self.check_dimensions(query)?;
+
+        let query_norm = norm(query);
+        let mut matches: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| IndexMatch {
+                id: entry.id.as_str(),
+                score: self.score(query, query_norm, entry),
+                payload: &entry.payload,
+            })
+            .collect();
+
+        let by_rank =
+            |a: &IndexMatch<'_, T>, b: &IndexMatch<'_, T>| self.metric.compare(a.score, b.score);
+        if k < matches.len() {
+            matches.select_nth_unstable_by(k, by_rank);
+            matches.truncate(k);
+        }
+        matches.sort_by(by_rank);
+        Ok(matches)
+    }
+
+    fn score(&self, query: &[f32], query_norm: f32, entry: &Entry<T>) -> f32 {
+        match self.metric {
+            RankingMetric::CosineSimilarity => {
+                let norms = query_norm * entry.norm;
+                if norms == 0.0 {
+                    0.0
+                } else {
+                    dot(query, &entry.vector) / norms
+                }
+            }
+            RankingMetric::L2Distance => self.metric.score(query, &entry.vector),
+        }
+    }
+
+    fn check_dimensions(&self, vector: &[f32]) -> Result<()> {
+        if vector.is_empty() {
+            return Err(GrokError::InvalidRequest("empty embedding vector".into()));
+        }
+        match self.dimensions {
+            Some(dimensions) if dimensions != vector.len() => {
+                Err(GrokError::InvalidRequest(format!(
+                    "embedding has {} dimensions but the index has {dimensions}",
+                    vector.len()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding(vector: &[f32]) -> Embedding {
+        Embedding {
+            index: 0,
+            vector: vector.to_vec(),
+            compact: None,
+        }
+    }
+
+    fn ids<T>(matches: &[IndexMatch<'_, T>]) -> Vec<String> {
+        matches.iter().map(|m| m.id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_top_k_by_cosine_similarity() {
+        let mut index = EmbeddingIndex::new(RankingMetric::CosineSimilarity);
+        index.add("x", &embedding(&[1.0, 0.0]), "east").unwrap();
+        index.add("y", &embedding(&[0.0, 2.0]), "north").unwrap();
+        index
+            .add("xy", &embedding(&[1.0, 1.0]), "north-east")
+            .unwrap();
+        index.add("-x", &embedding(&[-3.0, 0.0]), "west").unwrap();
+
+        let matches = index.top_k(&embedding(&[2.0, 0.5]), 2).unwrap();
+        assert_eq!(ids(&matches), ["x", "xy"]);
+        assert_eq!(*matches[0].payload, "east");
+        assert!((matches[0].score - 0.970_142_5).abs() < 1e-6);
+
+        let all = index.top_k(&embedding(&[2.0, 0.5]), 10).unwrap();
+        assert_eq!(ids(&all), ["x", "xy", "y", "-x"]);
+        assert!((all[3].score + 0.970_142_5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_top_k_by_l2_distance() {
+        let mut index = EmbeddingIndex::new(RankingMetric::L2Distance);
+        index.add_vector("near", vec![1.0, 1.0], ()).unwrap();
+        index.add_vector("far", vec![10.0, 10.0], ()).unwrap();
+        index.add_vector("origin", vec![0.0, 0.0], ()).unwrap();
+
+        let matches = index.top_k_vector(&[0.9, 1.2], 3).unwrap();
+        assert_eq!(ids(&matches), ["near", "origin", "far"]);
+        assert!(matches[0].score < matches[1].score);
+    }
+
+    #[test]
+    fn test_add_replace_and_remove() {
+        let mut index = EmbeddingIndex::default();
+        assert_eq!(index.add_vector("a", vec![1.0, 0.0], 1).unwrap(), None);
+        assert_eq!(index.add_vector("b", vec![0.0, 1.0], 2).unwrap(), None);
+        assert_eq!(index.add_vector("c", vec![1.0, 1.0], 3).unwrap(), None);
+        assert_eq!(index.add_vector("a", vec![-1.0, 0.0], 10).unwrap(), Some(1));
+        assert_eq!(index.len(), 3);
+
+        // Removing moves the last entry into the freed slot
+        assert_eq!(index.remove("a"), Some(10));
+        assert_eq!(index.remove("a"), None);
+        assert_eq!(index.get("c"), Some(&3));
+        assert_eq!(index.top_k_vector(&[1.0, 1.0], 1).unwrap()[0].id, "c");
+
+        index.clear();
+        assert!(index.is_empty());
+        assert_eq!(index.dimensions(), None);
+    }
+
+    #[test]
+    fn test_dimension_mismatch_is_rejected() {
+        let mut index = EmbeddingIndex::default();
+        index.add_vector("a", vec![1.0, 0.0], ()).unwrap();
+
+        assert!(index.add_vector("b", vec![1.0, 0.0, 0.0], ()).is_err());
+        assert!(index.add_vector("c", Vec::new(), ()).is_err());
+        assert!(index.top_k_vector(&[1.0], 1).is_err());
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_index_matches_embedding_similarity() {
+        let query = embedding(&[0.3, -0.2, 0.9]);
+        let candidate = embedding(&[0.1, 0.4, 0.7]);
+
+        for metric in [RankingMetric::CosineSimilarity, RankingMetric::L2Distance] {
+            let mut index = EmbeddingIndex::new(metric);
+            index.add("candidate", &candidate, ()).unwrap();
+
+            let score = index.top_k(&query, 1).unwrap()[0].score;
+            assert!((score - query.similarity(&candidate, metric)).abs() < 1e-6);
+        }
+    }
+}
@@ -12,6 +12,37 @@
 //! - **Advanced features** - Log probabilities, reasoning traces, deferred completions
 //! - **Secure by default** - Uses `secrecy` crate for API keys
 //!
+//! ## Serialization
+//!
+//! With the `serde` feature, request and response types (chat, tools, models,
+//! embeddings, tokenization, ...) implement `Serialize` and `Deserialize`, so
+//! conversations and responses can be stored and reloaded as JSON:
+//!
+//! - field names are the Rust field names;
+//! - enum variants are `snake_case` and externally tagged, e.g. `"stop"`,
+//!   `{"error": "..."}` or `{"user": "Hello"}`; [`MessageContent`] is untagged
+//!   (a string for text, a list of parts for multimodal content);
+//! - timestamps are `{"seconds": ..., "nanos": ...}` and include options use
+//!   their proto names.
+//!
+//! ## Tracing
+//!
+//! With the `tracing` feature, every [`GrokClient`] operation runs inside a
+//! `grok.rpc` span carrying the operation name, model, request id, token usage,
+//! finish reason, latency, retry count and, on failure, the gRPC status code.
+//! Streaming operations also record the time to the first chunk and emit a
+//! `TRACE` event per chunk. Prompt and completion text are only recorded when
+//! [`GrokConfig::trace_content`] is set.
+//!
+//! ## OpenTelemetry
+//!
+//! With the `otel` feature, chat, embedding, text sampling and image generation
+//! calls record the OpenTelemetry GenAI client metrics through the global meter
+//! provider: `gen_ai.client.operation.duration`, `gen_ai.client.token.usage`
+//! and, for streams, `gen_ai.client.operation.time_to_first_chunk`. Every
+//! request also carries the current OpenTelemetry context (W3C `traceparent`)
+//! in its metadata, using the global propagator.
+//!
 //! ## Quick Start
 //!
 //! ```no_run
@@ -20,7 +51,7 @@
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     // Initialize client from GROK_API_KEY environment variable
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     // Create a simple chat request
 //!     let request = ChatRequest::new()
@@ -44,7 +75,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     let request = ChatRequest::new()
 //!         .user_message("Write a short poem");
@@ -68,7 +99,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     let get_weather = FunctionTool::new(
 //!         "get_weather",
@@ -111,6 +142,12 @@ mod auth;
 /// Client implementation for connecting to the xAI Grok API.
 pub mod client;
 
+/// Credential providers for API key rotation.
+pub mod credentials;
+
+/// Tool registry and automatic tool-execution loop.
+pub mod agent;
+
 /// Error types for the client.
 mod error;
 
@@ -123,12 +160,21 @@ pub mod response;
 /// Tool calling support (function calling, web search, etc.).
 pub mod tools;
 
+/// Accumulation of streamed chat chunks into complete responses.
+pub mod stream;
+
+/// Typed structured output derived from Rust types.
+pub mod structured;
+
 /// Model listing and information API.
 pub mod models;
 
 /// Embedding API for vector representations.
 pub mod embedding;
 
+/// In-memory vector index for local similarity search.
+pub mod index;
+
 /// Tokenization API for counting tokens.
 pub mod tokenize;
 
@@ -144,30 +190,63 @@ pub mod image;
 /// Documents search API for RAG.
 pub mod documents;
 
+/// Multi-turn conversations with automatic history management.
+pub mod conversation;
+
+/// Context-window fitting and history truncation.
+pub mod context;
+
+/// Token usage and cost accounting.
+pub mod usage;
+
+/// Serde adapters for protobuf types used in the public API.
+#[cfg(feature = "serde")]
+mod serde_helpers;
+
+/// Scriptable in-process mock server for tests (requires the `mock-server` feature).
+#[cfg(feature = "mock-server")]
+pub mod mock;
+
 // Re-exports for convenient access
+pub use agent::{ToolRegistry, ToolRun};
 pub use api_key::ApiKeyInfo;
-pub use client::{GrokClient, GrokConfig};
+pub use client::{
+    GrokClient, GrokConfig, GrpcService, MiddlewareStack, RateLimit, RateLimitMode, RateLimiter,
+    RetryPolicy, TokenEstimate,
+};
+pub use context::{ContextManager, Summarizer, TruncationStrategy};
+pub use conversation::Conversation;
+pub use credentials::{CredentialProvider, EnvKey, FileKey, KeyPool, StaticKey};
 pub use documents::{DocumentSearchRequest, DocumentSearchResponse, RankingMetric, SearchMatch};
 pub use embedding::{
-    EmbedEncodingFormat, EmbedInput, EmbedRequest, EmbedResponse, Embedding, EmbeddingUsage,
+    CompactVector, EmbedBatching, EmbedEncodingFormat, EmbedInput, EmbedRequest, EmbedResponse,
+    Embedding, EmbeddingUsage, VectorFormat,
 };
 pub use error::{GrokError, Result};
 pub use image::{GeneratedImage, ImageFormat, ImageGenerationRequest, ImageGenerationResponse};
+pub use index::{EmbeddingIndex, IndexMatch};
+#[cfg(feature = "mock-server")]
+pub use mock::{MockGrokServer, MockMethod, MockServerHandle};
 pub use models::{EmbeddingModel, ImageGenerationModel, LanguageModel, Modality};
 pub use proto::IncludeOption;
 pub use request::{
-    ChatRequest, CompletionOptions, ContentPart, ImageDetail, Message, MessageContent,
-    ReasoningEffort, ResponseFormat, SearchConfig, SearchMode, SearchSource,
+    ChatRequest, CompletionOptions, ContentPart, ImageDetail, Message, MessageContent, NewsSource,
+    ReasoningEffort, ResponseFormat, RssSource, SearchConfig, SearchMode, SearchSource, WebSource,
+    XSource,
 };
 pub use response::{
-    ChatChunk, ChatResponse, FinishReason, LogProb, LogProbs, TokenUsage, TopLogProb,
+    ChatChunk, ChatResponse, Choice, ChoiceDelta, FinishReason, LogProb, LogProbs, TokenUsage,
+    TopLogProb,
 };
 pub use sample::{SampleChoice, SampleRequest, SampleResponse};
-pub use tokenize::{Token, TokenizeRequest, TokenizeResponse};
+pub use stream::{ChatStreamAccumulator, ChatStreamExt};
+pub use structured::{StructuredOutput, StructuredResponse};
+pub use tokenize::{Token, TokenCounter, TokenizeRequest, TokenizeResponse};
 pub use tools::{
     CollectionsSearchTool, DocumentSearchTool, FunctionCall, FunctionTool, McpTool, Tool, ToolCall,
     ToolCallKind, ToolCallStatusKind, ToolChoice, WebSearchTool, XSearchTool,
 };
+pub use usage::{UsageEntry, UsageReport, UsageTotals, UsageTracker};
 
 // Re-export tonic types for users who need custom channel configuration
 // This allows users to configure TLS, timeouts, and other transport options
@@ -177,7 +256,7 @@ pub use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
 /// Prelude module for convenient imports
 pub mod prelude {
     pub use crate::{
-        ChatRequest, ChatResponse, GrokClient, GrokConfig, Message, MessageContent,
-        Result as GrokResult, Tool, ToolChoice,
+        ChatRequest, ChatResponse, Conversation, GrokClient, GrokConfig, Message,
+        MessageContent, Result as GrokResult, Tool, ToolChoice,
     };
 }
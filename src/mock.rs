@@ -0,0 +1,1362 @@
+//! Scriptable in-process mock of the xAI gRPC API.
+//!
+//! [`MockGrokServer`] implements every service the client talks to (chat, models,
+//! embeddings, tokenization, auth, sampling, images and documents) and replies
+//! with canned responses that tests queue up in advance. Start it with
+//! [`MockGrokServer::start`] and point a [`GrokClient`] at it through
+//! [`GrokClient::with_channel`], or simply use [`MockServerHandle::client`].
+//!
+//! This module is only available with the `mock-server` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::mock::MockGrokServer;
+//! use xai_grpc_client::{ChatRequest, ChatResponse, FinishReason, TokenUsage};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockGrokServer::new();
+//! server.push_chat_response(ChatResponse {
+//!     request_id: "req-1".to_string(),
+//!     content: "Hello from the mock!".to_string(),
+//!     finish_reason: FinishReason::Stop,
+//!     model: "grok-2-1212".to_string(),
+//!     usage: TokenUsage::default(),
+//!     citations: vec![],
+//!     tool_calls: vec![],
+//!     reasoning_content: None,
+//!     logprobs: None,
+//!     created: None,
+//!     system_fingerprint: None,
+//!     choices: vec![],
+//! });
+//!
+//! let handle = server.start().await?;
+//! let client = handle.client().await?;
+//!
+//! let response = client
+//!     .complete_chat(ChatRequest::new().user_message("Hi"))
+//!     .await?;
+//! assert_eq!(response.content, "Hello from the mock!");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_key::ApiKeyInfo,
+    documents::DocumentSearchResponse,
+    embedding::EmbedResponse,
+    error::{GrokError, Result},
+    image::ImageGenerationResponse,
+    models::{EmbeddingModel, ImageGenerationModel, LanguageModel, Modality},
+    proto::{
+        self, auth_server::AuthServer, chat_server::ChatServer, documents_server::DocumentsServer,
+        embedder_server::EmbedderServer, image_server::ImageServer, models_server::ModelsServer,
+        sample_server::SampleServer, tokenize_server::TokenizeServer,
+    },
+    response::{ChatChunk, ChatResponse, Choice, ChoiceDelta, FinishReason, LogProbs, TokenUsage},
+    sample::SampleResponse,
+    tokenize::TokenizeResponse,
+    GrokClient,
+};
+use base64::Engine;
+use secrecy::SecretString;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    metadata::MetadataMap,
+    transport::{server::TcpIncoming, Channel, Server},
+    Request, Response,
+};
+
+// Re-exported so tests can inject errors without depending on tonic directly
+pub use tonic::{Code, Status};
+
+/// API key used by clients created through [`MockServerHandle::client`].
+pub const MOCK_API_KEY: &str = "xai-mock-api-key";
+
+/// Boxed stream type used for the server-streaming RPCs.
+type MockStream<T> = Pin<Box<dyn Stream<Item = std::result::Result<T, Status>> + Send>>;
+
+/// RPCs served by [`MockGrokServer`].
+///
+/// Used to inject errors with [`MockGrokServer::push_error`] and to inspect
+/// calls with [`MockGrokServer::call_count`] and [`MockGrokServer::last_metadata`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MockMethod {
+    /// `Chat/GetCompletion` ([`GrokClient::complete_chat`]).
+    GetCompletion,
+    /// `Chat/GetCompletionChunk` ([`GrokClient::stream_chat`]).
+    GetCompletionChunk,
+    /// `Chat/StartDeferredCompletion` ([`GrokClient::start_deferred`]).
+    StartDeferredCompletion,
+    /// `Chat/GetDeferredCompletion` ([`GrokClient::poll_deferred`]).
+    GetDeferredCompletion,
+    /// `Chat/GetStoredCompletion` ([`GrokClient::get_stored_completion`]).
+    GetStoredCompletion,
+    /// `Chat/DeleteStoredCompletion` ([`GrokClient::delete_stored_completion`]).
+    DeleteStoredCompletion,
+    /// `Models/ListLanguageModels` ([`GrokClient::list_models`]).
+    ListLanguageModels,
+    /// `Models/ListEmbeddingModels` ([`GrokClient::list_embedding_models`]).
+    ListEmbeddingModels,
+    /// `Models/ListImageGenerationModels` ([`GrokClient::list_image_generation_models`]).
+    ListImageGenerationModels,
+    /// `Models/GetLanguageModel` ([`GrokClient::get_model`]).
+    GetLanguageModel,
+    /// `Models/GetEmbeddingModel` ([`GrokClient::get_embedding_model`]).
+    GetEmbeddingModel,
+    /// `Models/GetImageGenerationModel` ([`GrokClient::get_image_generation_model`]).
+    GetImageGenerationModel,
+    /// `Embedder/Embed` ([`GrokClient::embed`]).
+    Embed,
+    /// `Tokenize/TokenizeText` ([`GrokClient::tokenize`]).
+    TokenizeText,
+    /// `Auth/GetApiKeyInfo` ([`GrokClient::get_api_key_info`]).
+    GetApiKeyInfo,
+    /// `Sample/SampleText` ([`GrokClient::sample_text`]).
+    SampleText,
+    /// `Sample/SampleTextStreaming` ([`GrokClient::sample_text_streaming`]).
+    SampleTextStreaming,
+    /// `Image/GenerateImage` ([`GrokClient::generate_image`]).
+    GenerateImage,
+    /// `Documents/Search` ([`GrokClient::search_documents`]).
+    Search,
+}
+
+/// Scripted state shared by all clones of a [`MockGrokServer`].
+#[derive(Default)]
+struct MockState {
+    latency: Duration,
+    errors: HashMap<MockMethod, VecDeque<Status>>,
+    calls: HashMap<MockMethod, usize>,
+    metadata: HashMap<MockMethod, MetadataMap>,
+    chat_responses: VecDeque<proto::GetChatCompletionResponse>,
+    chat_streams: VecDeque<Vec<std::result::Result<proto::GetChatCompletionChunk, Status>>>,
+    deferred_pending_polls: u32,
+    deferred_started: u64,
+    language_models: Vec<proto::LanguageModel>,
+    embedding_models: Vec<proto::EmbeddingModel>,
+    image_generation_models: Vec<proto::ImageGenerationModel>,
+    embed_responses: VecDeque<proto::EmbedResponse>,
+    tokenize_responses: VecDeque<proto::TokenizeTextResponse>,
+    api_key: Option<proto::ApiKey>,
+    sample_responses: VecDeque<proto::SampleTextResponse>,
+    sample_streams: VecDeque<Vec<proto::SampleTextResponse>>,
+    image_responses: VecDeque<proto::ImageResponse>,
+    search_responses: VecDeque<proto::SearchResponse>,
+}
+
+/// Pop the next canned reply, or fail with `FAILED_PRECONDITION` if none is queued.
+fn next<T>(queue: &mut VecDeque<T>, method: MockMethod) -> std::result::Result<T, Status> {
+    queue.pop_front().ok_or_else(|| {
+        Status::failed_precondition(format!("MockGrokServer: no response queued for {method:?}"))
+    })
+}
+
+/// Find a model by name or alias, or fail with `NOT_FOUND`.
+fn find_model<T: Clone>(
+    models: &[T],
+    name: &str,
+    names: impl Fn(&T) -> (&str, &[String]),
+) -> std::result::Result<T, Status> {
+    models
+        .iter()
+        .find(|model| {
+            let (model_name, aliases) = names(model);
+            model_name == name || aliases.iter().any(|alias| alias == name)
+        })
+        .cloned()
+        .ok_or_else(|| Status::not_found(format!("Model {name} not found")))
+}
+
+/// A scriptable, in-process mock of the xAI gRPC API.
+///
+/// Responses are queued per RPC and served in FIFO order. Calling an RPC with
+/// an empty queue fails with `FAILED_PRECONDITION`, so a test that makes more
+/// calls than it scripted fails loudly. Model listings and API key info are
+/// configured once with the `set_*` methods and served on every call.
+///
+/// The server is cheap to clone; all clones share the same script, so replies
+/// can be queued and calls inspected while the server is running.
+///
+/// Note that [`GrokClient`] retries retryable errors (see
+/// [`RetryPolicy`](crate::RetryPolicy)), so an injected `UNAVAILABLE` error is
+/// followed by another call to the same RPC.
+#[derive(Clone, Default)]
+pub struct MockGrokServer {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl std::fmt::Debug for MockGrokServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockGrokServer").finish_non_exhaustive()
+    }
+}
+
+impl MockGrokServer {
+    /// Create a mock server with nothing scripted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        // A panicking test thread must not hide the script from other tests
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Delay every reply (and every streamed chunk) by `latency`.
+    pub fn set_latency(&self, latency: Duration) -> &Self {
+        self.lock().latency = latency;
+        self
+    }
+
+    /// Make the next call to `method` fail with `status`.
+    ///
+    /// Errors take precedence over queued responses and are consumed in FIFO
+    /// order, one per call.
+    pub fn push_error(&self, method: MockMethod, status: Status) -> &Self {
+        self.lock()
+            .errors
+            .entry(method)
+            .or_default()
+            .push_back(status);
+        self
+    }
+
+    /// Number of calls received for `method`, including failed ones.
+    pub fn call_count(&self, method: MockMethod) -> usize {
+        self.lock().calls.get(&method).copied().unwrap_or(0)
+    }
+
+    /// Request metadata (headers) of the most recent call to `method`.
+    pub fn last_metadata(&self, method: MockMethod) -> Option<MetadataMap> {
+        self.lock().metadata.get(&method).cloned()
+    }
+
+    /// Queue a reply for [`GrokClient::complete_chat`].
+    ///
+    /// Chat replies are shared with [`GrokClient::get_stored_completion`] and
+    /// completed deferred requests, which pop from the same queue.
+    pub fn push_chat_response(&self, response: ChatResponse) -> &Self {
+        self.lock()
+            .chat_responses
+            .push_back(chat_response_to_proto(&response));
+        self
+    }
+
+    /// Queue a stream of chunks for [`GrokClient::stream_chat`].
+    pub fn push_chat_stream(&self, chunks: Vec<ChatChunk>) -> &Self {
+        let chunks = chunks.iter().map(|chunk| Ok(chat_chunk_to_proto(chunk)));
+        self.lock().chat_streams.push_back(chunks.collect());
+        self
+    }
+
+    /// Queue a stream for [`GrokClient::stream_chat`] that yields `chunks` and
+    /// then fails with `status`.
+    pub fn push_chat_stream_then_error(&self, chunks: Vec<ChatChunk>, status: Status) -> &Self {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| Ok(chat_chunk_to_proto(chunk)))
+            .chain(std::iter::once(Err(status)));
+        self.lock().chat_streams.push_back(chunks.collect());
+        self
+    }
+
+    /// Number of times [`GrokClient::poll_deferred`] reports a deferred
+    /// completion as pending before returning the next queued chat response.
+    pub fn set_deferred_pending_polls(&self, polls: u32) -> &Self {
+        self.lock().deferred_pending_polls = polls;
+        self
+    }
+
+    /// Set the language models served by the models service.
+    pub fn set_language_models(&self, models: Vec<LanguageModel>) -> &Self {
+        self.lock().language_models = models.iter().map(language_model_to_proto).collect();
+        self
+    }
+
+    /// Set the embedding models served by the models service.
+    pub fn set_embedding_models(&self, models: Vec<EmbeddingModel>) -> &Self {
+        self.lock().embedding_models = models.iter().map(embedding_model_to_proto).collect();
+        self
+    }
+
+    /// Set the image generation models served by the models service.
+    pub fn set_image_generation_models(&self, models: Vec<ImageGenerationModel>) -> &Self {
+        self.lock().image_generation_models =
+            models.iter().map(image_generation_model_to_proto).collect();
+        self
+    }
+
+    /// Queue a reply for [`GrokClient::embed`].
+    ///
+    /// The vectors are sent as base64 when the request asks for
+    /// [`EmbedEncodingFormat::Base64`](crate::EmbedEncodingFormat::Base64).
+    pub fn push_embed_response(&self, response: EmbedResponse) -> &Self {
+        self.lock()
+            .embed_responses
+            .push_back(embed_response_to_proto(&response));
+        self
+    }
+
+    /// Queue a reply for [`GrokClient::tokenize`].
+    pub fn push_tokenize_response(&self, response: TokenizeResponse) -> &Self {
+        self.lock()
+            .tokenize_responses
+            .push_back(tokenize_response_to_proto(&response));
+        self
+    }
+
+    /// Set the API key info served by [`GrokClient::get_api_key_info`].
+    pub fn set_api_key_info(&self, info: ApiKeyInfo) -> &Self {
+        self.lock().api_key = Some(api_key_to_proto(&info));
+        self
+    }
+
+    /// Queue a reply for [`GrokClient::sample_text`].
+    pub fn push_sample_response(&self, response: SampleResponse) -> &Self {
+        self.lock()
+            .sample_responses
+            .push_back(sample_response_to_proto(&response));
+        self
+    }
+
+    /// Queue a stream of chunks for [`GrokClient::sample_text_streaming`].
+    pub fn push_sample_stream(&self, chunks: Vec<SampleResponse>) -> &Self {
+        let chunks = chunks.iter().map(sample_response_to_proto).collect();
+        self.lock().sample_streams.push_back(chunks);
+        self
+    }
+
+    /// Queue a reply for [`GrokClient::generate_image`].
+    pub fn push_image_response(&self, response: ImageGenerationResponse) -> &Self {
+        self.lock()
+            .image_responses
+            .push_back(image_response_to_proto(&response));
+        self
+    }
+
+    /// Queue a reply for [`GrokClient::search_documents`].
+    pub fn push_search_response(&self, response: DocumentSearchResponse) -> &Self {
+        self.lock()
+            .search_responses
+            .push_back(search_response_to_proto(&response));
+        self
+    }
+
+    /// Start serving on an ephemeral localhost port.
+    ///
+    /// The server runs on a background task until the returned handle is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::Config`] if the listener cannot be bound.
+    pub async fn start(&self) -> Result<MockServerHandle> {
+        let incoming = TcpIncoming::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .map_err(|e| GrokError::Config(format!("Failed to bind mock server: {e}")))?;
+        let addr = incoming
+            .local_addr()
+            .map_err(|e| GrokError::Config(format!("Failed to bind mock server: {e}")))?;
+
+        let router = Server::builder()
+            .add_service(ChatServer::new(self.clone()))
+            .add_service(ModelsServer::new(self.clone()))
+            .add_service(EmbedderServer::new(self.clone()))
+            .add_service(TokenizeServer::new(self.clone()))
+            .add_service(AuthServer::new(self.clone()))
+            .add_service(SampleServer::new(self.clone()))
+            .add_service(ImageServer::new(self.clone()))
+            .add_service(DocumentsServer::new(self.clone()));
+
+        let task = tokio::spawn(async move {
+            // Serving only ends when the task is aborted or the listener fails
+            let _ = router.serve_with_incoming(incoming).await;
+        });
+
+        Ok(MockServerHandle { addr, task })
+    }
+
+    /// Record the call, then produce its reply: an injected error if one is
+    /// queued, otherwise whatever `reply` returns. The configured latency is
+    /// applied before answering.
+    async fn respond<T, F>(
+        &self,
+        method: MockMethod,
+        metadata: &MetadataMap,
+        reply: F,
+    ) -> std::result::Result<T, Status>
+    where
+        F: FnOnce(&mut MockState) -> std::result::Result<T, Status>,
+    {
+        let (latency, result) = {
+            let mut state = self.lock();
+            *state.calls.entry(method).or_default() += 1;
+            state.metadata.insert(method, metadata.clone());
+
+            let error = state.errors.get_mut(&method).and_then(VecDeque::pop_front);
+            let result = match error {
+                Some(status) => Err(status),
+                None => reply(&mut state),
+            };
+            (state.latency, result)
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        result
+    }
+
+    /// Turn scripted items into a response stream, spacing items by the latency.
+    fn stream<T: Send + 'static>(
+        &self,
+        items: Vec<std::result::Result<T, Status>>,
+    ) -> MockStream<T> {
+        let latency = self.lock().latency;
+        let stream = tokio_stream::iter(items);
+        if latency.is_zero() {
+            Box::pin(stream)
+        } else {
+            Box::pin(stream.throttle(latency))
+        }
+    }
+}
+
+/// Handle to a running [`MockGrokServer`].
+///
+/// Dropping the handle shuts the server down.
+#[derive(Debug)]
+pub struct MockServerHandle {
+    addr: SocketAddr,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServerHandle {
+    /// Address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Open a plaintext channel to the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::Transport`] if the connection fails.
+    pub async fn channel(&self) -> Result<Channel> {
+        let endpoint = Channel::from_shared(format!("http://{}", self.addr))
+            .map_err(|e| GrokError::Config(format!("Invalid mock server address: {e}")))?;
+        Ok(endpoint.connect().await?)
+    }
+
+    /// Create a [`GrokClient`] connected to the server.
+    ///
+    /// The client authenticates with [`MOCK_API_KEY`].
+    pub async fn client(&self) -> Result<GrokClient> {
+        let channel = self.channel().await?;
+        Ok(GrokClient::with_channel(
+            channel,
+            SecretString::from(MOCK_API_KEY.to_string()),
+        ))
+    }
+}
+
+impl Drop for MockServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[tonic::async_trait]
+impl proto::chat_server::Chat for MockGrokServer {
+    type GetCompletionChunkStream = MockStream<proto::GetChatCompletionChunk>;
+
+    async fn get_completion(
+        &self,
+        request: Request<proto::GetCompletionsRequest>,
+    ) -> std::result::Result<Response<proto::GetChatCompletionResponse>, Status> {
+        self.respond(MockMethod::GetCompletion, request.metadata(), |state| {
+            next(&mut state.chat_responses, MockMethod::GetCompletion)
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_completion_chunk(
+        &self,
+        request: Request<proto::GetCompletionsRequest>,
+    ) -> std::result::Result<Response<Self::GetCompletionChunkStream>, Status> {
+        let chunks = self
+            .respond(
+                MockMethod::GetCompletionChunk,
+                request.metadata(),
+                |state| next(&mut state.chat_streams, MockMethod::GetCompletionChunk),
+            )
+            .await?;
+        Ok(Response::new(self.stream(chunks)))
+    }
+
+    async fn start_deferred_completion(
+        &self,
+        request: Request<proto::GetCompletionsRequest>,
+    ) -> std::result::Result<Response<proto::StartDeferredResponse>, Status> {
+        self.respond(
+            MockMethod::StartDeferredCompletion,
+            request.metadata(),
+            |state| {
+                state.deferred_started += 1;
+                Ok(proto::StartDeferredResponse {
+                    request_id: format!("mock-deferred-{}", state.deferred_started),
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_deferred_completion(
+        &self,
+        request: Request<proto::GetDeferredRequest>,
+    ) -> std::result::Result<Response<proto::GetDeferredCompletionResponse>, Status> {
+        self.respond(
+            MockMethod::GetDeferredCompletion,
+            request.metadata(),
+            |state| {
+                if state.deferred_pending_polls > 0 {
+                    state.deferred_pending_polls -= 1;
+                    return Ok(proto::GetDeferredCompletionResponse {
+                        status: proto::DeferredStatus::Pending as i32,
+                        response: None,
+                    });
+                }
+
+                let response = next(&mut state.chat_responses, MockMethod::GetDeferredCompletion)?;
+                Ok(proto::GetDeferredCompletionResponse {
+                    status: proto::DeferredStatus::Done as i32,
+                    response: Some(response),
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_stored_completion(
+        &self,
+        request: Request<proto::GetStoredCompletionRequest>,
+    ) -> std::result::Result<Response<proto::GetChatCompletionResponse>, Status> {
+        self.respond(
+            MockMethod::GetStoredCompletion,
+            request.metadata(),
+            |state| next(&mut state.chat_responses, MockMethod::GetStoredCompletion),
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn delete_stored_completion(
+        &self,
+        request: Request<proto::DeleteStoredCompletionRequest>,
+    ) -> std::result::Result<Response<proto::DeleteStoredCompletionResponse>, Status> {
+        let response_id = request.get_ref().response_id.clone();
+        self.respond(
+            MockMethod::DeleteStoredCompletion,
+            request.metadata(),
+            |_| Ok(proto::DeleteStoredCompletionResponse { response_id }),
+        )
+        .await
+        .map(Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::models_server::Models for MockGrokServer {
+    async fn list_language_models(
+        &self,
+        request: Request<()>,
+    ) -> std::result::Result<Response<proto::ListLanguageModelsResponse>, Status> {
+        self.respond(
+            MockMethod::ListLanguageModels,
+            request.metadata(),
+            |state| {
+                Ok(proto::ListLanguageModelsResponse {
+                    models: state.language_models.clone(),
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn list_embedding_models(
+        &self,
+        request: Request<()>,
+    ) -> std::result::Result<Response<proto::ListEmbeddingModelsResponse>, Status> {
+        self.respond(
+            MockMethod::ListEmbeddingModels,
+            request.metadata(),
+            |state| {
+                Ok(proto::ListEmbeddingModelsResponse {
+                    models: state.embedding_models.clone(),
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn list_image_generation_models(
+        &self,
+        request: Request<()>,
+    ) -> std::result::Result<Response<proto::ListImageGenerationModelsResponse>, Status> {
+        self.respond(
+            MockMethod::ListImageGenerationModels,
+            request.metadata(),
+            |state| {
+                Ok(proto::ListImageGenerationModelsResponse {
+                    models: state.image_generation_models.clone(),
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_language_model(
+        &self,
+        request: Request<proto::GetModelRequest>,
+    ) -> std::result::Result<Response<proto::LanguageModel>, Status> {
+        let name = request.get_ref().name.clone();
+        self.respond(MockMethod::GetLanguageModel, request.metadata(), |state| {
+            find_model(&state.language_models, &name, |m| {
+                (m.name.as_str(), m.aliases.as_slice())
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_embedding_model(
+        &self,
+        request: Request<proto::GetModelRequest>,
+    ) -> std::result::Result<Response<proto::EmbeddingModel>, Status> {
+        let name = request.get_ref().name.clone();
+        self.respond(MockMethod::GetEmbeddingModel, request.metadata(), |state| {
+            find_model(&state.embedding_models, &name, |m| {
+                (m.name.as_str(), m.aliases.as_slice())
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn get_image_generation_model(
+        &self,
+        request: Request<proto::GetModelRequest>,
+    ) -> std::result::Result<Response<proto::ImageGenerationModel>, Status> {
+        let name = request.get_ref().name.clone();
+        self.respond(
+            MockMethod::GetImageGenerationModel,
+            request.metadata(),
+            |state| {
+                find_model(&state.image_generation_models, &name, |m| {
+                    (m.name.as_str(), m.aliases.as_slice())
+                })
+            },
+        )
+        .await
+        .map(Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::embedder_server::Embedder for MockGrokServer {
+    async fn embed(
+        &self,
+        request: Request<proto::EmbedRequest>,
+    ) -> std::result::Result<Response<proto::EmbedResponse>, Status> {
+        let base64 =
+            request.get_ref().encoding_format == proto::EmbedEncodingFormat::FormatBase64 as i32;
+        self.respond(MockMethod::Embed, request.metadata(), |state| {
+            next(&mut state.embed_responses, MockMethod::Embed)
+        })
+        .await
+        .map(|mut response| {
+            if base64 {
+                encode_embeddings_base64(&mut response);
+            }
+            Response::new(response)
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl proto::tokenize_server::Tokenize for MockGrokServer {
+    async fn tokenize_text(
+        &self,
+        request: Request<proto::TokenizeTextRequest>,
+    ) -> std::result::Result<Response<proto::TokenizeTextResponse>, Status> {
+        self.respond(MockMethod::TokenizeText, request.metadata(), |state| {
+            next(&mut state.tokenize_responses, MockMethod::TokenizeText)
+        })
+        .await
+        .map(Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::auth_server::Auth for MockGrokServer {
+    async fn get_api_key_info(
+        &self,
+        request: Request<()>,
+    ) -> std::result::Result<Response<proto::ApiKey>, Status> {
+        self.respond(MockMethod::GetApiKeyInfo, request.metadata(), |state| {
+            state.api_key.clone().ok_or_else(|| {
+                Status::failed_precondition("MockGrokServer: no API key info configured")
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::sample_server::Sample for MockGrokServer {
+    type SampleTextStreamingStream = MockStream<proto::SampleTextResponse>;
+
+    async fn sample_text(
+        &self,
+        request: Request<proto::SampleTextRequest>,
+    ) -> std::result::Result<Response<proto::SampleTextResponse>, Status> {
+        self.respond(MockMethod::SampleText, request.metadata(), |state| {
+            next(&mut state.sample_responses, MockMethod::SampleText)
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn sample_text_streaming(
+        &self,
+        request: Request<proto::SampleTextRequest>,
+    ) -> std::result::Result<Response<Self::SampleTextStreamingStream>, Status> {
+        let chunks = self
+            .respond(
+                MockMethod::SampleTextStreaming,
+                request.metadata(),
+                |state| next(&mut state.sample_streams, MockMethod::SampleTextStreaming),
+            )
+            .await?;
+        Ok(Response::new(
+            self.stream(chunks.into_iter().map(Ok).collect()),
+        ))
+    }
+}
+
+#[tonic::async_trait]
+impl proto::image_server::Image for MockGrokServer {
+    async fn generate_image(
+        &self,
+        request: Request<proto::GenerateImageRequest>,
+    ) -> std::result::Result<Response<proto::ImageResponse>, Status> {
+        self.respond(MockMethod::GenerateImage, request.metadata(), |state| {
+            next(&mut state.image_responses, MockMethod::GenerateImage)
+        })
+        .await
+        .map(Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::documents_server::Documents for MockGrokServer {
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> std::result::Result<Response<proto::SearchResponse>, Status> {
+        self.respond(MockMethod::Search, request.metadata(), |state| {
+            next(&mut state.search_responses, MockMethod::Search)
+        })
+        .await
+        .map(Response::new)
+    }
+}
+
+// Conversions from the public response types back to their wire representation.
+// They mirror the `From<proto::...>` impls used by the client.
+
+fn timestamp(seconds: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp { seconds, nanos: 0 }
+}
+
+fn finish_reason_to_proto(reason: &FinishReason) -> i32 {
+    let reason = match reason {
+        FinishReason::Stop => proto::FinishReason::ReasonStop,
+        FinishReason::Length => proto::FinishReason::ReasonMaxLen,
+        FinishReason::ToolCalls => proto::FinishReason::ReasonToolCalls,
+        FinishReason::Error(_) => proto::FinishReason::ReasonTimeLimit,
+        // The API has no content filter reason
+        FinishReason::ContentFilter | FinishReason::Unknown => proto::FinishReason::ReasonInvalid,
+    };
+    reason as i32
+}
+
+fn usage_to_proto(usage: &TokenUsage) -> proto::SamplingUsage {
+    proto::SamplingUsage {
+        prompt_tokens: usage.prompt_tokens as i32,
+        completion_tokens: usage.completion_tokens as i32,
+        total_tokens: usage.total_tokens as i32,
+        cached_prompt_text_tokens: usage.cached_prompt_tokens as i32,
+        prompt_image_tokens: usage.prompt_image_tokens as i32,
+        reasoning_tokens: usage.reasoning_tokens as i32,
+        num_sources_used: usage.num_sources_used as i32,
+        ..Default::default()
+    }
+}
+
+fn logprobs_to_proto(logprobs: &LogProbs) -> proto::LogProbs {
+    proto::LogProbs {
+        content: logprobs
+            .content
+            .iter()
+            .map(|log_prob| proto::LogProb {
+                token: log_prob.token.clone(),
+                logprob: log_prob.logprob,
+                bytes: log_prob.bytes.clone(),
+                top_logprobs: log_prob
+                    .top_logprobs
+                    .iter()
+                    .map(|top| proto::TopLogProb {
+                        token: top.token.clone(),
+                        logprob: top.logprob,
+                        bytes: top.bytes.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn chat_response_to_proto(response: &ChatResponse) -> proto::GetChatCompletionResponse {
+    // Responses built by hand often only fill in the top-level fields
+    let outputs = if response.choices.is_empty() {
+        vec![proto::CompletionOutput {
+            finish_reason: finish_reason_to_proto(&response.finish_reason),
+            index: 0,
+            message: Some(proto::CompletionMessage {
+                content: response.content.clone(),
+                reasoning_content: response.reasoning_content.clone().unwrap_or_default(),
+                role: proto::MessageRole::RoleAssistant as i32,
+                tool_calls: response.tool_calls.iter().map(|tc| tc.to_proto()).collect(),
+                ..Default::default()
+            }),
+            logprobs: response.logprobs.as_ref().map(logprobs_to_proto),
+        }]
+    } else {
+        response.choices.iter().map(choice_to_proto).collect()
+    };
+
+    proto::GetChatCompletionResponse {
+        id: response.request_id.clone(),
+        outputs,
+        created: response.created.map(timestamp),
+        model: response.model.clone(),
+        system_fingerprint: response.system_fingerprint.clone().unwrap_or_default(),
+        usage: Some(usage_to_proto(&response.usage)),
+        citations: response.citations.clone(),
+    }
+}
+
+fn choice_to_proto(choice: &Choice) -> proto::CompletionOutput {
+    proto::CompletionOutput {
+        finish_reason: finish_reason_to_proto(&choice.finish_reason),
+        index: choice.index as i32,
+        message: Some(proto::CompletionMessage {
+            content: choice.content.clone(),
+            reasoning_content: choice.reasoning_content.clone().unwrap_or_default(),
+            role: proto::MessageRole::RoleAssistant as i32,
+            tool_calls: choice.tool_calls.iter().map(|tc| tc.to_proto()).collect(),
+            ..Default::default()
+        }),
+        logprobs: choice.logprobs.as_ref().map(logprobs_to_proto),
+    }
+}
+
+fn chat_chunk_to_proto(chunk: &ChatChunk) -> proto::GetChatCompletionChunk {
+    // Chunks built by hand often only fill in the top-level fields
+    let outputs = if chunk.choices.is_empty() {
+        vec![choice_delta_to_proto(&ChoiceDelta {
+            index: chunk.index,
+            delta: chunk.delta.clone(),
+            finish_reason: chunk.finish_reason.clone(),
+            reasoning_delta: chunk.reasoning_delta.clone(),
+            tool_calls: chunk.tool_calls.clone(),
+            logprobs: chunk.logprobs.clone(),
+        })]
+    } else {
+        chunk.choices.iter().map(choice_delta_to_proto).collect()
+    };
+
+    proto::GetChatCompletionChunk {
+        id: chunk.request_id.clone(),
+        outputs,
+        created: chunk.created.map(timestamp),
+        model: chunk.model.clone(),
+        system_fingerprint: chunk.system_fingerprint.clone().unwrap_or_default(),
+        usage: Some(usage_to_proto(&chunk.cumulative_usage)),
+        citations: chunk.citations.clone(),
+    }
+}
+
+fn choice_delta_to_proto(delta: &ChoiceDelta) -> proto::CompletionOutputChunk {
+    proto::CompletionOutputChunk {
+        delta: Some(proto::Delta {
+            content: delta.delta.clone(),
+            reasoning_content: delta.reasoning_delta.clone().unwrap_or_default(),
+            role: proto::MessageRole::RoleAssistant as i32,
+            tool_calls: delta.tool_calls.iter().map(|tc| tc.to_proto()).collect(),
+            ..Default::default()
+        }),
+        logprobs: delta.logprobs.as_ref().map(logprobs_to_proto),
+        finish_reason: delta
+            .finish_reason
+            .as_ref()
+            .map(finish_reason_to_proto)
+            .unwrap_or_default(),
+        index: delta.index as i32,
+    }
+}
+
+fn modalities_to_proto(modalities: &[Modality]) -> Vec<i32> {
+    modalities
+        .iter()
+        .map(|modality| match modality {
+            Modality::Text => proto::Modality::Text as i32,
+            Modality::Image => proto::Modality::Image as i32,
+            Modality::Embedding => proto::Modality::Embedding as i32,
+        })
+        .collect()
+}
+
+fn language_model_to_proto(model: &LanguageModel) -> proto::LanguageModel {
+    proto::LanguageModel {
+        name: model.name.clone(),
+        aliases: model.aliases.clone(),
+        version: model.version.clone(),
+        input_modalities: modalities_to_proto(&model.input_modalities),
+        output_modalities: modalities_to_proto(&model.output_modalities),
+        prompt_text_token_price: model.prompt_text_token_price,
+        prompt_image_token_price: model.prompt_image_token_price,
+        cached_prompt_token_price: model.cached_prompt_token_price,
+        completion_text_token_price: model.completion_text_token_price,
+        search_price: model.search_price,
+        max_prompt_length: model.max_prompt_length,
+        system_fingerprint: model.system_fingerprint.clone(),
+        ..Default::default()
+    }
+}
+
+fn embedding_model_to_proto(model: &EmbeddingModel) -> proto::EmbeddingModel {
+    proto::EmbeddingModel {
+        name: model.name.clone(),
+        aliases: model.aliases.clone(),
+        version: model.version.clone(),
+        input_modalities: modalities_to_proto(&model.input_modalities),
+        output_modalities: modalities_to_proto(&model.output_modalities),
+        prompt_text_token_price: model.prompt_text_token_price,
+        prompt_image_token_price: model.prompt_image_token_price,
+        system_fingerprint: model.system_fingerprint.clone(),
+        ..Default::default()
+    }
+}
+
+fn image_generation_model_to_proto(model: &ImageGenerationModel) -> proto::ImageGenerationModel {
+    proto::ImageGenerationModel {
+        name: model.name.clone(),
+        aliases: model.aliases.clone(),
+        version: model.version.clone(),
+        input_modalities: modalities_to_proto(&model.input_modalities),
+        output_modalities: modalities_to_proto(&model.output_modalities),
+        image_price: model.image_price,
+        max_prompt_length: model.max_prompt_length,
+        system_fingerprint: model.system_fingerprint.clone(),
+        ..Default::default()
+    }
+}
+
+/// Move the float vectors of `response` to `base64_array`, as the API does
+/// when asked for base64.
+fn encode_embeddings_base64(response: &mut proto::EmbedResponse) {
+    for vector in response
+        .embeddings
+        .iter_mut()
+        .flat_map(|embedding| embedding.embeddings.iter_mut())
+    {
+        let bytes: Vec<u8> = std::mem::take(&mut vector.float_array)
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        vector.base64_array = base64::engine::general_purpose::STANDARD.encode(bytes);
+    }
+}
+
+fn embed_response_to_proto(response: &EmbedResponse) -> proto::EmbedResponse {
+    proto::EmbedResponse {
+        id: response.id.clone(),
+        embeddings: response
+            .embeddings
+            .iter()
+            .map(|embedding| proto::Embedding {
+                index: embedding.index as i32,
+                embeddings: vec![proto::FeatureVector {
+                    float_array: embedding.values().into_owned(),
+                    ..Default::default()
+                }],
+            })
+            .collect(),
+        usage: Some(proto::EmbeddingUsage {
+            num_text_embeddings: response.usage.num_text_embeddings as i32,
+            num_image_embeddings: response.usage.num_image_embeddings as i32,
+        }),
+        model: response.model.clone(),
+        system_fingerprint: response.system_fingerprint.clone(),
+    }
+}
+
+fn tokenize_response_to_proto(response: &TokenizeResponse) -> proto::TokenizeTextResponse {
+    proto::TokenizeTextResponse {
+        tokens: response
+            .tokens
+            .iter()
+            .map(|token| proto::Token {
+                token_id: token.token_id,
+                string_token: token.string_token.clone(),
+                token_bytes: token.token_bytes.clone(),
+            })
+            .collect(),
+        model: response.model.clone(),
+    }
+}
+
+fn api_key_to_proto(info: &ApiKeyInfo) -> proto::ApiKey {
+    proto::ApiKey {
+        redacted_api_key: info.redacted_api_key.clone(),
+        user_id: info.user_id.clone(),
+        name: info.name.clone(),
+        create_time: Some(timestamp(info.created_at)),
+        modify_time: Some(timestamp(info.modified_at)),
+        modified_by: info.modified_by.clone(),
+        team_id: info.team_id.clone(),
+        acls: info.acls.clone(),
+        api_key_id: info.api_key_id.clone(),
+        api_key_blocked: info.api_key_blocked,
+        team_blocked: info.team_blocked,
+        disabled: info.disabled,
+    }
+}
+
+fn sample_response_to_proto(response: &SampleResponse) -> proto::SampleTextResponse {
+    proto::SampleTextResponse {
+        id: response.id.clone(),
+        choices: response
+            .choices
+            .iter()
+            .map(|choice| {
+                let finish_reason = match choice.finish_reason.as_str() {
+                    "stop" => proto::FinishReason::ReasonStop,
+                    "length" => proto::FinishReason::ReasonMaxLen,
+                    "max_context" => proto::FinishReason::ReasonMaxContext,
+                    "tool_calls" => proto::FinishReason::ReasonToolCalls,
+                    "time_limit" => proto::FinishReason::ReasonTimeLimit,
+                    _ => proto::FinishReason::ReasonInvalid,
+                };
+                proto::SampleChoice {
+                    finish_reason: finish_reason as i32,
+                    index: choice.index,
+                    text: choice.text.clone(),
+                }
+            })
+            .collect(),
+        model: response.model.clone(),
+        usage: Some(proto::SamplingUsage {
+            total_tokens: response.total_tokens,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn image_response_to_proto(response: &ImageGenerationResponse) -> proto::ImageResponse {
+    proto::ImageResponse {
+        images: response
+            .images
+            .iter()
+            .map(|image| {
+                let data = match (&image.base64, &image.url) {
+                    (Some(b64), _) => Some(proto::generated_image::Image::Base64(b64.clone())),
+                    (None, Some(url)) => Some(proto::generated_image::Image::Url(url.clone())),
+                    (None, None) => None,
+                };
+                proto::GeneratedImage {
+                    image: data,
+                    up_sampled_prompt: image.upsampled_prompt.clone(),
+                    respect_moderation: image.respects_moderation,
+                }
+            })
+            .collect(),
+        model: response.model.clone(),
+    }
+}
+
+fn search_response_to_proto(response: &DocumentSearchResponse) -> proto::SearchResponse {
+    proto::SearchResponse {
+        matches: response
+            .matches
+            .iter()
+            .map(|m| proto::SearchMatch {
+                file_id: m.file_id.clone(),
+                chunk_id: m.chunk_id.clone(),
+                chunk_content: m.content.clone(),
+                score: m.score,
+                collection_ids: m.collection_ids.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        request::ChatRequest,
+        tokenize::{Token, TokenizeRequest},
+    };
+
+    fn chat_response(content: &str) -> ChatResponse {
+        ChatResponse {
+            model: "grok-2-1212".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 5,
+                completion_tokens: 3,
+                total_tokens: 8,
+                ..Default::default()
+            },
+            created: Some(1_700_000_000),
+            ..ChatResponse::test_reply("req-1", content)
+        }
+    }
+
+    fn chunk(delta: &str, finish_reason: Option<FinishReason>) -> ChatChunk {
+        ChatChunk {
+            request_id: "req-1".to_string(),
+            model: "grok-2-1212".to_string(),
+            created: None,
+            system_fingerprint: None,
+            index: 0,
+            delta: delta.to_string(),
+            finish_reason,
+            cumulative_usage: TokenUsage::default(),
+            reasoning_delta: None,
+            tool_calls: vec![],
+            logprobs: None,
+            citations: vec![],
+            choices: vec![],
+        }
+    }
+
+    fn language_model(name: &str, alias: &str) -> LanguageModel {
+        LanguageModel {
+            name: name.to_string(),
+            aliases: vec![alias.to_string()],
+            version: "1.0".to_string(),
+            input_modalities: vec![Modality::Text],
+            output_modalities: vec![Modality::Text],
+            prompt_text_token_price: 2000,
+            prompt_image_token_price: 0,
+            cached_prompt_token_price: 500,
+            completion_text_token_price: 10000,
+            search_price: 0,
+            max_prompt_length: 131072,
+            system_fingerprint: "fp".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_chat_round_trip() {
+        let server = MockGrokServer::new();
+        server.push_chat_response(chat_response("Hello!"));
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let response = client
+            .complete_chat(ChatRequest::new().user_message("Hi"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hello!");
+        assert_eq!(response.usage.total_tokens, 8);
+        assert_eq!(response.created, Some(1_700_000_000));
+        assert!(matches!(response.finish_reason, FinishReason::Stop));
+        assert_eq!(server.call_count(MockMethod::GetCompletion), 1);
+
+        let metadata = server.last_metadata(MockMethod::GetCompletion).unwrap();
+        assert_eq!(
+            metadata.get("authorization").unwrap(),
+            &format!("Bearer {MOCK_API_KEY}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_yields_chunks_then_error() {
+        let server = MockGrokServer::new();
+        server.push_chat_stream_then_error(
+            vec![chunk("Hel", None), chunk("lo", None)],
+            Status::internal("stream broke"),
+        );
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let mut stream = client
+            .stream_chat(ChatRequest::new().user_message("Hi"))
+            .await
+            .unwrap();
+
+        let mut text = String::new();
+        let mut error = None;
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => text.push_str(&chunk.delta),
+                Err(e) => error = Some(e),
+            }
+        }
+
+        assert_eq!(text, "Hello");
+        assert!(matches!(error, Some(GrokError::Status(_))));
+    }
+
+    #[tokio::test]
+    async fn test_injected_error_is_mapped() {
+        let server = MockGrokServer::new();
+        server.push_error(
+            MockMethod::GetCompletion,
+            Status::unauthenticated("bad key"),
+        );
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let result = client
+            .complete_chat(ChatRequest::new().user_message("Hi"))
+            .await;
+
+        assert!(matches!(result, Err(GrokError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retryable_error_is_retried() {
+        let server = MockGrokServer::new();
+        server
+            .push_error(MockMethod::TokenizeText, Status::unavailable("busy"))
+            .push_tokenize_response(TokenizeResponse {
+                tokens: vec![Token {
+                    token_id: 1,
+                    string_token: "Hi".to_string(),
+                    token_bytes: b"Hi".to_vec(),
+                }],
+                model: "grok-2-1212".to_string(),
+            });
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let response = client
+            .tokenize(TokenizeRequest::new("grok-2-1212").with_text("Hi"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.token_count(), 1);
+        assert_eq!(server.call_count(MockMethod::TokenizeText), 2);
+    }
+
+    #[tokio::test]
+    async fn test_models_lookup_by_alias() {
+        let server = MockGrokServer::new();
+        server.set_language_models(vec![language_model("grok-2-1212", "grok-2-latest")]);
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        assert_eq!(client.list_models().await.unwrap().len(), 1);
+
+        let model = client.get_model("grok-2-latest").await.unwrap();
+        assert_eq!(model.name, "grok-2-1212");
+        assert_eq!(model.cached_prompt_token_price, 500);
+
+        let missing = client.get_model("grok-0").await;
+        assert!(matches!(missing, Err(GrokError::Status(s)) if s.code() == tonic::Code::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_empty_queue_fails() {
+        let server = MockGrokServer::new();
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let result = client
+            .complete_chat(ChatRequest::new().user_message("Hi"))
+            .await;
+
+        assert!(
+            matches!(result, Err(GrokError::Status(s)) if s.code() == tonic::Code::FailedPrecondition)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deferred_completion_pending_then_done() {
+        let server = MockGrokServer::new();
+        server
+            .set_deferred_pending_polls(1)
+            .push_chat_response(chat_response("Done"));
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let request_id = client
+            .start_deferred(ChatRequest::new().user_message("Hi"))
+            .await
+            .unwrap();
+
+        assert!(client
+            .poll_deferred(request_id.clone())
+            .await
+            .unwrap()
+            .is_none());
+        let response = client.poll_deferred(request_id).await.unwrap().unwrap();
+        assert_eq!(response.content, "Done");
+    }
+
+    #[tokio::test]
+    async fn test_latency_is_applied() {
+        let server = MockGrokServer::new();
+        server
+            .set_latency(Duration::from_millis(50))
+            .push_chat_response(chat_response("Slow"));
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let started = std::time::Instant::now();
+        client
+            .complete_chat(ChatRequest::new().user_message("Hi"))
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
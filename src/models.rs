@@ -14,7 +14,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!     let models = client.list_models().await?;
 //!
 //!     for model in models {
@@ -31,7 +31,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!     let model = client.get_model("grok-2-1212").await?;
 //!
 //!     println!("Model: {}", model.name);
@@ -47,7 +47,16 @@
 //! }
 //! ```
 
-use crate::proto;
+use crate::{proto, response::TokenUsage};
+
+/// Divisor turning `count * price` into USD: prices are in 1/100 USD cents
+/// (1/10,000 USD) per million tokens.
+const PRICE_DIVISOR: f64 = 1_000_000.0 * 10_000.0;
+
+/// Cost in USD of `count` tokens at `price` (1/100 USD cents per 1M tokens).
+fn price_usd(count: u64, price: i64) -> f64 {
+    count as f64 * price as f64 / PRICE_DIVISOR
+}
 
 /// Information about a language model.
 ///
@@ -60,9 +69,11 @@ use crate::proto;
 /// - `prompt_text_token_price`: 1/100 USD cents per 1M tokens (e.g., 500 = $0.05 per 1M tokens)
 /// - `prompt_image_token_price`: 1/100 USD cents per 1M tokens
 /// - `completion_text_token_price`: 1/100 USD cents per 1M tokens
-/// - `cached_prompt_token_price`: USD cents per 100M tokens (e.g., 50 = $0.50 per 100M tokens)
+/// - `cached_prompt_token_price`: 1/100 USD cents per 1M tokens
 /// - `search_price`: 1/100 USD cents per 1M searches
 ///
+/// This is the same as USD cents per 100M tokens, the unit used by the API docs.
+///
 /// Use [`calculate_cost`](LanguageModel::calculate_cost) to convert these to USD amounts.
 ///
 /// # Examples
@@ -71,7 +82,7 @@ use crate::proto;
 /// # use xai_grpc_client::GrokClient;
 /// # #[tokio::main]
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// # let mut client = GrokClient::from_env().await?;
+/// # let client = GrokClient::from_env().await?;
 /// let model = client.get_model("grok-2-1212").await?;
 ///
 /// // Check capabilities
@@ -86,6 +97,7 @@ use crate::proto;
 /// # }
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LanguageModel {
     /// The model name used in API requests (e.g., "grok-2-1212").
     pub name: String,
@@ -114,7 +126,7 @@ pub struct LanguageModel {
 
     /// Price per million prompt text tokens in 1/100 USD cents.
     ///
-    /// Example: 500 = $0.05 per 1M tokens = $0.00005 per token
+    /// Example: 500 = $0.05 per 1M tokens
     pub prompt_text_token_price: i64,
 
     /// Price per million prompt image tokens in 1/100 USD cents.
@@ -122,9 +134,9 @@ pub struct LanguageModel {
     /// Only applicable for multimodal models that accept images.
     pub prompt_image_token_price: i64,
 
-    /// Price per 100 million cached prompt tokens in USD cents.
+    /// Price per million cached prompt tokens in 1/100 USD cents.
     ///
-    /// Example: 50 = $0.50 per 100M tokens
+    /// Example: 50 = $0.005 per 1M tokens
     ///
     /// Cached tokens are significantly cheaper as they're reused from
     /// previous requests with the same prefix.
@@ -132,7 +144,7 @@ pub struct LanguageModel {
 
     /// Price per million completion text tokens in 1/100 USD cents.
     ///
-    /// Example: 1500 = $0.15 per 1M tokens = $0.00015 per token
+    /// Example: 1500 = $0.15 per 1M tokens
     pub completion_text_token_price: i64,
 
     /// Price per million searches in 1/100 USD cents.
@@ -160,6 +172,8 @@ pub struct LanguageModel {
 /// - Vision models: `input_modalities: [Text, Image]`, `output_modalities: [Text]`
 /// - Embedding models: `input_modalities: [Text]`, `output_modalities: [Embedding]`
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Modality {
     /// Text input/output - supported by all language models.
     Text,
@@ -194,7 +208,7 @@ pub enum Modality {
 /// # use xai_grpc_client::GrokClient;
 /// # #[tokio::main]
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// # let mut client = GrokClient::from_env().await?;
+/// # let client = GrokClient::from_env().await?;
 /// let model = client.get_embedding_model("embed-large-v1").await?;
 ///
 /// println!("Model: {}", model.name);
@@ -203,6 +217,7 @@ pub enum Modality {
 /// # }
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmbeddingModel {
     /// The model name used in API requests (e.g., "embed-large-v1").
     pub name: String,
@@ -243,7 +258,7 @@ pub struct EmbeddingModel {
 /// # use xai_grpc_client::GrokClient;
 /// # #[tokio::main]
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// # let mut client = GrokClient::from_env().await?;
+/// # let client = GrokClient::from_env().await?;
 /// let model = client.get_image_generation_model("image-gen-1").await?;
 ///
 /// println!("Model: {}", model.name);
@@ -252,6 +267,7 @@ pub struct EmbeddingModel {
 /// # }
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImageGenerationModel {
     /// The model name used in API requests.
     pub name: String,
@@ -323,6 +339,12 @@ impl From<proto::Modality> for Modality {
 impl LanguageModel {
     /// Calculate the cost (in USD) for a given number of prompt and completion tokens.
     ///
+    /// `cached_tokens` are the part of `prompt_tokens` served from the prompt
+    /// cache, as in [`TokenUsage::cached_prompt_tokens`]: they are billed at
+    /// the cached prompt price and the rest of the prompt at the regular one.
+    /// To also bill image and reasoning tokens, use
+    /// [`usage_cost`](Self::usage_cost).
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -350,15 +372,57 @@ impl LanguageModel {
         completion_tokens: u32,
         cached_tokens: u32,
     ) -> f64 {
-        let prompt_cost =
-            (prompt_tokens as f64 * self.prompt_text_token_price as f64) / 1_000_000.0 / 100.0;
-        let cached_cost =
-            (cached_tokens as f64 * self.cached_prompt_token_price as f64) / 100_000_000.0;
-        let completion_cost = (completion_tokens as f64 * self.completion_text_token_price as f64)
-            / 1_000_000.0
-            / 100.0;
-
-        prompt_cost + cached_cost + completion_cost
+        self.tokens_cost(
+            prompt_tokens.into(),
+            cached_tokens.into(),
+            0,
+            completion_tokens.into(),
+        )
+    }
+
+    /// Calculate the cost (in USD) of the [`TokenUsage`] reported for a response.
+    ///
+    /// The cached and image tokens are read out of the prompt tokens: cached
+    /// tokens are billed at the cached prompt price, image tokens at the image
+    /// price and the rest at the text price. Reasoning tokens are billed as
+    /// completion tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use xai_grpc_client::{ChatRequest, GrokClient};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = GrokClient::from_env().await?;
+    /// let response = client.complete_chat(ChatRequest::new().user_message("Hi")).await?;
+    /// let model = client.get_model(&response.model).await?;
+    /// println!("Cost: ${:.6}", model.usage_cost(&response.usage));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn usage_cost(&self, usage: &TokenUsage) -> f64 {
+        self.tokens_cost(
+            usage.prompt_tokens.into(),
+            usage.cached_prompt_tokens.into(),
+            usage.prompt_image_tokens.into(),
+            u64::from(usage.completion_tokens) + u64::from(usage.reasoning_tokens),
+        )
+    }
+
+    /// Cost in USD of `prompt_tokens` (of which `cached_tokens` were cached and
+    /// `image_tokens` were images) and `completion_tokens`.
+    pub(crate) fn tokens_cost(
+        &self,
+        prompt_tokens: u64,
+        cached_tokens: u64,
+        image_tokens: u64,
+        completion_tokens: u64,
+    ) -> f64 {
+        let text_tokens = prompt_tokens.saturating_sub(cached_tokens + image_tokens);
+
+        price_usd(text_tokens, self.prompt_text_token_price)
+            + price_usd(cached_tokens, self.cached_prompt_token_price)
+            + price_usd(image_tokens, self.prompt_image_token_price)
+            + price_usd(completion_tokens, self.completion_text_token_price)
     }
 
     /// Check if the model supports multimodal input (text + images).
@@ -372,7 +436,7 @@ impl LanguageModel {
     /// # use xai_grpc_client::GrokClient;
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let mut client = GrokClient::from_env().await?;
+    /// # let client = GrokClient::from_env().await?;
     /// let model = client.get_model("grok-2-vision-1212").await?;
     ///
     /// if model.supports_multimodal() {
@@ -450,10 +514,10 @@ mod tests {
             version: "1.0".to_string(),
             input_modalities: vec![Modality::Text],
             output_modalities: vec![Modality::Text],
-            prompt_text_token_price: 500, // $0.005 per 1M tokens
+            prompt_text_token_price: 500, // $0.05 per 1M tokens
             prompt_image_token_price: 0,
-            cached_prompt_token_price: 50,     // $0.50 per 100M tokens
-            completion_text_token_price: 1500, // $0.015 per 1M tokens
+            cached_prompt_token_price: 50,     // $0.005 per 1M tokens
+            completion_text_token_price: 1500, // $0.15 per 1M tokens
             search_price: 0,
             max_prompt_length: 131072,
             system_fingerprint: "fp_test".to_string(),
@@ -467,11 +531,11 @@ mod tests {
         // price is in 1/100 USD cents per 1M tokens
         // 500 = $0.05 per 1M tokens = $0.00005 per 1K tokens
         // 1500 = $0.15 per 1M tokens = $0.00015 per 1K tokens
-        // 1000 prompt * 0.00005 + 500 completion * 0.00015 = 0.005 + 0.0075 = 0.0125
+        // 1K prompt * 0.00005 + 0.5K completion * 0.00015 = 0.00005 + 0.000075 = 0.000125
         let cost = model.calculate_cost(1000, 500, 0);
         assert!(
-            (cost - 0.0125).abs() < 0.0001,
-            "Expected ~$0.0125, got ${cost}"
+            (cost - 0.000125).abs() < 1e-12,
+            "Expected ~$0.000125, got ${cost}"
         );
     }
 
@@ -479,15 +543,24 @@ mod tests {
     fn test_calculate_cost_with_cached() {
         let model = create_test_model();
 
-        // cached_prompt_token_price is in USD cents per 100M tokens
-        // Formula: (cached_tokens * cached_prompt_token_price) / 100_000_000
-        // = (10000 * 50) / 100_000_000 = 500000 / 100000000 = $0.005
-        // Total: $0.005 (prompt) + $0.0075 (completion) + $0.005 (cached) = $0.0175
-        let cost = model.calculate_cost(1000, 500, 10000);
+        // cached_prompt_token_price uses the same unit as the other prices
+        // 50 = $0.005 per 1M tokens; the 8K cached tokens are part of the 10K prompt
+        // Total: 2K text * $0.05/1M + 8K cached * $0.005/1M + 0.5K completion * $0.15/1M
+        // = $0.0001 + $0.00004 + $0.000075 = $0.000215
+        let cost = model.calculate_cost(10_000, 500, 8_000);
         assert!(
-            (cost - 0.0175).abs() < 0.0001,
-            "Expected ~$0.0175, got ${cost}"
+            (cost - 0.000215).abs() < 1e-12,
+            "Expected ~$0.000215, got ${cost}"
         );
+
+        let usage = TokenUsage {
+            prompt_tokens: 10_000,
+            completion_tokens: 500,
+            total_tokens: 10_500,
+            cached_prompt_tokens: 8_000,
+            ..Default::default()
+        };
+        assert_eq!(cost, model.usage_cost(&usage));
     }
 
     #[test]
@@ -495,9 +568,35 @@ mod tests {
         let model = create_test_model();
 
         // 1M prompt + 100K completion
-        // = 1M * 0.00005 + 100K * 0.00015 = $5.0 + $1.5 = $6.50
+        // = 1M * $0.05/1M + 100K * $0.15/1M = $0.05 + $0.015 = $0.065
         let cost = model.calculate_cost(1_000_000, 100_000, 0);
-        assert!((cost - 6.5).abs() < 0.01, "Expected ~$6.50, got ${cost}");
+        assert!((cost - 0.065).abs() < 1e-9, "Expected ~$0.065, got ${cost}");
+    }
+
+    #[test]
+    fn test_usage_cost_splits_prompt_tokens() {
+        let model = LanguageModel {
+            prompt_image_token_price: 1000,
+            ..create_test_model()
+        };
+        let usage = TokenUsage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 100_000,
+            total_tokens: 1_200_000,
+            cached_prompt_tokens: 600_000,
+            prompt_image_tokens: 100_000,
+            reasoning_tokens: 100_000,
+            num_sources_used: 0,
+        };
+
+        // 300K text * $0.05/1M + 600K cached * $0.005/1M + 100K images * $0.10/1M
+        // + (100K completion + 100K reasoning) * $0.15/1M
+        // = $0.015 + $0.003 + $0.01 + $0.03 = $0.058
+        let cost = model.usage_cost(&usage);
+        assert!(
+            (cost - 0.058).abs() < 1e-12,
+            "Expected ~$0.058, got ${cost}"
+        );
     }
 
     #[test]
@@ -614,4 +713,16 @@ mod tests {
         assert_eq!(model.max_prompt_length, 32768);
         assert_eq!(model.system_fingerprint, "fp_test_123");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_language_model_serde_round_trip() {
+        let model = create_test_model();
+
+        let value = serde_json::to_value(&model).unwrap();
+        assert_eq!(value["input_modalities"], serde_json::json!(["text"]));
+
+        let restored: LanguageModel = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), value);
+    }
 }
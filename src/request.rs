@@ -5,7 +5,8 @@
 //! and more.
 
 use crate::proto::IncludeOption;
-use crate::tools::{Tool, ToolChoice};
+use crate::response::ChatResponse;
+use crate::tools::{Tool, ToolCall, ToolChoice};
 use serde_json::Value as JsonValue;
 
 /// Configuration options for chat completions.
@@ -24,6 +25,8 @@ use serde_json::Value as JsonValue;
 ///     .with_max_tokens(500);
 /// ```
 #[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CompletionOptions {
     /// Model to use for completion.
     pub model: Option<String>,
@@ -79,6 +82,8 @@ impl CompletionOptions {
 ///     .with_temperature(0.7);
 /// ```
 #[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ChatRequest {
     messages: Vec<Message>,
     model: Option<String>,
@@ -89,6 +94,7 @@ pub struct ChatRequest {
     reasoning_effort: Option<ReasoningEffort>,
     search: Option<SearchConfig>,
     seed: Option<i32>,
+    n: Option<u32>,
     response_format: Option<ResponseFormat>,
     tools: Option<Vec<Tool>>,
     tool_choice: Option<ToolChoice>,
@@ -103,6 +109,10 @@ pub struct ChatRequest {
     store_messages: bool,
     use_encrypted_content: bool,
     max_turns: Option<i32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::include_options")
+    )]
     include: Vec<IncludeOption>,
 }
 
@@ -111,6 +121,8 @@ pub struct ChatRequest {
 /// Messages can be from the system (instructions), user (input), assistant (AI response),
 /// or tool (result from a tool execution).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Message {
     /// System message providing instructions or context to the model.
     System(String),
@@ -118,17 +130,30 @@ pub enum Message {
     User(MessageContent),
     /// Assistant message containing the AI's previous response.
     Assistant(String),
+    /// Assistant message replayed with everything the model produced: its text,
+    /// its reasoning trace and the tool calls it made.
+    ///
+    /// Use this instead of [`Assistant`](Self::Assistant) when rebuilding a
+    /// conversation that contains tool calls, so the model sees which calls the
+    /// following [`Tool`](Self::Tool) messages answer. Usually created with
+    /// [`ChatResponse::to_message`] or [`ChatRequest::assistant_response`].
+    AssistantReply {
+        /// The text content of the reply.
+        content: String,
+        /// The reasoning trace produced before the reply, if any.
+        reasoning_content: Option<String>,
+        /// Tool calls made by the model in this reply.
+        tool_calls: Vec<ToolCall>,
+    },
     /// Tool result message containing the output from a tool execution.
     ///
     /// # Important: Message Order
     ///
-    /// The xAI gRPC API matches tool results to tool calls by **message order**, not by ID.
-    /// Tool results must be provided in the **same order** as the tool calls were received
-    /// from the model. The `tool_call_id` is accepted for API compatibility with other
-    /// providers (e.g., OpenAI) but is not used by xAI's gRPC API.
+    /// Tool results should be provided in the **same order** as the tool calls were
+    /// received from the model. The `tool_call_id` is sent along with the result, but
+    /// the xAI gRPC API falls back to matching results to calls by message order.
     Tool {
         /// The ID of the tool call this is responding to.
-        /// Note: Accepted for compatibility but not used by xAI's gRPC API.
         tool_call_id: String,
         /// The content/result of the tool execution.
         content: String,
@@ -137,6 +162,8 @@ pub enum Message {
 
 /// Content of a user message, which can be text-only or multimodal.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum MessageContent {
     /// Plain text message.
     Text(String),
@@ -146,6 +173,8 @@ pub enum MessageContent {
 
 /// A part of a multimodal message (text, image, or file attachment).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ContentPart {
     /// Text content.
     Text(String),
@@ -165,6 +194,8 @@ pub enum ContentPart {
 
 /// Level of detail for image processing in vision models.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ImageDetail {
     /// Automatic detail level.
     Auto,
@@ -174,6 +205,27 @@ pub enum ImageDetail {
     High,
 }
 
+impl Message {
+    /// The text of the message, leaving out images and file attachments.
+    pub(crate) fn text(&self) -> String {
+        match self {
+            Message::System(text) | Message::Assistant(text) => text.clone(),
+            Message::AssistantReply { content, .. } | Message::Tool { content, .. } => {
+                content.clone()
+            }
+            Message::User(MessageContent::Text(text)) => text.clone(),
+            Message::User(MessageContent::MultiModal(parts)) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
 impl From<String> for MessageContent {
     fn from(text: String) -> Self {
         MessageContent::Text(text)
@@ -191,6 +243,8 @@ impl From<&str> for MessageContent {
 /// Higher reasoning effort may produce better results for complex tasks
 /// but will take longer and cost more tokens.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ReasoningEffort {
     /// Minimal reasoning effort.
     Low,
@@ -200,21 +254,75 @@ pub enum ReasoningEffort {
     High,
 }
 
-/// Configuration for web search augmentation.
+/// Configuration for live search augmentation.
 ///
-/// Allows the model to search the web for up-to-date information.
+/// Allows the model to search the web, X, news and RSS feeds for up-to-date
+/// information.
+///
+/// # Examples
+///
+/// ```
+/// use xai_grpc_client::{ChatRequest, NewsSource, SearchConfig, SearchMode};
+///
+/// // News only, from French outlets, for the first week of January
+/// let search = SearchConfig::new()
+///     .with_mode(SearchMode::On)
+///     .with_source(NewsSource::new().with_country("FR"))
+///     .with_date_range(
+///         Some(prost_types::Timestamp { seconds: 1_735_689_600, nanos: 0 }),
+///         Some(prost_types::Timestamp { seconds: 1_736_294_400, nanos: 0 }),
+///     )
+///     .with_max_results(10);
+///
+/// let request = ChatRequest::new()
+///     .user_message("What happened in French politics this week?")
+///     .with_search(search);
+/// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SearchConfig {
     /// Search mode (default or advanced).
     pub mode: SearchMode,
     /// Sources to search (web, news, etc.).
+    ///
+    /// When empty, the server picks its default sources.
     pub sources: Vec<SearchSource>,
     /// Maximum number of search results to return.
     pub max_results: Option<u32>,
+    /// Only consider data published on or after this date.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_helpers::option_timestamp")
+    )]
+    pub from_date: Option<prost_types::Timestamp>,
+    /// Only consider data published on or before this date.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_helpers::option_timestamp")
+    )]
+    pub to_date: Option<prost_types::Timestamp>,
+    /// Whether to return the URLs of the sources used (default: true).
+    pub return_citations: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            mode: SearchMode::Auto,
+            sources: Vec::new(),
+            max_results: None,
+            from_date: None,
+            to_date: None,
+            return_citations: true,
+        }
+    }
 }
 
 /// Search mode for web search augmentation.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum SearchMode {
     /// Search disabled.
     Off,
@@ -224,19 +332,241 @@ pub enum SearchMode {
     Auto,
 }
 
-/// Source for search results.
+/// Source for search results, with its source-specific filters.
+///
+/// Each variant wraps a configuration struct; use the shorthand constructors
+/// ([`SearchSource::web`], [`SearchSource::x`], [`SearchSource::news`]) for the
+/// defaults, or convert a configured struct with `.into()`.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum SearchSource {
     /// General web search.
-    Web,
+    Web(WebSource),
     /// X (Twitter) search.
-    X,
+    X(XSource),
     /// News articles.
-    News,
+    News(NewsSource),
+    /// RSS feeds.
+    Rss(RssSource),
+}
+
+impl SearchSource {
+    /// Web search with default settings.
+    pub fn web() -> Self {
+        Self::Web(WebSource::default())
+    }
+
+    /// X search with default settings.
+    pub fn x() -> Self {
+        Self::X(XSource::default())
+    }
+
+    /// News search with default settings.
+    pub fn news() -> Self {
+        Self::News(NewsSource::default())
+    }
+}
+
+impl From<WebSource> for SearchSource {
+    fn from(source: WebSource) -> Self {
+        Self::Web(source)
+    }
+}
+
+impl From<XSource> for SearchSource {
+    fn from(source: XSource) -> Self {
+        Self::X(source)
+    }
+}
+
+impl From<NewsSource> for SearchSource {
+    fn from(source: NewsSource) -> Self {
+        Self::News(source)
+    }
+}
+
+impl From<RssSource> for SearchSource {
+    fn from(source: RssSource) -> Self {
+        Self::Rss(source)
+    }
+}
+
+/// Web search source configuration.
+///
+/// `allowed_websites` and `excluded_websites` cannot be combined.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WebSource {
+    /// Only search these websites (max 5).
+    pub allowed_websites: Vec<String>,
+    /// Never search these websites (max 5).
+    pub excluded_websites: Vec<String>,
+    /// ISO alpha-2 code of the country to favor results from (e.g. "FR").
+    pub country: Option<String>,
+    /// Filter out adult content (default: true).
+    pub safe_search: bool,
+}
+
+impl Default for WebSource {
+    fn default() -> Self {
+        Self {
+            allowed_websites: Vec::new(),
+            excluded_websites: Vec::new(),
+            country: None,
+            safe_search: true,
+        }
+    }
+}
+
+impl WebSource {
+    /// Create a web source with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict search to specific websites only.
+    pub fn with_allowed_websites(mut self, websites: Vec<String>) -> Self {
+        self.allowed_websites = websites;
+        self
+    }
+
+    /// Exclude specific websites from search results.
+    pub fn with_excluded_websites(mut self, websites: Vec<String>) -> Self {
+        self.excluded_websites = websites;
+        self
+    }
+
+    /// Favor results from a country (ISO alpha-2 code).
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    /// Enable or disable safe search.
+    pub fn with_safe_search(mut self, safe_search: bool) -> Self {
+        self.safe_search = safe_search;
+        self
+    }
+}
+
+/// News search source configuration.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NewsSource {
+    /// Never search these websites (max 5).
+    pub excluded_websites: Vec<String>,
+    /// ISO alpha-2 code of the country to favor results from (e.g. "FR").
+    pub country: Option<String>,
+    /// Filter out adult content (default: true).
+    pub safe_search: bool,
+}
+
+impl Default for NewsSource {
+    fn default() -> Self {
+        Self {
+            excluded_websites: Vec::new(),
+            country: None,
+            safe_search: true,
+        }
+    }
+}
+
+impl NewsSource {
+    /// Create a news source with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exclude specific websites from search results.
+    pub fn with_excluded_websites(mut self, websites: Vec<String>) -> Self {
+        self.excluded_websites = websites;
+        self
+    }
+
+    /// Favor results from a country (ISO alpha-2 code).
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    /// Enable or disable safe search.
+    pub fn with_safe_search(mut self, safe_search: bool) -> Self {
+        self.safe_search = safe_search;
+        self
+    }
+}
+
+/// X (Twitter) search source configuration.
+///
+/// `included_x_handles` and `excluded_x_handles` cannot be combined.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct XSource {
+    /// Only consider posts from these handles (max 10).
+    pub included_x_handles: Vec<String>,
+    /// Never consider posts from these handles (max 10).
+    pub excluded_x_handles: Vec<String>,
+    /// Only consider posts with at least this many likes.
+    pub post_favorite_count: Option<i32>,
+    /// Only consider posts with at least this many views.
+    pub post_view_count: Option<i32>,
+}
+
+impl XSource {
+    /// Create an X source with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only consider posts from these handles.
+    pub fn with_included_handles(mut self, handles: Vec<String>) -> Self {
+        self.included_x_handles = handles;
+        self
+    }
+
+    /// Never consider posts from these handles.
+    pub fn with_excluded_handles(mut self, handles: Vec<String>) -> Self {
+        self.excluded_x_handles = handles;
+        self
+    }
+
+    /// Only consider posts with at least `count` likes.
+    pub fn with_min_favorites(mut self, count: i32) -> Self {
+        self.post_favorite_count = Some(count);
+        self
+    }
+
+    /// Only consider posts with at least `count` views.
+    pub fn with_min_views(mut self, count: i32) -> Self {
+        self.post_view_count = Some(count);
+        self
+    }
+}
+
+/// RSS feed source configuration.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RssSource {
+    /// URLs of the RSS feeds to search.
+    pub links: Vec<String>,
+}
+
+impl RssSource {
+    /// Create an RSS source for the given feed URLs.
+    pub fn new(links: Vec<String>) -> Self {
+        Self { links }
+    }
 }
 
 /// Format for the model's response.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ResponseFormat {
     /// Plain text response (default).
     Text,
@@ -266,6 +596,39 @@ impl ChatRequest {
         self
     }
 
+    /// Add a previous model response to the conversation, including its
+    /// reasoning trace and tool calls.
+    ///
+    /// Unlike [`assistant_message`](Self::assistant_message), this keeps the
+    /// tool calls so that the tool results that follow can be paired with them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use xai_grpc_client::{ChatRequest, GrokClient};
+    ///
+    /// # async fn example(client: GrokClient, request: ChatRequest) -> Result<(), Box<dyn std::error::Error>> {
+    /// let response = client.complete_chat(request.clone()).await?;
+    ///
+    /// let mut follow_up = request.assistant_response(&response);
+    /// for call in &response.tool_calls {
+    ///     follow_up = follow_up.tool_result(&call.id, r#"{"temperature": 22}"#);
+    /// }
+    /// let final_response = client.complete_chat(follow_up).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assistant_response(mut self, response: &ChatResponse) -> Self {
+        self.messages.push(response.to_message());
+        self
+    }
+
+    /// Append an arbitrary message to the conversation.
+    pub fn add_message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
     /// Add a tool result message to the conversation.
     ///
     /// Tool result messages are sent after executing a client-side tool to provide
@@ -273,15 +636,17 @@ impl ChatRequest {
     ///
     /// # Important: Message Order
     ///
-    /// **Tool results must be provided in the same order as the tool calls were received.**
-    /// The xAI gRPC API matches tool results to tool calls by message order, not by ID.
-    /// If the model made multiple tool calls, you must provide the results in the exact
-    /// same sequence.
+    /// **Tool results should be provided in the same order as the tool calls were received.**
+    /// The `tool_call_id` is sent with the result, but the xAI gRPC API falls back to
+    /// matching tool results to tool calls by message order. If the model made multiple
+    /// tool calls, provide the results in the exact same sequence.
+    ///
+    /// To let the model see which calls are being answered, add the response that made
+    /// the calls with [`assistant_response`](Self::assistant_response) first.
     ///
     /// # Arguments
     ///
     /// * `tool_call_id` - The ID of the tool call this result corresponds to (from the model's tool_calls).
-    ///   Note: Accepted for API compatibility but not used by xAI's gRPC API.
     /// * `content` - The result content from the tool execution (typically JSON)
     ///
     /// # Example
@@ -396,11 +761,13 @@ impl ChatRequest {
     }
 
     pub fn with_web_search(mut self) -> Self {
-        self.search = Some(SearchConfig {
-            mode: SearchMode::Auto,
-            sources: vec![SearchSource::Web],
-            max_results: Some(5),
-        });
+        self.search = Some(SearchConfig::web());
+        self
+    }
+
+    /// Enable live search with the given sources and filters.
+    pub fn with_search(mut self, search: SearchConfig) -> Self {
+        self.search = Some(search);
         self
     }
 
@@ -424,6 +791,15 @@ impl ChatRequest {
         self
     }
 
+    /// Generate `n` completion choices for the same prompt in one request.
+    ///
+    /// The prompt is only processed (and billed) once; every choice is
+    /// returned in [`ChatResponse::choices`].
+    pub fn with_n(mut self, n: u32) -> Self {
+        self.n = Some(n);
+        self
+    }
+
     pub fn add_stop_sequence(mut self, seq: impl Into<String>) -> Self {
         self.stop.push(seq.into());
         self
@@ -565,6 +941,10 @@ impl ChatRequest {
         self.seed
     }
 
+    pub fn n(&self) -> Option<u32> {
+        self.n
+    }
+
     pub fn response_format(&self) -> Option<&ResponseFormat> {
         self.response_format.as_ref()
     }
@@ -621,6 +1001,11 @@ impl ChatRequest {
         &self.include
     }
 
+    /// Remove the messages, keeping the other options.
+    pub(crate) fn take_messages(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.messages)
+    }
+
     /// Create a ChatRequest from a list of messages with optional configuration
     pub fn from_messages(messages: Vec<Message>) -> Self {
         Self {
@@ -650,13 +1035,78 @@ impl ChatRequest {
 }
 
 impl SearchConfig {
+    /// Create a search configuration with automatic mode and the server's default sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Automatic web search returning up to 5 results.
     pub fn web() -> Self {
         Self {
-            mode: SearchMode::Auto,
-            sources: vec![SearchSource::Web],
+            sources: vec![SearchSource::web()],
             max_results: Some(5),
+            ..Default::default()
         }
     }
+
+    /// Automatic news search returning up to 5 results.
+    pub fn news() -> Self {
+        Self {
+            sources: vec![SearchSource::news()],
+            max_results: Some(5),
+            ..Default::default()
+        }
+    }
+
+    /// Automatic X search returning up to 5 results.
+    pub fn x() -> Self {
+        Self {
+            sources: vec![SearchSource::x()],
+            max_results: Some(5),
+            ..Default::default()
+        }
+    }
+
+    /// Set the search mode.
+    pub fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Add a source to search.
+    pub fn with_source(mut self, source: impl Into<SearchSource>) -> Self {
+        self.sources.push(source.into());
+        self
+    }
+
+    /// Replace the sources to search.
+    pub fn with_sources(mut self, sources: Vec<SearchSource>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Set the maximum number of search results.
+    pub fn with_max_results(mut self, max_results: u32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Only consider data published within this date range (both bounds optional).
+    pub fn with_date_range(
+        mut self,
+        from: Option<prost_types::Timestamp>,
+        to: Option<prost_types::Timestamp>,
+    ) -> Self {
+        self.from_date = from;
+        self.to_date = to;
+        self
+    }
+
+    /// Enable or disable returning citations.
+    pub fn with_return_citations(mut self, return_citations: bool) -> Self {
+        self.return_citations = return_citations;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -696,6 +1146,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_message_text_skips_images() {
+        let message = Message::User(MessageContent::MultiModal(vec![
+            ContentPart::Text("Compare".to_string()),
+            ContentPart::ImageUrl {
+                url: "https://example.com/a.jpg".to_string(),
+                detail: None,
+            },
+            ContentPart::Text("these".to_string()),
+        ]));
+        assert_eq!(message.text(), "Compare\nthese");
+        assert_eq!(Message::System("Be brief".to_string()).text(), "Be brief");
+    }
+
     #[test]
     fn test_from_messages() {
         let messages = vec![
@@ -775,6 +1239,42 @@ mod tests {
         assert_eq!(config.max_results, Some(5));
     }
 
+    #[test]
+    fn test_search_config_builder() {
+        let request = ChatRequest::new().user_message("News?").with_search(
+            SearchConfig::new()
+                .with_mode(SearchMode::On)
+                .with_source(NewsSource::new().with_country("FR").with_safe_search(false))
+                .with_source(XSource::new().with_included_handles(vec!["xai".to_string()]))
+                .with_date_range(
+                    Some(prost_types::Timestamp {
+                        seconds: 100,
+                        nanos: 0,
+                    }),
+                    None,
+                )
+                .with_return_citations(false),
+        );
+
+        let config = request.search_config().unwrap();
+        assert!(matches!(config.mode, SearchMode::On));
+        assert_eq!(config.sources.len(), 2);
+        assert!(matches!(
+            &config.sources[0],
+            SearchSource::News(news) if news.country.as_deref() == Some("FR") && !news.safe_search
+        ));
+        assert_eq!(config.from_date.map(|t| t.seconds), Some(100));
+        assert!(config.to_date.is_none());
+        assert!(!config.return_citations);
+    }
+
+    #[test]
+    fn test_search_source_defaults() {
+        assert!(matches!(SearchSource::web(), SearchSource::Web(w) if w.safe_search));
+        assert!(SearchConfig::default().return_citations);
+        assert!(SearchConfig::new().sources.is_empty());
+    }
+
     #[test]
     fn test_reasoning_effort() {
         let request = ChatRequest::new()
@@ -971,6 +1471,53 @@ mod tests {
         assert!(matches!(request.messages()[3], Message::Assistant(_)));
     }
 
+    #[test]
+    fn test_assistant_response_keeps_tool_calls() {
+        use crate::response::FinishReason;
+        use crate::tools::{FunctionCall, ToolCallKind, ToolCallStatusKind};
+
+        let response = ChatResponse {
+            finish_reason: FinishReason::ToolCalls,
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                call_type: ToolCallKind::ClientSideTool,
+                status: ToolCallStatusKind::Completed,
+                error_message: None,
+                function: FunctionCall {
+                    name: "add".to_string(),
+                    arguments: r#"{"a": 5, "b": 3}"#.to_string(),
+                },
+            }],
+            reasoning_content: Some("I should add.".to_string()),
+            ..ChatResponse::test_reply("req_1", "")
+        };
+
+        let request = ChatRequest::new()
+            .user_message("Add 5 and 3")
+            .assistant_response(&response)
+            .tool_result("call_1", "8");
+
+        match &request.messages()[1] {
+            Message::AssistantReply {
+                content,
+                reasoning_content,
+                tool_calls,
+            } => {
+                assert!(content.is_empty());
+                assert_eq!(reasoning_content.as_deref(), Some("I should add."));
+                assert_eq!(tool_calls.len(), 1);
+                assert_eq!(tool_calls[0].id, "call_1");
+            }
+            _ => panic!("Expected assistant reply"),
+        }
+    }
+
+    #[test]
+    fn test_add_message() {
+        let request = ChatRequest::new().add_message(Message::System("Be brief".to_string()));
+        assert!(matches!(request.messages()[0], Message::System(_)));
+    }
+
     #[test]
     fn test_tool_result_with_from_messages() {
         let messages = vec![
@@ -984,4 +1531,87 @@ mod tests {
         let request = ChatRequest::from_messages(messages);
         assert_eq!(request.messages().len(), 2);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chat_request_serde_round_trip() {
+        use crate::tools::{FunctionCall, FunctionTool, ToolCallKind, ToolCallStatusKind};
+        use serde_json::json;
+
+        let request = ChatRequest::new()
+            .system_message("Be brief")
+            .user_message("What's in this image?")
+            .user_with_image("Describe", "https://example.com/cat.png")
+            .add_message(Message::AssistantReply {
+                content: String::new(),
+                reasoning_content: Some("Need the weather".to_string()),
+                tool_calls: vec![ToolCall {
+                    id: "call_1".to_string(),
+                    call_type: ToolCallKind::ClientSideTool,
+                    status: ToolCallStatusKind::Completed,
+                    error_message: None,
+                    function: FunctionCall {
+                        name: "get_weather".to_string(),
+                        arguments: r#"{"location": "Paris"}"#.to_string(),
+                    },
+                }],
+            })
+            .tool_result("call_1", "22C")
+            .with_model("grok-2")
+            .with_temperature(0.5)
+            .with_n(2)
+            .add_tool(Tool::Function(FunctionTool::new("get_weather", "Weather")))
+            .with_json_schema(json!({"type": "object"}))
+            .with_search(SearchConfig::news().with_date_range(
+                Some(prost_types::Timestamp {
+                    seconds: 1_700_000_000,
+                    nanos: 0,
+                }),
+                None,
+            ))
+            .add_include_option(IncludeOption::InlineCitations);
+
+        let value = serde_json::to_value(&request).unwrap();
+        let restored: ChatRequest = serde_json::from_value(value.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&restored).unwrap(), value);
+        assert_eq!(restored.messages().len(), 5);
+        assert_eq!(restored.n(), Some(2));
+        assert_eq!(
+            restored.include_options(),
+            &[IncludeOption::InlineCitations]
+        );
+        assert_eq!(
+            restored.search_config().unwrap().from_date,
+            Some(prost_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_message_json_representation() {
+        use serde_json::json;
+
+        assert_eq!(
+            serde_json::to_value(Message::User("Hi".into())).unwrap(),
+            json!({"user": "Hi"})
+        );
+        assert_eq!(
+            serde_json::to_value(Message::Tool {
+                tool_call_id: "call_1".to_string(),
+                content: "22C".to_string(),
+            })
+            .unwrap(),
+            json!({"tool": {"tool_call_id": "call_1", "content": "22C"}})
+        );
+
+        // Missing fields fall back to their defaults
+        let request: ChatRequest =
+            serde_json::from_value(json!({"messages": [{"system": "Be brief"}]})).unwrap();
+        assert_eq!(request.messages().len(), 1);
+        assert!(request.model().is_none());
+    }
 }
@@ -3,13 +3,21 @@
 //! This module contains types for both streaming and non-streaming responses,
 //! including token usage, finish reasons, log probabilities, and tool calls.
 
+use crate::proto;
+use crate::request::Message;
 use crate::tools::ToolCall;
 
 /// Response from a chat completion request.
 ///
 /// Contains the generated content, metadata, token usage, and optional extras
 /// like tool calls, citations, and reasoning traces.
+///
+/// The top-level `content`, `finish_reason`, `tool_calls`, `reasoning_content`
+/// and `logprobs` describe the first choice. When the request asked for several
+/// choices with [`ChatRequest::with_n`](crate::ChatRequest::with_n), every one
+/// of them is available in [`choices`](Self::choices).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChatResponse {
     /// Unique request identifier.
     pub request_id: String,
@@ -33,10 +41,78 @@ pub struct ChatResponse {
     pub created: Option<i64>,
     /// Backend configuration fingerprint.
     pub system_fingerprint: Option<String>,
+    /// All completion choices, ordered by index.
+    pub choices: Vec<Choice>,
+}
+
+impl ChatResponse {
+    /// Convert this response into an assistant message for the conversation history.
+    ///
+    /// The message keeps the content, reasoning trace and tool calls, so the
+    /// history can be replayed faithfully in a follow-up request.
+    pub fn to_message(&self) -> Message {
+        Message::AssistantReply {
+            content: self.content.clone(),
+            reasoning_content: self.reasoning_content.clone(),
+            tool_calls: self.tool_calls.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl ChatResponse {
+    /// A finished reply with `content` and no usage, for tests. Override
+    /// the other fields with struct update syntax.
+    pub(crate) fn test_reply(request_id: &str, content: &str) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            content: content.to_string(),
+            finish_reason: FinishReason::Stop,
+            model: "grok-2".to_string(),
+            usage: TokenUsage::default(),
+            citations: vec![],
+            tool_calls: vec![],
+            reasoning_content: None,
+            logprobs: None,
+            created: None,
+            system_fingerprint: None,
+            choices: vec![],
+        }
+    }
+}
+
+/// A single completion choice of a chat response.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Choice {
+    /// Index of this choice.
+    pub index: u32,
+    /// Generated text content.
+    pub content: String,
+    /// Reason why generation stopped for this choice.
+    pub finish_reason: FinishReason,
+    /// Tool calls made by the model in this choice.
+    pub tool_calls: Vec<ToolCall>,
+    /// Reasoning trace the model produced before this answer.
+    pub reasoning_content: Option<String>,
+    /// Log probabilities for the generated tokens (if requested).
+    pub logprobs: Option<LogProbs>,
+}
+
+impl Choice {
+    /// Convert this choice into an assistant message for the conversation history.
+    pub fn to_message(&self) -> Message {
+        Message::AssistantReply {
+            content: self.content.clone(),
+            reasoning_content: self.reasoning_content.clone(),
+            tool_calls: self.tool_calls.clone(),
+        }
+    }
 }
 
 /// Log probabilities for all tokens in a response.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LogProbs {
     /// Log probability information for each token.
     pub content: Vec<LogProb>,
@@ -44,6 +120,7 @@ pub struct LogProbs {
 
 /// Log probability information for a single token.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LogProb {
     /// The token string.
     pub token: String,
@@ -57,6 +134,7 @@ pub struct LogProb {
 
 /// Alternative token with its log probability.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TopLogProb {
     /// The alternative token string.
     pub token: String,
@@ -69,8 +147,23 @@ pub struct TopLogProb {
 /// A chunk of a streaming chat response.
 ///
 /// Contains incremental content as it's generated in real-time.
+///
+/// The top-level delta fields belong to the output at `index`. When several
+/// choices are generated, a chunk may carry deltas for more than one of them;
+/// all are listed in [`choices`](Self::choices).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChatChunk {
+    /// Unique request identifier.
+    pub request_id: String,
+    /// Model that generated the response.
+    pub model: String,
+    /// Timestamp when the response was created.
+    pub created: Option<i64>,
+    /// Backend configuration fingerprint.
+    pub system_fingerprint: Option<String>,
+    /// Index of the choice the top-level delta belongs to.
+    pub index: u32,
     /// Incremental content delta (new text generated).
     pub delta: String,
     /// Finish reason if this is the last chunk.
@@ -85,21 +178,87 @@ pub struct ChatChunk {
     pub logprobs: Option<LogProbs>,
     /// Citations used by the model (typically only in the last chunk).
     pub citations: Vec<String>,
+    /// Deltas for every choice in this chunk, ordered by index.
+    pub choices: Vec<ChoiceDelta>,
+}
+
+/// Incremental content for a single choice in a streaming chunk.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChoiceDelta {
+    /// Index of the choice this delta belongs to.
+    pub index: u32,
+    /// Incremental content delta.
+    pub delta: String,
+    /// Finish reason if this choice is complete.
+    pub finish_reason: Option<FinishReason>,
+    /// Reasoning trace delta.
+    pub reasoning_delta: Option<String>,
+    /// Tool calls emitted for this choice.
+    pub tool_calls: Vec<ToolCall>,
+    /// Log probabilities for tokens in this delta.
+    pub logprobs: Option<LogProbs>,
 }
 
 /// Token usage statistics for a completion.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TokenUsage {
     /// Number of tokens in the prompt.
     pub prompt_tokens: u32,
     /// Number of tokens generated in the completion.
     pub completion_tokens: u32,
-    /// Total tokens used (prompt + completion).
+    /// Total tokens used (prompt, completion and reasoning).
     pub total_tokens: u32,
+    /// Prompt tokens served from the prompt cache, billed at the cached price.
+    ///
+    /// Included in `prompt_tokens`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cached_prompt_tokens: u32,
+    /// Prompt tokens spent on images. Included in `prompt_tokens`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub prompt_image_tokens: u32,
+    /// Tokens spent on reasoning, billed as completion tokens.
+    ///
+    /// Not included in `completion_tokens`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reasoning_tokens: u32,
+    /// Number of search sources used to answer.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub num_sources_used: u32,
+}
+
+impl std::ops::AddAssign<&TokenUsage> for TokenUsage {
+    fn add_assign(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cached_prompt_tokens += other.cached_prompt_tokens;
+        self.prompt_image_tokens += other.prompt_image_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.num_sources_used += other.num_sources_used;
+    }
+}
+
+impl From<proto::SamplingUsage> for TokenUsage {
+    fn from(usage: proto::SamplingUsage) -> Self {
+        let count = |value: i32| u32::try_from(value).unwrap_or_default();
+        Self {
+            prompt_tokens: count(usage.prompt_tokens),
+            completion_tokens: count(usage.completion_tokens),
+            total_tokens: count(usage.total_tokens),
+            cached_prompt_tokens: count(usage.cached_prompt_text_tokens),
+            prompt_image_tokens: count(usage.prompt_image_tokens),
+            reasoning_tokens: count(usage.reasoning_tokens),
+            num_sources_used: count(usage.num_sources_used),
+        }
+    }
 }
 
 /// Reason why the model stopped generating.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FinishReason {
     /// Model reached a natural stopping point.
     Stop,
@@ -140,6 +299,26 @@ mod tests {
         assert_eq!(usage.total_tokens, 0);
     }
 
+    #[test]
+    fn test_token_usage_add_assign() {
+        let mut usage = TokenUsage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+            ..Default::default()
+        };
+        usage += &TokenUsage {
+            prompt_tokens: 20,
+            completion_tokens: 1,
+            total_tokens: 21,
+            ..Default::default()
+        };
+
+        assert_eq!(usage.prompt_tokens, 30);
+        assert_eq!(usage.completion_tokens, 6);
+        assert_eq!(usage.total_tokens, 36);
+    }
+
     #[test]
     fn test_finish_reason_display() {
         assert_eq!(FinishReason::Stop.to_string(), "stop");
@@ -164,6 +343,7 @@ mod tests {
                 prompt_tokens: 10,
                 completion_tokens: 5,
                 total_tokens: 15,
+                ..Default::default()
             },
             citations: vec!["https://example.com".to_string()],
             tool_calls: vec![],
@@ -171,6 +351,7 @@ mod tests {
             logprobs: None,
             created: Some(1234567890),
             system_fingerprint: Some("fp_abc123".to_string()),
+            choices: vec![],
         };
 
         assert_eq!(response.request_id, "req_123");
@@ -182,17 +363,24 @@ mod tests {
     #[test]
     fn test_chat_chunk() {
         let chunk = ChatChunk {
+            request_id: "req_123".to_string(),
+            model: "grok-2".to_string(),
+            created: None,
+            system_fingerprint: None,
+            index: 0,
             delta: "Hello".to_string(),
             finish_reason: None,
             cumulative_usage: TokenUsage {
                 prompt_tokens: 5,
                 completion_tokens: 1,
                 total_tokens: 6,
+                ..Default::default()
             },
             reasoning_delta: None,
             tool_calls: vec![],
             logprobs: None,
             citations: vec![],
+            choices: vec![],
         };
 
         assert_eq!(chunk.delta, "Hello");
@@ -233,17 +421,8 @@ mod tests {
     #[test]
     fn test_response_with_reasoning() {
         let response = ChatResponse {
-            request_id: "req_456".to_string(),
-            content: "The answer is 42".to_string(),
-            finish_reason: FinishReason::Stop,
-            model: "grok-2".to_string(),
-            usage: TokenUsage::default(),
-            citations: vec![],
-            tool_calls: vec![],
             reasoning_content: Some("First, I considered...".to_string()),
-            logprobs: None,
-            created: None,
-            system_fingerprint: None,
+            ..ChatResponse::test_reply("req_456", "The answer is 42")
         };
 
         assert!(response.reasoning_content.is_some());
@@ -252,4 +431,79 @@ mod tests {
             "First, I considered..."
         );
     }
+
+    #[test]
+    fn test_choice_to_message() {
+        let choice = Choice {
+            index: 1,
+            content: "Second answer".to_string(),
+            finish_reason: FinishReason::Stop,
+            tool_calls: vec![],
+            reasoning_content: Some("Thinking...".to_string()),
+            logprobs: None,
+        };
+
+        match choice.to_message() {
+            Message::AssistantReply {
+                content,
+                reasoning_content,
+                tool_calls,
+            } => {
+                assert_eq!(content, "Second answer");
+                assert_eq!(reasoning_content.as_deref(), Some("Thinking..."));
+                assert!(tool_calls.is_empty());
+            }
+            other => panic!("Expected assistant reply, got {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chat_response_serde_round_trip() {
+        let choice = Choice {
+            index: 0,
+            content: "Hello".to_string(),
+            finish_reason: FinishReason::Error("Time limit reached".to_string()),
+            tool_calls: vec![],
+            reasoning_content: None,
+            logprobs: Some(LogProbs {
+                content: vec![LogProb {
+                    token: "Hello".to_string(),
+                    logprob: -0.25,
+                    bytes: b"Hello".to_vec(),
+                    top_logprobs: vec![],
+                }],
+            }),
+        };
+        let response = ChatResponse {
+            request_id: "req_1".to_string(),
+            content: choice.content.clone(),
+            finish_reason: choice.finish_reason.clone(),
+            model: "grok-2".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 3,
+                completion_tokens: 1,
+                total_tokens: 4,
+                ..Default::default()
+            },
+            citations: vec!["https://x.ai".to_string()],
+            tool_calls: vec![],
+            reasoning_content: None,
+            logprobs: choice.logprobs.clone(),
+            created: Some(1_700_000_000),
+            system_fingerprint: None,
+            choices: vec![choice],
+        };
+
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            value["finish_reason"],
+            serde_json::json!({"error": "Time limit reached"})
+        );
+
+        let restored: ChatResponse = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), value);
+        assert_eq!(restored.choices.len(), 1);
+        assert_eq!(restored.usage.total_tokens, 4);
+    }
 }
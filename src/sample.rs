@@ -10,6 +10,7 @@ use crate::proto;
 
 /// Request for text sampling
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SampleRequest {
     /// Text prompts to sample from
     pub prompts: Vec<String>,
@@ -86,6 +87,7 @@ impl SampleRequest {
 
 /// Response from sampling
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SampleResponse {
     /// Request ID
     pub id: String,
@@ -99,6 +101,7 @@ pub struct SampleResponse {
 
 /// A single completion choice
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SampleChoice {
     /// Index of this choice
     pub index: i32,
@@ -0,0 +1,65 @@
+//! Serde adapters for protobuf types used in the public API.
+
+use crate::proto::IncludeOption;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// JSON form of a `prost_types::Timestamp`.
+#[derive(Serialize, Deserialize)]
+struct TimestampRepr {
+    seconds: i64,
+    #[serde(default)]
+    nanos: i32,
+}
+
+/// `Option<prost_types::Timestamp>` as `{"seconds": ..., "nanos": ...}` or `null`.
+pub(crate) mod option_timestamp {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<prost_types::Timestamp>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|ts| TimestampRepr {
+                seconds: ts.seconds,
+                nanos: ts.nanos,
+            })
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<prost_types::Timestamp>, D::Error> {
+        let repr = Option::<TimestampRepr>::deserialize(deserializer)?;
+        Ok(repr.map(|repr| prost_types::Timestamp {
+            seconds: repr.seconds,
+            nanos: repr.nanos,
+        }))
+    }
+}
+
+/// `Vec<IncludeOption>` as a list of the options' proto names.
+pub(crate) mod include_options {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &[IncludeOption],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(|option| option.as_str_name()))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<IncludeOption>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|name| {
+                IncludeOption::from_str_name(name).ok_or_else(|| {
+                    serde::de::Error::custom(format!("unknown include option: {name}"))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,452 @@
+//! Accumulating streamed chat chunks into a complete response.
+//!
+//! [`ChatStreamAccumulator`] folds the [`ChatChunk`]s returned by
+//! [`GrokClient::stream_chat`](crate::GrokClient::stream_chat) into the same
+//! [`ChatResponse`] that [`GrokClient::complete_chat`](crate::GrokClient::complete_chat)
+//! would have returned, while exposing the partial state as it grows.
+//! [`ChatStreamExt::collect_response`] does the same in one call when the
+//! intermediate state is not needed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{ChatRequest, ChatStreamAccumulator, GrokClient};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//! let mut stream = client
+//!     .stream_chat(ChatRequest::new().user_message("Write a haiku"))
+//!     .await?;
+//!
+//! let mut accumulator = ChatStreamAccumulator::new();
+//! while let Some(chunk) = stream.next().await {
+//!     let chunk = chunk?;
+//!     print!("{}", chunk.delta);
+//!     accumulator.push(&chunk);
+//! }
+//!
+//! let response = accumulator.into_response();
+//! println!("\n{} tokens", response.usage.total_tokens);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::Result,
+    response::{ChatChunk, ChatResponse, Choice, ChoiceDelta, FinishReason, LogProbs, TokenUsage},
+    tools::ToolCall,
+};
+use std::future::Future;
+use tokio_stream::{Stream, StreamExt};
+
+/// Folds streamed [`ChatChunk`]s into a [`ChatResponse`].
+///
+/// Text and reasoning deltas are concatenated per choice, tool call deltas are
+/// merged by id, the latest usage is kept and citations are collected.
+#[derive(Clone, Debug, Default)]
+pub struct ChatStreamAccumulator {
+    request_id: String,
+    model: String,
+    created: Option<i64>,
+    system_fingerprint: Option<String>,
+    usage: TokenUsage,
+    citations: Vec<String>,
+    choices: Vec<Choice>,
+}
+
+impl ChatStreamAccumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk to the accumulated state.
+    pub fn push(&mut self, chunk: &ChatChunk) {
+        if !chunk.request_id.is_empty() {
+            self.request_id.clone_from(&chunk.request_id);
+        }
+        if !chunk.model.is_empty() {
+            self.model.clone_from(&chunk.model);
+        }
+        if chunk.created.is_some() {
+            self.created = chunk.created;
+        }
+        if chunk.system_fingerprint.is_some() {
+            self.system_fingerprint
+                .clone_from(&chunk.system_fingerprint);
+        }
+
+        // Usage is cumulative, so the latest non-empty value wins
+        if chunk.cumulative_usage.total_tokens > 0 {
+            self.usage = chunk.cumulative_usage.clone();
+        }
+
+        for citation in &chunk.citations {
+            if !self.citations.contains(citation) {
+                self.citations.push(citation.clone());
+            }
+        }
+
+        if chunk.choices.is_empty() {
+            // Chunks built by hand often only fill in the top-level fields
+            self.push_delta(&ChoiceDelta {
+                index: chunk.index,
+                delta: chunk.delta.clone(),
+                finish_reason: chunk.finish_reason.clone(),
+                reasoning_delta: chunk.reasoning_delta.clone(),
+                tool_calls: chunk.tool_calls.clone(),
+                logprobs: chunk.logprobs.clone(),
+            });
+        } else {
+            for delta in &chunk.choices {
+                self.push_delta(delta);
+            }
+        }
+    }
+
+    fn push_delta(&mut self, delta: &ChoiceDelta) {
+        let choice = self.choice_mut(delta.index);
+
+        choice.content.push_str(&delta.delta);
+
+        if let Some(reasoning) = &delta.reasoning_delta {
+            choice
+                .reasoning_content
+                .get_or_insert_with(String::new)
+                .push_str(reasoning);
+        }
+
+        for tool_call in &delta.tool_calls {
+            merge_tool_call(&mut choice.tool_calls, tool_call);
+        }
+
+        if let Some(logprobs) = &delta.logprobs {
+            choice
+                .logprobs
+                .get_or_insert_with(|| LogProbs {
+                    content: Vec::new(),
+                })
+                .content
+                .extend(logprobs.content.iter().cloned());
+        }
+
+        if let Some(finish_reason) = &delta.finish_reason {
+            choice.finish_reason = finish_reason.clone();
+        }
+    }
+
+    /// Choice with the given index, created if this is its first delta.
+    fn choice_mut(&mut self, index: u32) -> &mut Choice {
+        let position = match self.choices.binary_search_by_key(&index, |c| c.index) {
+            Ok(position) => position,
+            Err(position) => {
+                self.choices.insert(
+                    position,
+                    Choice {
+                        index,
+                        content: String::new(),
+                        finish_reason: FinishReason::Unknown,
+                        tool_calls: Vec::new(),
+                        reasoning_content: None,
+                        logprobs: None,
+                    },
+                );
+                position
+            }
+        };
+        &mut self.choices[position]
+    }
+
+    /// Text accumulated so far for the first choice.
+    pub fn content(&self) -> &str {
+        self.choices.first().map_or("", |c| c.content.as_str())
+    }
+
+    /// Reasoning trace accumulated so far for the first choice.
+    pub fn reasoning_content(&self) -> Option<&str> {
+        self.choices
+            .first()
+            .and_then(|c| c.reasoning_content.as_deref())
+    }
+
+    /// Tool calls accumulated so far for the first choice.
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        self.choices
+            .first()
+            .map(|c| c.tool_calls.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Finish reason of the first choice, once the server has sent it.
+    pub fn finish_reason(&self) -> Option<&FinishReason> {
+        self.choices
+            .first()
+            .map(|c| &c.finish_reason)
+            .filter(|reason| !matches!(reason, FinishReason::Unknown))
+    }
+
+    /// Partial state of every choice seen so far, ordered by index.
+    pub fn choices(&self) -> &[Choice] {
+        &self.choices
+    }
+
+    /// Latest cumulative token usage.
+    pub fn usage(&self) -> &TokenUsage {
+        &self.usage
+    }
+
+    /// Citations collected so far, without duplicates.
+    pub fn citations(&self) -> &[String] {
+        &self.citations
+    }
+
+    /// Build the final response.
+    ///
+    /// The top-level fields mirror the first choice, as in
+    /// [`GrokClient::complete_chat`](crate::GrokClient::complete_chat).
+    pub fn into_response(self) -> ChatResponse {
+        let first = self.choices.first().cloned();
+
+        ChatResponse {
+            request_id: self.request_id,
+            content: first
+                .as_ref()
+                .map(|c| c.content.clone())
+                .unwrap_or_default(),
+            finish_reason: first
+                .as_ref()
+                .map_or(FinishReason::Unknown, |c| c.finish_reason.clone()),
+            model: self.model,
+            usage: self.usage,
+            citations: self.citations,
+            tool_calls: first
+                .as_ref()
+                .map(|c| c.tool_calls.clone())
+                .unwrap_or_default(),
+            reasoning_content: first.as_ref().and_then(|c| c.reasoning_content.clone()),
+            logprobs: first.and_then(|c| c.logprobs),
+            created: self.created,
+            system_fingerprint: self.system_fingerprint,
+            choices: self.choices,
+        }
+    }
+}
+
+/// Merge a streamed tool call into the calls accumulated so far.
+///
+/// A call with a known id (or no id, continuing the previous call) extends the
+/// existing call's arguments; any other call is appended.
+fn merge_tool_call(tool_calls: &mut Vec<ToolCall>, delta: &ToolCall) {
+    let existing = if delta.id.is_empty() {
+        tool_calls.last_mut()
+    } else {
+        tool_calls.iter_mut().find(|tc| tc.id == delta.id)
+    };
+
+    let Some(existing) = existing else {
+        tool_calls.push(delta.clone());
+        return;
+    };
+
+    if existing.function.name.is_empty() {
+        existing.function.name.clone_from(&delta.function.name);
+    }
+
+    // Status updates resend the full arguments; fragments are appended
+    if delta
+        .function
+        .arguments
+        .starts_with(&existing.function.arguments)
+    {
+        existing
+            .function
+            .arguments
+            .clone_from(&delta.function.arguments);
+    } else {
+        existing
+            .function
+            .arguments
+            .push_str(&delta.function.arguments);
+    }
+
+    existing.status = delta.status.clone();
+    if delta.error_message.is_some() {
+        existing.error_message.clone_from(&delta.error_message);
+    }
+}
+
+/// Extension trait to collect a chat stream into a [`ChatResponse`].
+pub trait ChatStreamExt: Stream<Item = Result<ChatChunk>> {
+    /// Consume the stream and return the accumulated response.
+    ///
+    /// Fails with the first error the stream yields.
+    fn collect_response(self) -> impl Future<Output = Result<ChatResponse>> + Send
+    where
+        Self: Sized + Send,
+    {
+        async move {
+            let mut stream = std::pin::pin!(self);
+            let mut accumulator = ChatStreamAccumulator::new();
+            while let Some(chunk) = stream.next().await {
+                accumulator.push(&chunk?);
+            }
+            Ok(accumulator.into_response())
+        }
+    }
+}
+
+impl<S> ChatStreamExt for S where S: Stream<Item = Result<ChatChunk>> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{FunctionCall, ToolCallKind, ToolCallStatusKind};
+
+    fn chunk(delta: &str) -> ChatChunk {
+        ChatChunk {
+            request_id: "req_1".to_string(),
+            model: "grok-2".to_string(),
+            created: Some(1_700_000_000),
+            system_fingerprint: None,
+            index: 0,
+            delta: delta.to_string(),
+            finish_reason: None,
+            cumulative_usage: TokenUsage::default(),
+            reasoning_delta: None,
+            tool_calls: vec![],
+            logprobs: None,
+            citations: vec![],
+            choices: vec![],
+        }
+    }
+
+    fn tool_call(id: &str, name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: ToolCallKind::ClientSideTool,
+            status: ToolCallStatusKind::InProgress,
+            error_message: None,
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_accumulates_text_reasoning_and_usage() {
+        let mut accumulator = ChatStreamAccumulator::new();
+
+        let mut first = chunk("Hel");
+        first.reasoning_delta = Some("Greet ".to_string());
+        accumulator.push(&first);
+        assert_eq!(accumulator.content(), "Hel");
+        assert!(accumulator.finish_reason().is_none());
+
+        let mut second = chunk("lo");
+        second.reasoning_delta = Some("the user".to_string());
+        accumulator.push(&second);
+
+        let mut last = chunk("!");
+        last.finish_reason = Some(FinishReason::Stop);
+        last.cumulative_usage = TokenUsage {
+            prompt_tokens: 4,
+            completion_tokens: 3,
+            total_tokens: 7,
+            ..Default::default()
+        };
+        last.citations = vec!["https://x.ai".to_string()];
+        accumulator.push(&last);
+
+        let response = accumulator.into_response();
+        assert_eq!(response.request_id, "req_1");
+        assert_eq!(response.content, "Hello!");
+        assert_eq!(
+            response.reasoning_content.as_deref(),
+            Some("Greet the user")
+        );
+        assert!(matches!(response.finish_reason, FinishReason::Stop));
+        assert_eq!(response.usage.total_tokens, 7);
+        assert_eq!(response.citations, vec!["https://x.ai".to_string()]);
+        assert_eq!(response.created, Some(1_700_000_000));
+        assert_eq!(response.choices.len(), 1);
+    }
+
+    #[test]
+    fn test_merges_tool_call_fragments() {
+        let mut accumulator = ChatStreamAccumulator::new();
+
+        let mut first = chunk("");
+        first.tool_calls = vec![tool_call("call_1", "get_weather", r#"{"loca"#)];
+        accumulator.push(&first);
+
+        let mut second = chunk("");
+        second.tool_calls = vec![
+            tool_call("call_1", "", r#"tion": "Paris"}"#),
+            tool_call("call_2", "get_time", "{}"),
+        ];
+        accumulator.push(&second);
+
+        // Status update resending the full arguments
+        let mut third = chunk("");
+        let mut completed = tool_call("call_1", "get_weather", r#"{"location": "Paris"}"#);
+        completed.status = ToolCallStatusKind::Completed;
+        third.tool_calls = vec![completed];
+        accumulator.push(&third);
+
+        let tool_calls = accumulator.tool_calls();
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"location": "Paris"}"#);
+        assert!(matches!(
+            tool_calls[0].status,
+            ToolCallStatusKind::Completed
+        ));
+        assert_eq!(tool_calls[1].function.name, "get_time");
+    }
+
+    #[test]
+    fn test_accumulates_choices_by_index() {
+        let delta = |index: u32, text: &str| ChoiceDelta {
+            index,
+            delta: text.to_string(),
+            ..Default::default()
+        };
+
+        let mut accumulator = ChatStreamAccumulator::new();
+        let mut first = chunk("");
+        first.choices = vec![delta(1, "B"), delta(0, "A")];
+        accumulator.push(&first);
+        let mut second = chunk("");
+        second.choices = vec![delta(0, "a"), delta(1, "b")];
+        accumulator.push(&second);
+
+        let response = accumulator.into_response();
+        assert_eq!(response.content, "Aa");
+        assert_eq!(response.choices.len(), 2);
+        assert_eq!(response.choices[0].index, 0);
+        assert_eq!(response.choices[1].content, "Bb");
+    }
+
+    #[tokio::test]
+    async fn test_collect_response() {
+        let mut last = chunk(" world");
+        last.finish_reason = Some(FinishReason::Stop);
+        let stream = tokio_stream::iter(vec![Ok(chunk("Hello")), Ok(last)]);
+
+        let response = stream.collect_response().await.unwrap();
+        assert_eq!(response.content, "Hello world");
+        assert!(matches!(response.finish_reason, FinishReason::Stop));
+    }
+
+    #[tokio::test]
+    async fn test_collect_response_returns_stream_error() {
+        let stream = tokio_stream::iter(vec![
+            Ok(chunk("Hello")),
+            Err(crate::GrokError::InvalidRequest("boom".to_string())),
+        ]);
+
+        assert!(stream.collect_response().await.is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Typed structured output.
+//!
+//! [`GrokClient::complete_chat_typed`] asks the model to answer with JSON that
+//! matches the schema of a Rust type and deserializes the reply into it. The
+//! schema comes from the [`StructuredOutput`] trait, which is implemented for
+//! every [`schemars::JsonSchema`] type when the `schemars` feature is enabled,
+//! so the schema always follows the struct definition.
+//!
+//! # Examples
+//!
+#![cfg_attr(feature = "schemars", doc = "```no_run")]
+#![cfg_attr(not(feature = "schemars"), doc = "```ignore")]
+//! use serde::Deserialize;
+//! use xai_grpc_client::{ChatRequest, GrokClient};
+//!
+//! #[derive(Deserialize, schemars::JsonSchema)]
+//! struct Weather {
+//!     city: String,
+//!     temperature_celsius: f64,
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrokClient::from_env().await?;
+//!
+//! let request = ChatRequest::new().user_message("Weather in Paris today, as JSON");
+//! let weather = client.complete_chat_typed::<Weather>(request).await?;
+//!
+//! println!("{}: {}°C", weather.value.city, weather.value.temperature_celsius);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::GrokClient,
+    error::{GrokError, Result},
+    request::ChatRequest,
+    response::{ChatResponse, TokenUsage},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A type the model can be asked to produce as structured output.
+///
+/// Implemented automatically for [`schemars::JsonSchema`] types with the
+/// `schemars` feature; implement it by hand to supply a schema yourself.
+pub trait StructuredOutput: DeserializeOwned {
+    /// JSON schema describing the serialized form of the type.
+    fn json_schema() -> Value;
+}
+
+#[cfg(feature = "schemars")]
+impl<T> StructuredOutput for T
+where
+    T: DeserializeOwned + schemars::JsonSchema,
+{
+    fn json_schema() -> Value {
+        schemars::schema_for!(T).to_value()
+    }
+}
+
+/// Make a JSON schema strict: every object schema rejects unknown properties
+/// and requires all of its properties.
+///
+/// Optional fields stay optional by allowing `null` in their own schema, which
+/// is how `schemars` describes `Option<T>`.
+pub fn strict_schema(mut schema: Value) -> Value {
+    make_strict(&mut schema);
+    schema
+}
+
+/// Keywords whose value is a map from names to schemas.
+const SCHEMA_MAPS: [&str; 3] = ["properties", "$defs", "definitions"];
+
+/// Keywords whose value is a schema, or an array of schemas.
+const SCHEMA_KEYWORDS: [&str; 7] = [
+    "items",
+    "prefixItems",
+    "additionalProperties",
+    "anyOf",
+    "oneOf",
+    "allOf",
+    "not",
+];
+
+fn make_strict(schema: &mut Value) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+
+    if let Some(Value::Object(properties)) = object.get("properties") {
+        let required = properties.keys().cloned().map(Value::String).collect();
+        object.insert("required".to_string(), Value::Array(required));
+        object.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+
+    // Only recurse into schema positions: the keys of `properties` are
+    // property names, which may well be `properties` or `items` themselves
+    for keyword in SCHEMA_MAPS {
+        if let Some(Value::Object(schemas)) = object.get_mut(keyword) {
+            schemas.values_mut().for_each(make_strict);
+        }
+    }
+    for keyword in SCHEMA_KEYWORDS {
+        match object.get_mut(keyword) {
+            Some(Value::Array(schemas)) => schemas.iter_mut().for_each(make_strict),
+            Some(schema) => make_strict(schema),
+            None => {}
+        }
+    }
+}
+
+/// A chat response together with its content deserialized into `T`.
+#[derive(Clone, Debug)]
+pub struct StructuredResponse<T> {
+    /// The deserialized reply.
+    pub value: T,
+    /// The response the value was parsed from.
+    pub response: ChatResponse,
+    /// Token usage summed over every request made (two if the model was re-prompted).
+    pub usage: TokenUsage,
+}
+
+/// Deserialize the content of `response` into `T`.
+fn parse_response<T: DeserializeOwned>(response: &ChatResponse) -> Result<T> {
+    serde_json::from_str(response.content.trim()).map_err(|error| GrokError::StructuredOutput {
+        message: error.to_string(),
+        raw: response.content.clone(),
+    })
+}
+
+impl ChatRequest {
+    /// Ask for JSON output matching the schema of `T`, in strict mode.
+    pub fn with_output_type<T: StructuredOutput>(self) -> Self {
+        self.with_json_schema(strict_schema(T::json_schema()))
+    }
+}
+
+impl GrokClient {
+    /// Complete a chat request and deserialize the reply into `T`.
+    ///
+    /// The request's response format is replaced by the strict JSON schema of
+    /// `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::StructuredOutput`] with the raw reply if it does not
+    /// deserialize into `T`, plus any error from [`complete_chat`](Self::complete_chat).
+    pub async fn complete_chat_typed<T: StructuredOutput>(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StructuredResponse<T>> {
+        let response = self.complete_chat(request.with_output_type::<T>()).await?;
+        let value = parse_response(&response)?;
+        let usage = response.usage.clone();
+
+        Ok(StructuredResponse {
+            value,
+            response,
+            usage,
+        })
+    }
+
+    /// Like [`complete_chat_typed`](Self::complete_chat_typed), but re-prompts
+    /// the model once with the validation error if its first reply does not
+    /// deserialize into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::StructuredOutput`] with the raw second reply if it
+    /// still does not match, plus any error from [`complete_chat`](Self::complete_chat).
+    pub async fn complete_chat_typed_with_reprompt<T: StructuredOutput>(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StructuredResponse<T>> {
+        let request = request.with_output_type::<T>();
+        let response = self.complete_chat(request.clone()).await?;
+
+        let message = match parse_response(&response) {
+            Ok(value) => {
+                let usage = response.usage.clone();
+                return Ok(StructuredResponse {
+                    value,
+                    response,
+                    usage,
+                });
+            }
+            Err(GrokError::StructuredOutput { message, .. }) => message,
+            Err(error) => return Err(error),
+        };
+
+        let mut usage = response.usage.clone();
+        let retry = request.assistant_response(&response).user_message(format!(
+            "Your reply did not match the required JSON schema: {message}. \
+             Reply again with only JSON that matches the schema."
+        ));
+
+        let response = self.complete_chat(retry).await?;
+        usage += &response.usage;
+        let value = parse_response(&response)?;
+
+        Ok(StructuredResponse {
+            value,
+            response,
+            usage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Weather {
+        city: String,
+        temperature: f64,
+    }
+
+    impl StructuredOutput for Weather {
+        fn json_schema() -> Value {
+            json!({
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "temperature": { "type": "number" }
+                },
+                "required": ["city"]
+            })
+        }
+    }
+
+    fn response(content: &str) -> ChatResponse {
+        ChatResponse {
+            usage: TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+                ..Default::default()
+            },
+            ..ChatResponse::test_reply("req_1", content)
+        }
+    }
+
+    #[test]
+    fn test_strict_schema_closes_nested_objects() {
+        let schema = strict_schema(json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "address": {
+                    "type": "object",
+                    "properties": { "street": { "type": ["string", "null"] } }
+                }
+            },
+            "$defs": {
+                "Tag": { "type": "object", "properties": { "label": { "type": "string" } } }
+            }
+        }));
+
+        let required = schema["required"].as_array().unwrap();
+        assert_eq!(required.len(), 2);
+        assert!(required.contains(&json!("name")));
+        assert!(required.contains(&json!("address")));
+        assert_eq!(schema["additionalProperties"], json!(false));
+        let address = &schema["properties"]["address"];
+        assert_eq!(address["required"], json!(["street"]));
+        assert_eq!(address["additionalProperties"], json!(false));
+        assert_eq!(schema["$defs"]["Tag"]["additionalProperties"], json!(false));
+    }
+
+    #[test]
+    fn test_strict_schema_with_property_named_properties() {
+        let schema = strict_schema(json!({
+            "type": "object",
+            "properties": {
+                "properties": {
+                    "type": "object",
+                    "properties": { "color": { "type": "string" } }
+                },
+                "items": {
+                    "type": "array",
+                    "items": { "type": "object", "properties": { "id": { "type": "integer" } } }
+                }
+            }
+        }));
+
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties.len(), 2);
+        assert!(!properties.contains_key("required"));
+        assert!(!properties.contains_key("additionalProperties"));
+
+        let nested = &properties["properties"];
+        assert_eq!(nested["required"], json!(["color"]));
+        assert_eq!(nested["additionalProperties"], json!(false));
+        assert_eq!(nested["properties"].as_object().unwrap().len(), 1);
+        assert_eq!(properties["items"]["items"]["required"], json!(["id"]));
+    }
+
+    #[test]
+    fn test_with_output_type_sets_strict_schema() {
+        let request = ChatRequest::new()
+            .user_message("Weather?")
+            .with_output_type::<Weather>();
+
+        match request.response_format() {
+            Some(crate::request::ResponseFormat::JsonSchema(schema)) => {
+                assert_eq!(schema["required"].as_array().unwrap().len(), 2);
+                assert_eq!(schema["additionalProperties"], json!(false));
+            }
+            other => panic!("Expected JSON schema format, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let weather: Weather =
+            parse_response(&response(r#" {"city": "Paris", "temperature": 21.5} "#)).unwrap();
+        assert_eq!(
+            weather,
+            Weather {
+                city: "Paris".to_string(),
+                temperature: 21.5,
+            }
+        );
+
+        match parse_response::<Weather>(&response(r#"{"city": "Paris"}"#)) {
+            Err(GrokError::StructuredOutput { message, raw }) => {
+                assert!(message.contains("temperature"));
+                assert_eq!(raw, r#"{"city": "Paris"}"#);
+            }
+            other => panic!("Expected structured output error, got {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_complete_chat_typed_with_reprompt() {
+        use crate::mock::{MockGrokServer, MockMethod};
+
+        let server = MockGrokServer::new();
+        server
+            .push_chat_response(response("It is sunny in Paris"))
+            .push_chat_response(response(r#"{"city": "Paris", "temperature": 20}"#));
+
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let weather = client
+            .complete_chat_typed_with_reprompt::<Weather>(
+                ChatRequest::new().user_message("Weather in Paris?"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(weather.value.city, "Paris");
+        assert_eq!(weather.usage.total_tokens, 30);
+        assert_eq!(server.call_count(MockMethod::GetCompletion), 2);
+    }
+}
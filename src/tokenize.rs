@@ -14,7 +14,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut client = GrokClient::from_env().await?;
+//!     let client = GrokClient::from_env().await?;
 //!
 //!     let request = TokenizeRequest::new("grok-2-1212")
 //!         .with_text("Hello, world!");
@@ -29,6 +29,19 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! To count many texts, [`GrokClient::tokenize_batch`] runs requests
+//! concurrently, and a [`TokenCounter`] adds a cache of token counts on top.
+
+use crate::{client::GrokClient, error::Result};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+/// Default number of concurrent requests of [`TokenCounter`].
+pub const DEFAULT_TOKENIZE_CONCURRENCY: usize = 8;
 
 /// A request to tokenize text
 ///
@@ -42,6 +55,7 @@
 ///     .with_user("user-123");
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TokenizeRequest {
     /// The text to tokenize
     pub text: String,
@@ -120,6 +134,7 @@ impl TokenizeRequest {
 /// - The string representation
 /// - The raw byte representation
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     /// The token's vocabulary ID
     pub token_id: u32,
@@ -133,6 +148,7 @@ pub struct Token {
 ///
 /// Contains the list of tokens and the model used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TokenizeResponse {
     /// The list of tokens
     pub tokens: Vec<Token>,
@@ -171,6 +187,234 @@ impl TokenizeResponse {
     }
 }
 
+/// Counts tokens for many texts, with concurrent requests and an optional
+/// cache of counts.
+///
+/// The cache is keyed by a hash of the model and the text, keeps the most
+/// recently used counts up to its capacity, and is shared by clones of the
+/// counter. Repeated texts, within a batch or across calls, are tokenized once.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xai_grpc_client::{GrokClient, TokenCounter};
+///
+/// # async fn example(documents: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = GrokClient::from_env().await?;
+/// let counter = TokenCounter::new().with_concurrency(16).with_cache(10_000);
+///
+/// let counts = counter.count_batch(&client, "grok-4", documents).await?;
+/// let total: u32 = counts.iter().sum();
+/// println!("{total} tokens");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TokenCounter {
+    concurrency: usize,
+    cache: Option<Arc<Mutex<CountCache>>>,
+}
+
+impl Default for TokenCounter {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_TOKENIZE_CONCURRENCY,
+            cache: None,
+        }
+    }
+}
+
+impl TokenCounter {
+    /// Create a counter with 8 concurrent requests and no cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of concurrent requests (default: 8).
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Cache up to `capacity` token counts, evicting the least recently used.
+    ///
+    /// A capacity of 0 disables the cache.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = (capacity > 0).then(|| Arc::new(Mutex::new(CountCache::new(capacity))));
+        self
+    }
+
+    /// Maximum number of concurrent requests.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Number of cached counts.
+    pub fn cached(&self) -> usize {
+        self.cache
+            .as_ref()
+            .map_or(0, |cache| cache.lock().unwrap().entries.len())
+    }
+
+    /// Remove every cached count.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    /// Count the tokens of `text` for `model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from [`GrokClient::tokenize`] if the count is not cached.
+    pub async fn count(&self, client: &GrokClient, model: &str, text: &str) -> Result<u32> {
+        let key = cache_key(model, text);
+        if let Some(count) = self.cached_count(key) {
+            return Ok(count);
+        }
+
+        let response = client
+            .tokenize(TokenizeRequest::new(model).with_text(text))
+            .await?;
+        let count = count_of(&response);
+        self.cache_count(key, count);
+        Ok(count)
+    }
+
+    /// Count the tokens of each of `texts` for `model`, in input order.
+    ///
+    /// Texts missing from the cache are tokenized concurrently with
+    /// [`GrokClient::tokenize_batch`], each distinct text once.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from [`GrokClient::tokenize`], in input order.
+    /// The counts of the texts that succeeded are cached all the same.
+    pub async fn count_batch<I>(
+        &self,
+        client: &GrokClient,
+        model: &str,
+        texts: I,
+    ) -> Result<Vec<u32>>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        // Each text's key, with its count if cached
+        let mut counts = Vec::new();
+        let mut requests = Vec::new();
+        let mut request_keys = Vec::new();
+        let mut pending = HashSet::new();
+
+        for text in texts {
+            let text = text.into();
+            let key = cache_key(model, &text);
+            let cached = self.cached_count(key);
+            if cached.is_none() && pending.insert(key) {
+                requests.push(TokenizeRequest::new(model).with_text(text));
+                request_keys.push(key);
+            }
+            counts.push((key, cached));
+        }
+
+        let responses = client.tokenize_batch(requests, self.concurrency).await;
+        let mut fetched = HashMap::with_capacity(responses.len());
+        let mut first_error = None;
+        for (key, response) in request_keys.into_iter().zip(responses) {
+            match response {
+                Ok(response) => {
+                    let count = count_of(&response);
+                    self.cache_count(key, count);
+                    fetched.insert(key, count);
+                }
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        if let Some(error) = first_error {
+            return Err(error);
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(|(key, cached)| cached.unwrap_or_else(|| fetched[&key]))
+            .collect())
+    }
+
+    fn cached_count(&self, key: u64) -> Option<u32> {
+        self.cache.as_ref()?.lock().unwrap().get(key)
+    }
+
+    fn cache_count(&self, key: u64, count: u32) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().insert(key, count);
+        }
+    }
+}
+
+/// Number of tokens in `response`, saturated to `u32`.
+fn count_of(response: &TokenizeResponse) -> u32 {
+    u32::try_from(response.token_count()).unwrap_or(u32::MAX)
+}
+
+/// Hash of `model` and `text`, identifying a count in the cache.
+fn cache_key(model: &str, text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Least recently used cache of token counts.
+#[derive(Debug)]
+struct CountCache {
+    capacity: usize,
+    /// Incremented on every access, to order entries by recency.
+    clock: u64,
+    /// Count and last access of each key.
+    entries: HashMap<u64, (u32, u64)>,
+    /// Key of each last access, oldest first.
+    recency: BTreeMap<u64, u64>,
+}
+
+impl CountCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: u64) -> Option<u32> {
+        let count = self.entries.get(&key)?.0;
+        self.insert(key, count);
+        Some(count)
+    }
+
+    fn insert(&mut self, key: u64, count: u32) {
+        self.clock += 1;
+        if let Some((_, last_used)) = self.entries.insert(key, (count, self.clock)) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(self.clock, key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,4 +527,127 @@ mod tests {
         assert_eq!(response.token_count(), 0);
         assert_eq!(response.text(), "");
     }
+
+    #[test]
+    fn test_count_cache_evicts_least_recently_used() {
+        let mut cache = CountCache::new(2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        assert_eq!(cache.get(1), Some(10));
+
+        cache.insert(3, 30);
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(1), Some(10));
+        assert_eq!(cache.get(3), Some(30));
+
+        // Updating an entry does not evict anything
+        cache.insert(3, 31);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.recency.len(), 2);
+        assert_eq!(cache.get(3), Some(31));
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_model() {
+        assert_eq!(cache_key("grok-4", "hi"), cache_key("grok-4", "hi"));
+        assert_ne!(cache_key("grok-4", "hi"), cache_key("grok-3", "hi"));
+    }
+
+    #[cfg(feature = "mock-server")]
+    fn tokens(count: usize) -> TokenizeResponse {
+        TokenizeResponse {
+            tokens: vec![
+                Token {
+                    token_id: 1,
+                    string_token: "x".to_string(),
+                    token_bytes: vec![120],
+                };
+                count
+            ],
+            model: "grok-4".to_string(),
+        }
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_tokenize_batch_preserves_order() {
+        use crate::mock::{MockGrokServer, MockMethod, Status};
+
+        let server = MockGrokServer::new();
+        server
+            .push_tokenize_response(tokens(1))
+            .push_error(MockMethod::TokenizeText, Status::invalid_argument("bad"))
+            .push_tokenize_response(tokens(3));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let requests = ["a", "b", "c"]
+            .into_iter()
+            .map(|text| TokenizeRequest::new("grok-4").with_text(text))
+            .collect();
+        let results = client.tokenize_batch(requests, 1).await;
+
+        // Errors take precedence over queued responses
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().unwrap().token_count(), 1);
+        assert_eq!(results[2].as_ref().unwrap().token_count(), 3);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_tokenize_batch_runs_concurrently() {
+        use crate::mock::MockGrokServer;
+        use std::time::{Duration, Instant};
+
+        let server = MockGrokServer::new();
+        server.set_latency(Duration::from_millis(200));
+        for _ in 0..4 {
+            server.push_tokenize_response(tokens(2));
+        }
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let requests = (0..4)
+            .map(|i| TokenizeRequest::new("grok-4").with_text(i.to_string()))
+            .collect();
+        let started = Instant::now();
+        let results = client.tokenize_batch(requests, 4).await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(Result::is_ok));
+        assert!(started.elapsed() < Duration::from_millis(800));
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_count_batch_uses_cache() {
+        use crate::mock::{MockGrokServer, MockMethod};
+
+        let server = MockGrokServer::new();
+        server
+            .push_tokenize_response(tokens(2))
+            .push_tokenize_response(tokens(3));
+        let handle = server.start().await.unwrap();
+        let client = handle.client().await.unwrap();
+
+        let counter = TokenCounter::new().with_concurrency(1).with_cache(10);
+        let counts = counter
+            .count_batch(&client, "grok-4", ["a", "b", "a"])
+            .await
+            .unwrap();
+        assert_eq!(counts, [2, 3, 2]);
+        assert_eq!(counter.cached(), 2);
+
+        // Served from the cache: no response is queued anymore
+        let counts = counter
+            .count_batch(&client, "grok-4", ["b", "a"])
+            .await
+            .unwrap();
+        assert_eq!(counts, [3, 2]);
+        assert_eq!(counter.count(&client, "grok-4", "a").await.unwrap(), 2);
+        assert_eq!(server.call_count(MockMethod::TokenizeText), 2);
+
+        // Other models are counted separately
+        assert!(counter.count(&client, "grok-3", "a").await.is_err());
+    }
 }
@@ -14,6 +14,10 @@
 //! - **MCP** - Model Context Protocol integration
 //! - **DocumentSearch** - Document retrieval from knowledge bases
 
+use crate::{
+    error::{GrokError, Result},
+    structured::{strict_schema, StructuredOutput},
+};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -52,6 +56,8 @@ use crate::proto::{
 /// let search_tool = Tool::WebSearch(WebSearchTool::new());
 /// ```
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Tool {
     /// Client-side function calling (like OpenAI).
     Function(FunctionTool),
@@ -88,6 +94,7 @@ impl Tool {
 
 /// Client-side function tool definition
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionTool {
     /// Name of the function
     pub name: String,
@@ -95,6 +102,11 @@ pub struct FunctionTool {
     pub description: String,
     /// JSON Schema describing the function parameters
     pub parameters: Value,
+    /// Whether the model must produce arguments that match the schema exactly.
+    ///
+    /// The schema is made strict when sent (see [`strict_schema`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strict: bool,
 }
 
 impl FunctionTool {
@@ -107,27 +119,76 @@ impl FunctionTool {
                 "type": "object",
                 "properties": {},
             }),
+            strict: false,
         }
     }
 
+    /// Create a function tool whose parameters schema is derived from the
+    /// argument type `T`.
+    ///
+    /// Parse the calls it receives with [`FunctionCall::parse_arguments`].
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(feature = "schemars", doc = "```no_run")]
+    #[cfg_attr(not(feature = "schemars"), doc = "```ignore")]
+    /// use serde::Deserialize;
+    /// use xai_grpc_client::{FunctionCall, FunctionTool};
+    ///
+    /// /// Arguments of `get_weather`.
+    /// #[derive(Deserialize, schemars::JsonSchema)]
+    /// struct WeatherArgs {
+    ///     /// City name
+    ///     location: String,
+    /// }
+    ///
+    /// let tool = FunctionTool::from_args::<WeatherArgs>("get_weather", "Get the weather")
+    ///     .with_strict(true);
+    ///
+    /// # fn handle(call: &FunctionCall) -> xai_grpc_client::Result<()> {
+    /// let args: WeatherArgs = call.parse_arguments()?;
+    /// println!("Weather in {}", args.location);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_args<T: StructuredOutput>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self::new(name, description).with_parameters(T::json_schema())
+    }
+
     /// Set the parameters JSON schema
     pub fn with_parameters(mut self, parameters: Value) -> Self {
         self.parameters = parameters;
         self
     }
 
+    /// Enable or disable strict argument validation by the model.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     fn to_proto(&self) -> ProtoFunction {
+        let parameters = if self.strict {
+            strict_schema(self.parameters.clone())
+        } else {
+            self.parameters.clone()
+        };
+
         ProtoFunction {
             name: self.name.clone(),
             description: self.description.clone(),
-            strict: false,
-            parameters: self.parameters.to_string(),
+            strict: self.strict,
+            parameters: parameters.to_string(),
         }
     }
 }
 
 /// Web search tool configuration
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WebSearchTool {
     /// Domains to exclude from results (max 5)
     pub excluded_domains: Vec<String>,
@@ -172,10 +233,19 @@ impl WebSearchTool {
 
 /// X (Twitter) search tool configuration
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XSearchTool {
     /// Start date for search results (ISO-8601)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_helpers::option_timestamp")
+    )]
     pub from_date: Option<prost_types::Timestamp>,
     /// End date for search results (ISO-8601)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_helpers::option_timestamp")
+    )]
     pub to_date: Option<prost_types::Timestamp>,
     /// Allowed X handles
     pub allowed_x_handles: Vec<String>,
@@ -237,6 +307,7 @@ impl XSearchTool {
 
 /// Collections search tool configuration
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CollectionsSearchTool {
     /// Collection IDs to search (max 10)
     pub collection_ids: Vec<String>,
@@ -269,6 +340,7 @@ impl CollectionsSearchTool {
 
 /// Model Context Protocol server configuration
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct McpTool {
     /// Label for the MCP server
     pub server_label: String,
@@ -341,6 +413,7 @@ impl McpTool {
 
 /// Document search tool configuration
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DocumentSearchTool {
     /// Number of files to limit search to
     pub limit: Option<i32>,
@@ -368,6 +441,8 @@ impl DocumentSearchTool {
 /// Controls whether the model can freely choose tools, must use a tool,
 /// or should call a specific function.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ToolChoice {
     /// Let the model decide whether to use tools.
     Auto,
@@ -399,6 +474,7 @@ impl ToolChoice {
 /// Contains information about which tool was called, its status,
 /// and the function details including arguments.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ToolCall {
     /// Unique identifier for this tool call.
     pub id: String,
@@ -448,6 +524,8 @@ impl ToolCall {
 
 /// Type of tool call
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ToolCallKind {
     /// Client-side function (maps to OpenAI's function_call)
     ClientSideTool,
@@ -495,6 +573,8 @@ impl ToolCallKind {
 
 /// Status of a tool call
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ToolCallStatusKind {
     /// Tool call is in progress
     InProgress,
@@ -529,6 +609,7 @@ impl ToolCallStatusKind {
 
 /// Function call details
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionCall {
     /// Name of the function to call
     pub name: String,
@@ -537,9 +618,18 @@ pub struct FunctionCall {
 }
 
 impl FunctionCall {
-    /// Parse arguments as JSON
-    pub fn parse_arguments<T: serde::de::DeserializeOwned>(&self) -> serde_json::Result<T> {
-        serde_json::from_str(&self.arguments)
+    /// Parse arguments into the argument type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GrokError::ToolArguments`] naming the function, the mismatch
+    /// and the raw arguments when they do not deserialize into `T`.
+    pub fn parse_arguments<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.arguments).map_err(|error| GrokError::ToolArguments {
+            function: self.name.clone(),
+            message: error.to_string(),
+            arguments: self.arguments.clone(),
+        })
     }
 
     /// Get arguments as a JSON value
@@ -616,6 +706,72 @@ mod tests {
 
         let json = call.arguments_json().unwrap();
         assert_eq!(json["param"], "value");
+
+        #[derive(serde::Deserialize)]
+        struct Args {
+            param: String,
+        }
+        assert_eq!(call.parse_arguments::<Args>().unwrap().param, "value");
+    }
+
+    #[test]
+    fn test_function_call_parse_arguments_error() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Args {
+            location: String,
+            days: u32,
+        }
+
+        let call = FunctionCall {
+            name: "get_forecast".to_string(),
+            arguments: r#"{"location": "Paris", "days": "three"}"#.to_string(),
+        };
+
+        let error = call.parse_arguments::<Args>().unwrap_err();
+        assert!(matches!(
+            &error,
+            GrokError::ToolArguments { function, arguments, .. }
+                if function == "get_forecast" && arguments.contains("three")
+        ));
+        let message = error.to_string();
+        assert!(message.contains("get_forecast"));
+        assert!(message.contains("invalid type"));
+    }
+
+    #[test]
+    fn test_function_tool_from_args() {
+        #[derive(serde::Deserialize)]
+        #[allow(dead_code)]
+        struct WeatherArgs {
+            location: String,
+            unit: Option<String>,
+        }
+
+        impl StructuredOutput for WeatherArgs {
+            fn json_schema() -> Value {
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "location": {"type": "string"},
+                        "unit": {"type": ["string", "null"]}
+                    },
+                    "required": ["location"]
+                })
+            }
+        }
+
+        let tool = FunctionTool::from_args::<WeatherArgs>("get_weather", "Get weather");
+        assert!(!tool.strict);
+        let proto = tool.to_proto();
+        assert!(!proto.strict);
+        assert!(!proto.parameters.contains("additionalProperties"));
+
+        let proto = tool.with_strict(true).to_proto();
+        assert!(proto.strict);
+        let parameters: Value = serde_json::from_str(&proto.parameters).unwrap();
+        assert_eq!(parameters["additionalProperties"], json!(false));
+        assert_eq!(parameters["required"].as_array().unwrap().len(), 2);
     }
 
     #[test]
@@ -639,4 +795,30 @@ mod tests {
 
         assert_eq!(tool.limit, Some(20));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_tools_serde_round_trip() {
+        let tools = vec![
+            Tool::Function(FunctionTool::new("get_weather", "Weather")),
+            Tool::CodeExecution,
+            Tool::XSearch(XSearchTool::new().with_date_range(
+                Some(prost_types::Timestamp {
+                    seconds: 1_700_000_000,
+                    nanos: 5,
+                }),
+                None,
+            )),
+        ];
+
+        let value = serde_json::to_value(&tools).unwrap();
+        assert_eq!(value[1], json!("code_execution"));
+        assert_eq!(
+            value[2]["x_search"]["from_date"],
+            json!({"seconds": 1_700_000_000, "nanos": 5})
+        );
+
+        let restored: Vec<Tool> = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), value);
+    }
 }
@@ -0,0 +1,733 @@
+//! Token usage and cost accounting.
+//!
+//! A [`UsageTracker`] set on [`GrokConfig::usage_tracker`](crate::GrokConfig::usage_tracker)
+//! records the [`TokenUsage`] of every chat completion, grouped by model, by
+//! the request's [`user`](crate::ChatRequest::with_user) tag and by time
+//! window. Costs are computed from the pricing returned by
+//! [`GrokClient::list_models`](crate::GrokClient::list_models), fetched once
+//! by [`UsageTracker::load_prices`] and cached. An optional budget makes the
+//! client reject requests once it has been spent; the client then loads the
+//! pricing itself before its first request.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xai_grpc_client::{ChatRequest, GrokClient, GrokConfig, UsageTracker};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let tracker = UsageTracker::new().with_budget_usd(500.0);
+//! let client = GrokClient::new(GrokConfig {
+//!     api_key: std::env::var("XAI_API_KEY")?.into(),
+//!     usage_tracker: Some(tracker.clone()),
+//!     ..Default::default()
+//! })
+//! .await?;
+//!
+//! let request = ChatRequest::new()
+//!     .user_message("Hello!")
+//!     .with_user("customer-42");
+//! client.complete_chat(request).await?;
+//!
+//! for (user, totals) in tracker.report().by_user() {
+//!     println!("{user:?}: ${:.4}", totals.cost_usd);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{GrokError, Result},
+    models::LanguageModel,
+    response::TokenUsage,
+    GrokClient,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::OnceCell;
+
+/// Records token usage and computes its cost.
+///
+/// Clones share the same records, prices and budget.
+#[derive(Clone, Debug)]
+pub struct UsageTracker {
+    window: Duration,
+    budget_usd: Option<f64>,
+    prices: Arc<OnceCell<HashMap<String, LanguageModel>>>,
+    records: Arc<Mutex<BTreeMap<RecordKey, Tokens>>>,
+}
+
+/// Records are grouped by window start (Unix seconds), model and user.
+type RecordKey = (u64, String, Option<String>);
+
+/// Token counts of one group of records.
+#[derive(Clone, Copy, Debug, Default)]
+struct Tokens {
+    requests: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    cached_prompt_tokens: u64,
+    prompt_image_tokens: u64,
+    reasoning_tokens: u64,
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageTracker {
+    /// Create a tracker with one-hour windows and no budget.
+    pub fn new() -> Self {
+        Self {
+            window: Duration::from_secs(3600),
+            budget_usd: None,
+            prices: Arc::new(OnceCell::new()),
+            records: Arc::default(),
+        }
+    }
+
+    /// Set the length of the time windows usage is grouped by.
+    ///
+    /// Windows are aligned on the Unix epoch, so a one-day window covers one
+    /// UTC day. Zero is treated as one second.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Reject requests with [`GrokError::BudgetExceeded`] once the tracked
+    /// cost reaches `budget_usd`.
+    ///
+    /// The budget is checked before each request, so the request that
+    /// crosses it still completes.
+    pub fn with_budget_usd(mut self, budget_usd: f64) -> Self {
+        self.budget_usd = Some(budget_usd);
+        self
+    }
+
+    /// Use the pricing of `models` instead of fetching it with `list_models`.
+    pub fn with_prices(self, models: Vec<LanguageModel>) -> Self {
+        Self {
+            prices: Arc::new(OnceCell::from(price_table(models))),
+            ..self
+        }
+    }
+
+    /// Fetch the model pricing with `client`, unless it was already loaded.
+    ///
+    /// Without a budget, call it before reading costs from a report; with
+    /// one, the client calls it before its first request.
+    pub async fn load_prices(&self, client: &GrokClient) -> Result<()> {
+        self.prices
+            .get_or_try_init(|| async { client.list_models().await.map(price_table) })
+            .await?;
+        Ok(())
+    }
+
+    /// Returns true if the model pricing has been loaded.
+    pub fn has_prices(&self) -> bool {
+        self.prices.initialized()
+    }
+
+    /// Record the usage of one request to `model`, tagged with `user`.
+    pub fn record(&self, model: &str, user: Option<&str>, usage: &TokenUsage) {
+        self.record_at(SystemTime::now(), model, user, usage);
+    }
+
+    fn record_at(&self, time: SystemTime, model: &str, user: Option<&str>, usage: &TokenUsage) {
+        let key = (
+            self.window_start(time),
+            model.to_string(),
+            user.map(str::to_string),
+        );
+
+        let mut records = self.records.lock().unwrap();
+        let tokens = records.entry(key).or_default();
+        tokens.requests += 1;
+        tokens.prompt_tokens += u64::from(usage.prompt_tokens);
+        tokens.completion_tokens += u64::from(usage.completion_tokens);
+        tokens.total_tokens += u64::from(usage.total_tokens);
+        tokens.cached_prompt_tokens += u64::from(usage.cached_prompt_tokens);
+        tokens.prompt_image_tokens += u64::from(usage.prompt_image_tokens);
+        tokens.reasoning_tokens += u64::from(usage.reasoning_tokens);
+    }
+
+    /// Total cost of the usage recorded so far, in USD.
+    ///
+    /// Usage of models without known pricing is not counted.
+    pub fn spent_usd(&self) -> f64 {
+        self.report().total().cost_usd
+    }
+
+    /// Fail with [`GrokError::BudgetExceeded`] if the budget has been spent.
+    pub fn check_budget(&self) -> Result<()> {
+        let Some(budget_usd) = self.budget_usd else {
+            return Ok(());
+        };
+
+        let spent_usd = self.spent_usd();
+        if spent_usd >= budget_usd {
+            return Err(GrokError::BudgetExceeded {
+                spent_usd,
+                budget_usd,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns true if a budget is configured.
+    pub(crate) fn has_budget(&self) -> bool {
+        self.budget_usd.is_some()
+    }
+
+    /// Report all the usage recorded so far.
+    pub fn report(&self) -> UsageReport {
+        self.report_filtered(|_| true)
+    }
+
+    /// Report the usage of the windows starting in `[from, to)`.
+    pub fn report_between(&self, from: SystemTime, to: SystemTime) -> UsageReport {
+        let (from, to) = (unix_secs(from), unix_secs(to));
+        self.report_filtered(|start| (from..to).contains(&start))
+    }
+
+    fn report_filtered(&self, include: impl Fn(u64) -> bool) -> UsageReport {
+        let prices = self.prices.get();
+        let records = self.records.lock().unwrap();
+
+        let entries = records
+            .iter()
+            .filter(|((start, _, _), _)| include(*start))
+            .map(|((start, model, user), tokens)| UsageEntry {
+                window_start: *start,
+                model: model.clone(),
+                user: user.clone(),
+                requests: tokens.requests,
+                prompt_tokens: tokens.prompt_tokens,
+                completion_tokens: tokens.completion_tokens,
+                total_tokens: tokens.total_tokens,
+                cached_prompt_tokens: tokens.cached_prompt_tokens,
+                prompt_image_tokens: tokens.prompt_image_tokens,
+                reasoning_tokens: tokens.reasoning_tokens,
+                cost_usd: prices
+                    .and_then(|prices| prices.get(model))
+                    .map(|model| tokens.cost_usd(model)),
+            })
+            .collect();
+
+        UsageReport { entries }
+    }
+
+    /// Forget all recorded usage, resetting the budget.
+    pub fn reset(&self) {
+        self.records.lock().unwrap().clear();
+    }
+
+    fn window_start(&self, time: SystemTime) -> u64 {
+        let secs = unix_secs(time);
+        secs - secs % self.window.as_secs()
+    }
+}
+
+impl Tokens {
+    fn cost_usd(&self, model: &LanguageModel) -> f64 {
+        model.tokens_cost(
+            self.prompt_tokens,
+            self.cached_prompt_tokens,
+            self.prompt_image_tokens,
+            self.completion_tokens + self.reasoning_tokens,
+        )
+    }
+}
+
+/// Index `models` by name and alias.
+fn price_table(models: Vec<LanguageModel>) -> HashMap<String, LanguageModel> {
+    let mut table = HashMap::new();
+    for model in models {
+        for alias in &model.aliases {
+            table.insert(alias.clone(), model.clone());
+        }
+        table.insert(model.name.clone(), model);
+    }
+    table
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Usage of one model by one user during one time window.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageEntry {
+    /// Start of the window, in seconds since the Unix epoch.
+    pub window_start: u64,
+    /// Model the requests were sent to.
+    pub model: String,
+    /// The requests' `user` tag, if any.
+    pub user: Option<String>,
+    /// Number of requests.
+    pub requests: u64,
+    /// Prompt tokens used.
+    pub prompt_tokens: u64,
+    /// Completion tokens used.
+    pub completion_tokens: u64,
+    /// Total tokens used.
+    pub total_tokens: u64,
+    /// Prompt tokens served from the prompt cache.
+    pub cached_prompt_tokens: u64,
+    /// Prompt tokens spent on images.
+    pub prompt_image_tokens: u64,
+    /// Tokens spent on reasoning.
+    pub reasoning_tokens: u64,
+    /// Cost in USD, or `None` if the model's pricing is unknown.
+    pub cost_usd: Option<f64>,
+}
+
+/// Usage summed over several [`UsageEntry`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageTotals {
+    /// Number of requests.
+    pub requests: u64,
+    /// Prompt tokens used.
+    pub prompt_tokens: u64,
+    /// Completion tokens used.
+    pub completion_tokens: u64,
+    /// Total tokens used.
+    pub total_tokens: u64,
+    /// Prompt tokens served from the prompt cache.
+    pub cached_prompt_tokens: u64,
+    /// Prompt tokens spent on images.
+    pub prompt_image_tokens: u64,
+    /// Tokens spent on reasoning.
+    pub reasoning_tokens: u64,
+    /// Cost in USD of the entries with known pricing.
+    pub cost_usd: f64,
+}
+
+impl std::ops::AddAssign<&UsageEntry> for UsageTotals {
+    fn add_assign(&mut self, entry: &UsageEntry) {
+        self.requests += entry.requests;
+        self.prompt_tokens += entry.prompt_tokens;
+        self.completion_tokens += entry.completion_tokens;
+        self.total_tokens += entry.total_tokens;
+        self.cached_prompt_tokens += entry.cached_prompt_tokens;
+        self.prompt_image_tokens += entry.prompt_image_tokens;
+        self.reasoning_tokens += entry.reasoning_tokens;
+        self.cost_usd += entry.cost_usd.unwrap_or_default();
+    }
+}
+
+/// A snapshot of recorded usage, ordered by window, model and user.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageReport {
+    /// One entry per window, model and user.
+    pub entries: Vec<UsageEntry>,
+}
+
+impl UsageReport {
+    /// Usage summed over every entry.
+    pub fn total(&self) -> UsageTotals {
+        let mut total = UsageTotals::default();
+        for entry in &self.entries {
+            total += entry;
+        }
+        total
+    }
+
+    /// Usage summed per model.
+    pub fn by_model(&self) -> BTreeMap<String, UsageTotals> {
+        self.group_by(|entry| entry.model.clone())
+    }
+
+    /// Usage summed per `user` tag.
+    pub fn by_user(&self) -> BTreeMap<Option<String>, UsageTotals> {
+        self.group_by(|entry| entry.user.clone())
+    }
+
+    fn group_by<K: Ord>(&self, key: impl Fn(&UsageEntry) -> K) -> BTreeMap<K, UsageTotals> {
+        let mut groups = BTreeMap::<K, UsageTotals>::new();
+        for entry in &self.entries {
+            *groups.entry(key(entry)).or_default() += entry;
+        }
+        groups
+    }
+
+    /// Render the entries as CSV, with a header row.
+    ///
+    /// Unknown costs are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "window_start,model,user,requests,prompt_tokens,completion_tokens,total_tokens,\
+             cached_prompt_tokens,prompt_image_tokens,reasoning_tokens,cost_usd\n",
+        );
+        for entry in &self.entries {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                entry.window_start,
+                csv_field(&entry.model),
+                csv_field(entry.user.as_deref().unwrap_or_default()),
+                entry.requests,
+                entry.prompt_tokens,
+                entry.completion_tokens,
+                entry.total_tokens,
+                entry.cached_prompt_tokens,
+                entry.prompt_image_tokens,
+                entry.reasoning_tokens,
+                entry
+                    .cost_usd
+                    .map(|cost| format!("{cost:.6}"))
+                    .unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Modality;
+
+    fn model(name: &str, alias: &str) -> LanguageModel {
+        LanguageModel {
+            name: name.to_string(),
+            aliases: vec![alias.to_string()],
+            version: "1.0".to_string(),
+            input_modalities: vec![Modality::Text],
+            output_modalities: vec![Modality::Text],
+            prompt_text_token_price: 20_000, // $2 per 1M tokens
+            prompt_image_token_price: 0,
+            cached_prompt_token_price: 5_000,
+            completion_text_token_price: 100_000, // $10 per 1M tokens
+            search_price: 0,
+            max_prompt_length: 131072,
+            system_fingerprint: "fp".to_string(),
+        }
+    }
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            ..Default::default()
+        }
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_groups_by_window_model_and_user() {
+        let tracker = UsageTracker::new().with_window(Duration::from_secs(100));
+
+        tracker.record_at(at(1_010), "grok-4", Some("acme"), &usage(100, 10));
+        tracker.record_at(at(1_090), "grok-4", Some("acme"), &usage(200, 20));
+        tracker.record_at(at(1_090), "grok-4", None, &usage(1, 1));
+        tracker.record_at(at(1_100), "grok-4", Some("acme"), &usage(300, 30));
+
+        let report = tracker.report();
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(report.entries[0].window_start, 1_000);
+        assert_eq!(report.entries[0].user, None);
+        assert_eq!(report.entries[1].requests, 2);
+        assert_eq!(report.entries[1].prompt_tokens, 300);
+        assert_eq!(report.entries[2].window_start, 1_100);
+
+        let by_user = report.by_user();
+        assert_eq!(by_user[&Some("acme".to_string())].requests, 3);
+        assert_eq!(by_user[&None].total_tokens, 2);
+        assert_eq!(report.total().requests, 4);
+
+        let window = tracker.report_between(at(1_100), at(1_200));
+        assert_eq!(window.entries.len(), 1);
+        assert_eq!(window.total().prompt_tokens, 300);
+    }
+
+    #[test]
+    fn test_costs_use_prices_by_name_and_alias() {
+        let tracker = UsageTracker::new().with_prices(vec![model("grok-4-0709", "grok-4")]);
+        assert!(tracker.has_prices());
+
+        tracker.record("grok-4", Some("acme"), &usage(1_000_000, 100_000));
+        tracker.record("grok-4-0709", Some("acme"), &usage(500_000, 0));
+        tracker.record("unknown-model", None, &usage(1_000, 1_000));
+
+        let by_model = tracker.report().by_model();
+        assert!((by_model["grok-4"].cost_usd - 3.0).abs() < 1e-9);
+        assert!((by_model["grok-4-0709"].cost_usd - 1.0).abs() < 1e-9);
+        assert_eq!(by_model["unknown-model"].cost_usd, 0.0);
+        assert!((tracker.spent_usd() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_costs_use_cached_and_reasoning_tokens() {
+        let tracker = UsageTracker::new().with_prices(vec![model("grok-4", "grok-4-latest")]);
+        tracker.record(
+            "grok-4",
+            None,
+            &TokenUsage {
+                prompt_tokens: 1_000_000,
+                completion_tokens: 50_000,
+                total_tokens: 1_100_000,
+                cached_prompt_tokens: 800_000,
+                reasoning_tokens: 50_000,
+                ..Default::default()
+            },
+        );
+
+        // 200K text * $2/1M + 800K cached * $0.50/1M + 100K completion * $10/1M
+        let total = tracker.report().total();
+        assert_eq!(total.cached_prompt_tokens, 800_000);
+        assert_eq!(total.reasoning_tokens, 50_000);
+        assert!((total.cost_usd - 1.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_of_counts_beyond_u32() {
+        let tokens = Tokens {
+            requests: 1,
+            prompt_tokens: 10_000_000_000,
+            completion_tokens: 0,
+            total_tokens: 10_000_000_000,
+            ..Default::default()
+        };
+        // 10B tokens at $2 per 1M tokens
+        assert!((tokens.cost_usd(&model("grok-4", "grok")) - 20_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_budget() {
+        let tracker = UsageTracker::new()
+            .with_prices(vec![model("grok-4", "grok-4-latest")])
+            .with_budget_usd(2.0);
+
+        tracker.record("grok-4", None, &usage(500_000, 0));
+        assert!(tracker.check_budget().is_ok());
+
+        tracker.record("grok-4", None, &usage(500_000, 0));
+        match tracker.check_budget() {
+            Err(GrokError::BudgetExceeded {
+                spent_usd,
+                budget_usd,
+            }) => {
+                assert!((spent_usd - 2.0).abs() < 1e-9);
+                assert_eq!(budget_usd, 2.0);
+            }
+            other => panic!("Expected budget error, got {other:?}"),
+        }
+
+        tracker.reset();
+        assert!(tracker.check_budget().is_ok());
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let tracker = UsageTracker::new()
+            .with_window(Duration::from_secs(60))
+            .with_prices(vec![model("grok-4", "grok-4-latest")]);
+        tracker.record_at(at(120), "grok-4", Some("acme, inc"), &usage(1_000_000, 0));
+        tracker.record_at(at(120), "other", None, &usage(1, 2));
+
+        assert_eq!(
+            tracker.report().to_csv(),
+            "window_start,model,user,requests,prompt_tokens,completion_tokens,total_tokens,\
+             cached_prompt_tokens,prompt_image_tokens,reasoning_tokens,cost_usd\n\
+             120,grok-4,\"acme, inc\",1,1000000,0,1000000,0,0,0,2.000000\n\
+             120,other,,1,1,2,3,0,0,0,\n"
+        );
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_client_records_chat_usage() {
+        use crate::{
+            mock::{MockGrokServer, MockMethod},
+            request::ChatRequest,
+            response::ChatResponse,
+            GrokConfig,
+        };
+
+        let server = MockGrokServer::new();
+        server.set_language_models(vec![model("grok-4-0709", "grok-4")]);
+        for _ in 0..2 {
+            server.push_chat_response(ChatResponse {
+                model: "grok-4-0709".to_string(),
+                usage: usage(1_000_000, 0),
+                ..ChatResponse::test_reply("req-1", "Hi")
+            });
+        }
+
+        let handle = server.start().await.unwrap();
+        let tracker = UsageTracker::new().with_budget_usd(1.0);
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                usage_tracker: Some(tracker.clone()),
+                ..Default::default()
+            },
+        );
+
+        let request = ChatRequest::new()
+            .user_message("Hello")
+            .with_model("grok-4")
+            .with_user("acme");
+        client.complete_chat(request.clone()).await.unwrap();
+
+        let report = tracker.report();
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].model, "grok-4-0709");
+        assert_eq!(report.entries[0].user.as_deref(), Some("acme"));
+        assert!((tracker.spent_usd() - 2.0).abs() < 1e-9);
+
+        // Over budget: rejected before reaching the server
+        assert!(matches!(
+            client.complete_chat(request).await,
+            Err(GrokError::BudgetExceeded { .. })
+        ));
+        assert_eq!(server.call_count(MockMethod::GetCompletion), 1);
+        assert_eq!(server.call_count(MockMethod::ListLanguageModels), 1);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_client_records_deferred_usage() {
+        use crate::{
+            mock::{MockGrokServer, MockMethod},
+            request::ChatRequest,
+            response::ChatResponse,
+            GrokConfig,
+        };
+
+        let server = MockGrokServer::new();
+        server
+            .set_language_models(vec![model("grok-4-0709", "grok-4")])
+            .set_deferred_pending_polls(1)
+            .push_chat_response(ChatResponse {
+                model: "grok-4-0709".to_string(),
+                usage: usage(1_000_000, 0),
+                ..ChatResponse::test_reply("req-1", "Hi")
+            });
+
+        let handle = server.start().await.unwrap();
+        let tracker = UsageTracker::new().with_budget_usd(1.0);
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                usage_tracker: Some(tracker.clone()),
+                ..Default::default()
+            },
+        );
+
+        let request = ChatRequest::new()
+            .user_message("Hello")
+            .with_model("grok-4")
+            .with_user("acme");
+        let request_id = client.start_deferred(request.clone()).await.unwrap();
+        assert!(client
+            .poll_deferred(request_id.clone())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(tracker.report().entries.is_empty());
+
+        client.poll_deferred(request_id).await.unwrap().unwrap();
+        let report = tracker.report();
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].model, "grok-4-0709");
+        assert_eq!(report.entries[0].user.as_deref(), Some("acme"));
+        assert_eq!(report.entries[0].prompt_tokens, 1_000_000);
+        assert!((tracker.spent_usd() - 2.0).abs() < 1e-9);
+
+        // The deferred spend counts towards the budget
+        assert!(matches!(
+            client.start_deferred(request).await,
+            Err(GrokError::BudgetExceeded { .. })
+        ));
+        assert_eq!(server.call_count(MockMethod::StartDeferredCompletion), 1);
+    }
+
+    #[cfg(feature = "mock-server")]
+    #[tokio::test]
+    async fn test_client_records_stream_usage_of_every_choice() {
+        use crate::{
+            mock::{MockGrokServer, MockMethod},
+            request::ChatRequest,
+            response::{ChatChunk, FinishReason},
+            GrokConfig,
+        };
+        use tokio_stream::StreamExt;
+
+        let chunk = |index, finish_reason, completion_tokens| ChatChunk {
+            request_id: "req-1".to_string(),
+            model: "grok-4-0709".to_string(),
+            created: None,
+            system_fingerprint: None,
+            index,
+            delta: "x".to_string(),
+            finish_reason,
+            cumulative_usage: usage(10, completion_tokens),
+            reasoning_delta: None,
+            tool_calls: vec![],
+            logprobs: None,
+            citations: vec![],
+            choices: vec![],
+        };
+
+        // The first choice finishes while the second one is still streaming
+        let server = MockGrokServer::new();
+        server.push_chat_stream(vec![
+            chunk(0, None, 1),
+            chunk(0, Some(FinishReason::Stop), 2),
+            chunk(1, None, 3),
+            chunk(1, Some(FinishReason::Stop), 4),
+        ]);
+
+        let handle = server.start().await.unwrap();
+        let tracker = UsageTracker::new();
+        let client = GrokClient::with_channel_and_config(
+            handle.channel().await.unwrap(),
+            GrokConfig {
+                usage_tracker: Some(tracker.clone()),
+                ..Default::default()
+            },
+        );
+
+        let request = ChatRequest::new()
+            .user_message("Hello")
+            .with_n(2)
+            .with_user("acme");
+        let mut stream = client.stream_chat(request).await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            chunk.unwrap();
+        }
+
+        let total = tracker.report().total();
+        assert_eq!(total.requests, 1);
+        assert_eq!(total.prompt_tokens, 10);
+        assert_eq!(total.completion_tokens, 4);
+
+        // Without a budget, the pricing is only fetched on request
+        assert_eq!(server.call_count(MockMethod::ListLanguageModels), 0);
+    }
+}